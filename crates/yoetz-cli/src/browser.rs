@@ -111,6 +111,9 @@ pub struct RecipeStep {
 
 pub struct RecipeContext {
     pub bundle_path: Option<String>,
+    /// Numbered parts of a split bundle. When non-empty, the built-in upload
+    /// actions attach these in order instead of `bundle_path`.
+    pub bundle_parts: Vec<String>,
     pub bundle_text: Option<String>,
     /// A built-in-owned prompt inserted after all other recipe interpolation so
     /// caller bytes such as `{{run_id}}` remain opaque.
@@ -126,6 +129,17 @@ pub struct RecipeContext {
     pub target_url: String,
 }
 
+impl RecipeContext {
+    /// Files the built-in upload actions attach, in upload order.
+    fn upload_paths(&self) -> Vec<&str> {
+        if self.bundle_parts.is_empty() {
+            self.bundle_path.as_deref().into_iter().collect()
+        } else {
+            self.bundle_parts.iter().map(String::as_str).collect()
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct BrowserDefaults {
     pub profile: Option<String>,
@@ -2082,45 +2096,54 @@ fn run_chatgpt_upload_bundle(
     use_stealth: bool,
     headed: bool,
 ) -> Result<String> {
-    let bundle_path = ctx
-        .bundle_path
-        .as_deref()
-        .context("ChatGPT upload requires `bundle_path` in the recipe context")?;
+    let upload_paths = ctx.upload_paths();
+    if upload_paths.is_empty() {
+        bail!("ChatGPT upload requires `bundle_path` in the recipe context");
+    }
     let marker_selector = format!(
         "input[type='file'][title='{}']",
         chatgpt_web::COMPOSER_FILE_INPUT_MARKER
     );
     let scope_expression = build_chatgpt_upload_scope_with_nudges_expression()?;
 
-    let scope_result = eval_chatgpt_upload_scope(
-        &scope_expression,
-        connection,
-        use_stealth,
-        headed,
-        CHATGPT_POLL_COMMAND_TIMEOUT_MS_DEFAULT,
-    )?;
-    if !chatgpt_upload_scope_is_marked(&scope_result) {
-        return Err(anyhow!(
-            "composer-scoped ChatGPT file input was not available for upload: {}",
-            scope_result
-        ));
-    }
+    // The composer may remount its file input after each attachment, so the
+    // input is re-scoped before every part.
+    for bundle_path in &upload_paths {
+        let scope_result = eval_chatgpt_upload_scope(
+            &scope_expression,
+            connection,
+            use_stealth,
+            headed,
+            CHATGPT_POLL_COMMAND_TIMEOUT_MS_DEFAULT,
+        )?;
+        if !chatgpt_upload_scope_is_marked(&scope_result) {
+            return Err(anyhow!(
+                "composer-scoped ChatGPT file input was not available for upload: {}",
+                scope_result
+            ));
+        }
 
-    run_agent_browser_with_connection_timeout(
-        vec![
-            "upload".to_string(),
-            marker_selector.clone(),
-            bundle_path.to_string(),
-        ],
-        OutputFormat::Text,
-        connection,
-        use_stealth,
-        headed,
-        Some(CHATGPT_POLL_COMMAND_TIMEOUT_MS_DEFAULT),
-    )
-    .with_context(|| format!("upload `{bundle_path}` through {marker_selector}"))?;
+        run_agent_browser_with_connection_timeout(
+            vec![
+                "upload".to_string(),
+                marker_selector.clone(),
+                bundle_path.to_string(),
+            ],
+            OutputFormat::Text,
+            connection,
+            use_stealth,
+            headed,
+            Some(CHATGPT_POLL_COMMAND_TIMEOUT_MS_DEFAULT),
+        )
+        .with_context(|| format!("upload `{bundle_path}` through {marker_selector}"))?;
+    }
 
-    Ok(json!({"status": "ok", "selector": marker_selector}).to_string())
+    Ok(json!({
+        "status": "ok",
+        "selector": marker_selector,
+        "files": upload_paths.len(),
+    })
+    .to_string())
 }
 
 fn build_chatgpt_upload_scope_with_nudges_expression() -> Result<String> {
@@ -2182,14 +2205,40 @@ fn run_chatgpt_wait_upload(
     let options = parse_upload_poll_options(args)?;
     let started_at = Instant::now();
     let deadline = started_at + Duration::from_millis(options.timeout_ms);
-    let bundle_path = ctx
-        .bundle_path
-        .as_deref()
-        .context("ChatGPT upload waiter requires `bundle_path` in the recipe context")?;
-    let file_name = Path::new(bundle_path)
-        .file_name()
-        .and_then(|value| value.to_str())
-        .context("bundle path must end in a UTF-8 filename")?;
+    let upload_paths = ctx.upload_paths();
+    if upload_paths.is_empty() {
+        bail!("ChatGPT upload waiter requires `bundle_path` in the recipe context");
+    }
+    let mut polls = 0;
+    for bundle_path in &upload_paths {
+        let file_name = Path::new(bundle_path)
+            .file_name()
+            .and_then(|value| value.to_str())
+            .context("bundle path must end in a UTF-8 filename")?;
+        polls += wait_for_chatgpt_attachment(
+            file_name,
+            &options,
+            started_at,
+            deadline,
+            connection,
+            use_stealth,
+            headed,
+        )?;
+    }
+    Ok(json!({"status": "ok", "polls": polls, "files": upload_paths.len()}).to_string())
+}
+
+/// Poll until the attachment chip for `file_name` is stably ready, returning
+/// the number of polls taken.
+fn wait_for_chatgpt_attachment(
+    file_name: &str,
+    options: &ChatgptPollOptions,
+    started_at: Instant,
+    deadline: Instant,
+    connection: Option<&BrowserConnection>,
+    use_stealth: bool,
+    headed: bool,
+) -> Result<usize> {
     let function = chatgpt_web::build_attachment_probe_function(file_name)?;
     let check_script = chatgpt_web::wrap_function_source_for_json_eval(&function)?;
 
@@ -2225,7 +2274,7 @@ fn run_chatgpt_wait_upload(
                     .and_then(Value::as_u64)
                     .unwrap_or(0);
                 if stable_ready_count >= chatgpt_web::CHATGPT_UPLOAD_STABLE_POLLS {
-                    return Ok(json!({"status": "ok", "polls": attempt}).to_string());
+                    return Ok(attempt);
                 }
             }
             "failed" => {
@@ -2499,29 +2548,31 @@ fn run_claude_upload_bundle(
     use_stealth: bool,
     headed: bool,
 ) -> Result<String> {
-    let bundle_path = ctx
-        .bundle_path
-        .as_deref()
-        .context("Claude upload requires `bundle_path` in the recipe context")?;
-    run_claude_open_attachment_ui(connection, use_stealth, headed)?;
+    let upload_paths = ctx.upload_paths();
+    if upload_paths.is_empty() {
+        bail!("Claude upload requires `bundle_path` in the recipe context");
+    }
     let selector = format!(
         "input[data-testid='file-upload'][title='{}']",
         claude_web::FILE_INPUT_MARKER
     );
-    run_agent_browser_with_connection_timeout(
-        vec![
-            "upload".to_string(),
-            selector.clone(),
-            bundle_path.to_string(),
-        ],
-        OutputFormat::Text,
-        connection,
-        use_stealth,
-        headed,
-        Some(CHATGPT_POLL_COMMAND_TIMEOUT_MS_DEFAULT),
-    )
-    .with_context(|| format!("upload `{bundle_path}` through {selector}"))?;
-    Ok(json!({"status":"ok","selector":selector}).to_string())
+    for bundle_path in &upload_paths {
+        run_claude_open_attachment_ui(connection, use_stealth, headed)?;
+        run_agent_browser_with_connection_timeout(
+            vec![
+                "upload".to_string(),
+                selector.clone(),
+                bundle_path.to_string(),
+            ],
+            OutputFormat::Text,
+            connection,
+            use_stealth,
+            headed,
+            Some(CHATGPT_POLL_COMMAND_TIMEOUT_MS_DEFAULT),
+        )
+        .with_context(|| format!("upload `{bundle_path}` through {selector}"))?;
+    }
+    Ok(json!({"status":"ok","selector":selector,"files":upload_paths.len()}).to_string())
 }
 
 fn run_claude_wait_upload(
@@ -2532,39 +2583,51 @@ fn run_claude_wait_upload(
     headed: bool,
 ) -> Result<String> {
     let options = parse_chatgpt_poll_args(CLAUDE_WAIT_UPLOAD_ACTION, args)?;
-    let bundle_path = ctx
-        .bundle_path
-        .as_deref()
-        .context("Claude upload waiter requires `bundle_path` in the recipe context")?;
-    let file_name = Path::new(bundle_path)
-        .file_name()
-        .and_then(|value| value.to_str())
-        .context("Claude bundle path must end in a UTF-8 filename")?;
-    let probe = claude_web::build_attachment_probe_function(file_name)?;
+    let upload_paths = ctx.upload_paths();
+    if upload_paths.is_empty() {
+        bail!("Claude upload waiter requires `bundle_path` in the recipe context");
+    }
     let started = Instant::now();
     let deadline = started + Duration::from_millis(options.timeout_ms);
-    let mut stable_candidates = 0_u8;
-    for attempt in 1..=options.attempts {
-        if Instant::now() >= deadline {
-            break;
-        }
-        if attempt > 1 {
-            thread::sleep(Duration::from_millis(options.interval_ms));
-        }
-        let state = run_claude_dom_function(&probe, connection, use_stealth, headed)?;
-        if state.get("status").and_then(Value::as_str) == Some("candidate") {
-            stable_candidates = stable_candidates.saturating_add(1);
-            if stable_candidates >= 2 {
-                return Ok(json!({"status":"ok","polls":attempt,"attachment":state}).to_string());
+    let mut polls = 0;
+    let mut attachments = Vec::with_capacity(upload_paths.len());
+    'files: for bundle_path in &upload_paths {
+        let file_name = Path::new(bundle_path)
+            .file_name()
+            .and_then(|value| value.to_str())
+            .context("Claude bundle path must end in a UTF-8 filename")?;
+        let probe = claude_web::build_attachment_probe_function(file_name)?;
+        let mut stable_candidates = 0_u8;
+        for attempt in 1..=options.attempts {
+            if Instant::now() >= deadline {
+                break;
+            }
+            if attempt > 1 {
+                thread::sleep(Duration::from_millis(options.interval_ms));
+            }
+            let state = run_claude_dom_function(&probe, connection, use_stealth, headed)?;
+            if state.get("status").and_then(Value::as_str) == Some("candidate") {
+                stable_candidates = stable_candidates.saturating_add(1);
+                if stable_candidates >= 2 {
+                    polls += attempt;
+                    attachments.push(state);
+                    continue 'files;
+                }
+            } else {
+                stable_candidates = 0;
             }
-        } else {
-            stable_candidates = 0;
         }
+        return Err(anyhow!(
+            "Claude attachment `{file_name}` did not become ready within {}ms",
+            options.timeout_ms
+        ));
     }
-    Err(anyhow!(
-        "Claude attachment `{file_name}` did not become ready within {}ms",
-        options.timeout_ms
-    ))
+    let attachment = if attachments.len() == 1 {
+        attachments.pop().unwrap_or(Value::Null)
+    } else {
+        Value::Array(attachments)
+    };
+    Ok(json!({"status":"ok","polls":polls,"attachment":attachment}).to_string())
 }
 
 fn run_claude_send(
@@ -5934,6 +5997,7 @@ mod tests {
     fn recipe_context() -> RecipeContext {
         RecipeContext {
             bundle_path: Some("/tmp/bundle.md".to_string()),
+            bundle_parts: Vec::new(),
             bundle_text: Some("hello world".to_string()),
             opaque_prompt: None,
             profile_dir: None,
@@ -7115,6 +7179,23 @@ browser_cdp = "http://evil.example.com:9222"
         assert!(err.to_string().contains("--var missing="));
     }
 
+    #[test]
+    fn upload_paths_prefer_split_parts_in_order() {
+        let mut ctx = recipe_context();
+        assert_eq!(ctx.upload_paths(), vec!["/tmp/bundle.md"]);
+        ctx.bundle_parts = vec![
+            "/tmp/bundle.part-1-of-2.md".to_string(),
+            "/tmp/bundle.part-2-of-2.md".to_string(),
+        ];
+        assert_eq!(
+            ctx.upload_paths(),
+            vec!["/tmp/bundle.part-1-of-2.md", "/tmp/bundle.part-2-of-2.md"]
+        );
+        ctx.bundle_parts.clear();
+        ctx.bundle_path = None;
+        assert!(ctx.upload_paths().is_empty());
+    }
+
     #[test]
     fn interpolate_json_filter_works_for_bundle_path() {
         let ctx = recipe_context();
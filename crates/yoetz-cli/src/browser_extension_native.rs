@@ -1844,6 +1844,60 @@ pub(crate) fn run_claude_recipe_with_lease(
     }
 }
 
+/// One job of a split-bundle upload: the part to attach and what to send
+/// with it.
+pub(crate) struct BundlePartJob {
+    pub(crate) bundle: PathBuf,
+    pub(crate) prompt: String,
+    pub(crate) run_id: String,
+    pub(crate) conversation_id: Option<String>,
+}
+
+/// Prompt sent with every part of a split bundle but the last.
+fn split_part_hold_prompt(part: usize, total: usize) -> String {
+    format!(
+        "This message carries part {part} of {total} of a file bundle; the remaining parts follow in the next messages. Do not answer yet. Reply with exactly OK."
+    )
+}
+
+/// Upload a split bundle one part per job, all in one conversation. The
+/// extension attaches a single file per job, so every part but the last goes
+/// with a hold prompt and the last carries `prompt`. Returns the last job's
+/// result.
+pub(crate) fn run_split_bundle(
+    parts: &[PathBuf],
+    prompt: &str,
+    run_id: &str,
+    conversation_id: Option<String>,
+    mut run: impl FnMut(BundlePartJob) -> Result<ExtensionRecipeResult>,
+) -> Result<ExtensionRecipeResult> {
+    let (last, earlier) = parts.split_last().context("split bundle has no parts")?;
+    let total = parts.len();
+    let mut conversation_id = conversation_id;
+    for (idx, part) in earlier.iter().enumerate() {
+        let number = idx + 1;
+        let result = run(BundlePartJob {
+            bundle: part.clone(),
+            prompt: split_part_hold_prompt(number, total),
+            run_id: format!("{run_id}-part-{number}"),
+            conversation_id: conversation_id.take(),
+        })
+        .with_context(|| format!("upload bundle part {number} of {total}"))?;
+        conversation_id = Some(result.conversation_id.with_context(|| {
+            format!("bundle part {number} of {total} reported no conversation id; cannot attach the remaining parts")
+        })?);
+    }
+    run(BundlePartJob {
+        bundle: last.clone(),
+        prompt: format!(
+            "The file bundle is attached in {total} parts across this conversation; this message carries the last part.\n\n{prompt}"
+        ),
+        run_id: run_id.to_string(),
+        conversation_id,
+    })
+    .with_context(|| format!("upload bundle part {total} of {total}"))
+}
+
 pub fn serve_native_host_chatgpt() -> Result<()> {
     #[cfg(unix)]
     {
@@ -4381,6 +4435,40 @@ mod tests {
     use serial_test::serial;
     use tempfile::TempDir;
 
+    #[test]
+    fn split_bundle_sends_parts_in_order_in_one_conversation() {
+        let parts: Vec<PathBuf> = (1..=3)
+            .map(|k| PathBuf::from(format!("bundle.part-{k}-of-3.md")))
+            .collect();
+        let mut jobs = Vec::new();
+        let result = run_split_bundle(&parts, "Review it.", "run-1", None, |job| {
+            let number = jobs.len() + 1;
+            jobs.push((job.bundle, job.prompt, job.run_id, job.conversation_id));
+            Ok(ExtensionRecipeResult {
+                response: format!("reply {number}"),
+                model_used: None,
+                model_selection_status: ChatgptModelSelectionStatus::Selected,
+                warnings: Vec::new(),
+                warning_details: Vec::new(),
+                conversation_id: Some("conv-1".to_string()),
+                conversation_url: None,
+                diagnostics: Default::default(),
+            })
+        })
+        .unwrap();
+        assert_eq!(result.response, "reply 3");
+
+        let bundles: Vec<&PathBuf> = jobs.iter().map(|job| &job.0).collect();
+        assert_eq!(bundles, parts.iter().collect::<Vec<_>>());
+        assert!(jobs[0].1.contains("part 1 of 3") && jobs[0].1.contains("Do not answer yet"));
+        assert!(jobs[2].1.ends_with("\n\nReview it."));
+        assert_eq!(jobs[0].2, "run-1-part-1");
+        assert_eq!(jobs[2].2, "run-1");
+        assert_eq!(jobs[0].3, None);
+        assert_eq!(jobs[1].3.as_deref(), Some("conv-1"));
+        assert_eq!(jobs[2].3.as_deref(), Some("conv-1"));
+    }
+
     struct EnvGuard {
         key: &'static str,
         old: Option<String>,
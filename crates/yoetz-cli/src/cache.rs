@@ -0,0 +1,265 @@
+//! Opt-in local response cache for `ask` and `review`.
+//!
+//! Entries live under `<yoetz dir>/cache/<aa>/<hash>.json`, keyed by the
+//! SHA-256 of the canonical JSON of everything sent to the provider (model,
+//! messages with the rendered bundle, media digests, temperature, output
+//! limit, response format). A repeated request is then answered without a
+//! provider call, which also makes runs replayable offline once recorded.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tempfile::NamedTempFile;
+use yoetz_core::config::{CacheConfig, CacheMode};
+use yoetz_core::media::MediaInput;
+use yoetz_core::session::yoetz_root_dir;
+use yoetz_core::types::Usage;
+
+const DEFAULT_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// Bumped when the key layout changes so old entries stop matching.
+const KEY_VERSION: u32 = 1;
+
+/// A stored provider answer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CachedResponse {
+    /// Unix seconds when the response was stored.
+    pub(crate) created_at: u64,
+    pub(crate) provider: Option<String>,
+    pub(crate) model: String,
+    pub(crate) content: String,
+    /// Usage reported by the original call.
+    pub(crate) usage: Usage,
+    pub(crate) response_id: Option<String>,
+}
+
+impl CachedResponse {
+    pub(crate) fn new(
+        provider: Option<String>,
+        model: String,
+        content: String,
+        usage: Usage,
+        response_id: Option<String>,
+    ) -> Self {
+        Self {
+            created_at: unix_now(),
+            provider,
+            model,
+            content,
+            usage: Usage {
+                attempts: Vec::new(),
+                ..usage
+            },
+            response_id,
+        }
+    }
+
+    /// Usage to report for a cache hit: the original token counts, no cost.
+    pub(crate) fn hit_usage(&self) -> Usage {
+        Usage {
+            cost_usd: Some(0.0),
+            ..self.usage.clone()
+        }
+    }
+}
+
+pub(crate) struct ResponseCache {
+    dir: PathBuf,
+    mode: CacheMode,
+    ttl: Option<Duration>,
+}
+
+impl ResponseCache {
+    /// `--cache` when given, else `[cache] mode`.
+    pub(crate) fn new(config: &CacheConfig, flag: Option<CacheMode>) -> Self {
+        Self::in_dir(yoetz_root_dir().join("cache"), config, flag)
+    }
+
+    fn in_dir(dir: PathBuf, config: &CacheConfig, flag: Option<CacheMode>) -> Self {
+        let ttl = match config.ttl_secs {
+            Some(0) => None,
+            Some(secs) => Some(Duration::from_secs(secs)),
+            None => Some(DEFAULT_TTL),
+        };
+        Self {
+            dir,
+            mode: flag.or(config.mode).unwrap_or_default(),
+            ttl,
+        }
+    }
+
+    pub(crate) fn enabled(&self) -> bool {
+        self.mode != CacheMode::Off
+    }
+
+    /// A fresh entry for `key`, when reading is enabled. Unreadable entries
+    /// are treated as misses.
+    pub(crate) fn lookup(&self, key: &str) -> Option<CachedResponse> {
+        if self.mode != CacheMode::Read {
+            return None;
+        }
+        let path = self.entry_path(key);
+        let text = fs::read_to_string(&path).ok()?;
+        let entry: CachedResponse = match serde_json::from_str(&text) {
+            Ok(entry) => entry,
+            Err(err) => {
+                eprintln!(
+                    "warning: ignoring unreadable cache entry {}: {err}",
+                    path.display()
+                );
+                return None;
+            }
+        };
+        let age = Duration::from_secs(unix_now().saturating_sub(entry.created_at));
+        if self.ttl.is_some_and(|ttl| age > ttl) {
+            return None;
+        }
+        Some(entry)
+    }
+
+    /// Store `entry` under `key`, replacing any previous entry.
+    pub(crate) fn store(&self, key: &str, entry: &CachedResponse) -> Result<()> {
+        if !self.enabled() {
+            return Ok(());
+        }
+        let path = self.entry_path(key);
+        let parent = path.parent().unwrap_or(&self.dir);
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+        let mut tmp = NamedTempFile::new_in(parent)?;
+        tmp.write_all(serde_json::to_string_pretty(entry)?.as_bytes())?;
+        tmp.persist(&path)
+            .with_context(|| format!("write {}", path.display()))?;
+        Ok(())
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        let shard = key.get(..2).unwrap_or("00");
+        self.dir.join(shard).join(format!("{key}.json"))
+    }
+}
+
+/// Hex SHA-256 of `request` serialized with sorted object keys.
+pub(crate) fn request_key(command: &str, request: &Value) -> String {
+    let mut canonical = format!("yoetz-cache-v{KEY_VERSION}:{command}:");
+    write_canonical(request, &mut canonical);
+    hex::encode(Sha256::digest(canonical.as_bytes()))
+}
+
+/// Content digest for a media input, so the key tracks file contents rather
+/// than paths. URLs are keyed as given.
+pub(crate) fn media_digest(media: &MediaInput) -> Result<String> {
+    let data = media.as_data_url()?;
+    Ok(hex::encode(Sha256::digest(data.as_bytes())))
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (idx, key) in keys.into_iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (idx, item) in items.iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(content: &str) -> CachedResponse {
+        CachedResponse::new(
+            Some("openrouter".to_string()),
+            "model-a".to_string(),
+            content.to_string(),
+            Usage {
+                input_tokens: Some(10),
+                cost_usd: Some(0.5),
+                ..Usage::default()
+            },
+            None,
+        )
+    }
+
+    #[test]
+    fn request_key_ignores_object_key_order() {
+        let a = json!({"model": "m", "temperature": 0.1, "messages": [{"role": "user", "content": "hi"}]});
+        let b = json!({"messages": [{"content": "hi", "role": "user"}], "temperature": 0.1, "model": "m"});
+        assert_eq!(request_key("ask", &a), request_key("ask", &b));
+        assert_ne!(request_key("ask", &a), request_key("review", &a));
+        let c = json!({"model": "m", "temperature": 0.2, "messages": [{"role": "user", "content": "hi"}]});
+        assert_ne!(request_key("ask", &a), request_key("ask", &c));
+    }
+
+    #[test]
+    fn read_mode_serves_fresh_entries_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = CacheConfig::default();
+        let writer =
+            ResponseCache::in_dir(dir.path().to_path_buf(), &config, Some(CacheMode::Write));
+        writer.store("abcdef", &entry("cached answer")).unwrap();
+        assert!(writer.lookup("abcdef").is_none(), "write mode never reads");
+
+        let reader =
+            ResponseCache::in_dir(dir.path().to_path_buf(), &config, Some(CacheMode::Read));
+        let hit = reader.lookup("abcdef").unwrap();
+        assert_eq!(hit.content, "cached answer");
+        assert_eq!(hit.hit_usage().cost_usd, Some(0.0));
+        assert_eq!(hit.hit_usage().input_tokens, Some(10));
+        assert!(reader.lookup("missing").is_none());
+
+        let mut stale = entry("old");
+        stale.created_at -= DEFAULT_TTL.as_secs() + 1;
+        reader.store("abcdef", &stale).unwrap();
+        assert!(reader.lookup("abcdef").is_none());
+        let forever = ResponseCache::in_dir(
+            dir.path().to_path_buf(),
+            &CacheConfig {
+                ttl_secs: Some(0),
+                ..config
+            },
+            Some(CacheMode::Read),
+        );
+        assert!(forever.lookup("abcdef").is_some());
+    }
+
+    #[test]
+    fn off_mode_neither_reads_nor_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::in_dir(dir.path().to_path_buf(), &CacheConfig::default(), None);
+        assert!(!cache.enabled());
+        cache.store("abcdef", &entry("x")).unwrap();
+        assert!(fs::read_dir(dir.path()).unwrap().next().is_none());
+    }
+}
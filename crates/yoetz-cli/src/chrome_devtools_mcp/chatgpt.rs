@@ -348,14 +348,17 @@ async fn run_attached_recipe_inner(
     // `take_snapshot` to find the input's uid, then `upload_file` against
     // that uid.
     //
-    let bundle_path = ctx
-        .bundle_path
-        .as_ref()
-        .context("ChatGPT recipe requires a bundle file path")?;
-    try_upload_bundle(client, bundle_path, ctx.upload_timeout_ms)
-        .await
-        .with_chatgpt_phase(chatgpt_recipe::ChatgptTransportPhase::Upload)
-        .context("upload bundle to ChatGPT")?;
+    // A split bundle attaches every part, in order, before the prompt.
+    let upload_paths = ctx.upload_paths();
+    if upload_paths.is_empty() {
+        return Err(anyhow!("ChatGPT recipe requires a bundle file path"));
+    }
+    for bundle_path in upload_paths {
+        try_upload_bundle(client, bundle_path, ctx.upload_timeout_ms)
+            .await
+            .with_chatgpt_phase(chatgpt_recipe::ChatgptTransportPhase::Upload)
+            .with_context(|| format!("upload bundle `{}` to ChatGPT", bundle_path.display()))?;
+    }
 
     // Step 4: type the delivery text into the focused composer.
     //
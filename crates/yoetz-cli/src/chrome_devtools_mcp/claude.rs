@@ -110,10 +110,17 @@ async fn run_with_client(
         bail!("Claude Fable 5 / Max selection was not verified")
     }
 
-    upload_bundle(client, bundle_path, ctx.upload_timeout_ms)
-        .await
-        .with_claude_phase(WebRecipeTransportPhase::Upload)
-        .context("upload bundle to Claude")?;
+    let upload_paths = if ctx.bundle_parts.is_empty() {
+        vec![bundle_path]
+    } else {
+        ctx.upload_paths()
+    };
+    for path in upload_paths {
+        upload_bundle(client, path, ctx.upload_timeout_ms)
+            .await
+            .with_claude_phase(WebRecipeTransportPhase::Upload)
+            .with_context(|| format!("upload bundle `{}` to Claude", path.display()))?;
+    }
 
     client
         .evaluate_script(&claude_web::build_focus_composer_function(), vec![])
@@ -38,7 +38,7 @@ pub mod claude;
 use crate::chatgpt_web;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
 pub enum RecipeThreadMode {
@@ -72,6 +72,11 @@ pub struct DevtoolsMcpRecipeContext {
     /// does not fall back to paste mode.
     pub bundle_path: Option<PathBuf>,
 
+    /// Numbered parts of a split bundle, attached in order instead of
+    /// `bundle_path` when non-empty.
+    #[serde(default)]
+    pub bundle_parts: Vec<PathBuf>,
+
     /// Reserved for compatibility with other recipe contexts. The current
     /// live-attach Chrome transport does not use inline bundle text.
     pub bundle_text: Option<String>,
@@ -107,11 +112,24 @@ pub struct DevtoolsMcpRecipeContext {
     pub show_approval_guidance: bool,
 }
 
+impl DevtoolsMcpRecipeContext {
+    /// Files to attach, in upload order: the split parts when present,
+    /// otherwise the bundle itself.
+    pub fn upload_paths(&self) -> Vec<&Path> {
+        if self.bundle_parts.is_empty() {
+            self.bundle_path.as_deref().into_iter().collect()
+        } else {
+            self.bundle_parts.iter().map(PathBuf::as_path).collect()
+        }
+    }
+}
+
 impl Default for DevtoolsMcpRecipeContext {
     fn default() -> Self {
         Self {
             cdp_endpoint: None,
             bundle_path: None,
+            bundle_parts: Vec::new(),
             bundle_text: None,
             model: String::new(),
             prompt: String::new(),
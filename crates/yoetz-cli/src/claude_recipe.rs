@@ -129,7 +129,7 @@ pub fn inline_size_warnings(
             bundle_path.display()
         )
     })?;
-    let estimated_tokens = yoetz_core::bundle::estimate_tokens(contents.chars().count());
+    let estimated_tokens = yoetz_core::tokenizer::count_tokens(Some("claude"), &contents);
     if estimated_tokens <= inline_warn_tokens {
         return Ok(Vec::new());
     }
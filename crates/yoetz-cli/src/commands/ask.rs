@@ -1,20 +1,32 @@
 use anyhow::{anyhow, Result};
 
+use crate::cache::{media_digest, request_key, CachedResponse, ResponseCache};
+use crate::conversation::{AskLineage, Conversation, Part, Turn};
+use crate::include_cmd::run_include_cmds;
 use crate::notifications;
-use crate::providers::{gemini, openai};
+use crate::providers::{chat_stream, gemini, openai};
+use crate::retry::{call_with_retry, fallback_targets, FallbackLimits, ModelTarget, PartialOutput};
+use crate::tools::ToolSet;
 use crate::{
-    apply_capability_warnings, call_model, maybe_write_output, normalize_model_name_with_aliases,
-    parse_media_input, parse_media_inputs, resolve_max_output_tokens_for_provider, resolve_prompt,
-    resolve_provider_for_model, resolve_registry_model_id, resolve_response_format,
-    validate_cursor_options, AppContext, AskArgs,
+    apply_capability_warnings, apply_since_session, call_model, chat_completions_auth,
+    git_selector_from_flags, maybe_write_output, normalize_model_name_with_aliases,
+    parse_media_input, parse_media_inputs, preset_bundle_options, resolve_bundle_format,
+    resolve_bundle_token_budget, resolve_conversation_prompt,
+    resolve_max_output_tokens_for_provider, resolve_provider_for_model, resolve_registry_model_id,
+    resolve_response_format, validate_cursor_options, warn_bundle_adjustments, wire_model,
+    write_session_bundle, AppContext, AskArgs, CallResult,
 };
-use crate::{budget, providers, registry};
+use crate::{budget, prompt_cache, providers, registry, tools};
+use serde_json::{json, Value};
 use std::env;
-use std::time::Instant;
-use yoetz_core::bundle::{build_bundle, estimate_tokens, BundleOptions};
-use yoetz_core::media::MediaType;
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use yoetz_core::bundle::{build_bundle, BundleOptions};
+use yoetz_core::media::{MediaInput, MediaType};
 use yoetz_core::output::{write_json, write_jsonl, OutputFormat};
-use yoetz_core::session::{create_session_dir, write_json as write_json_file, write_text};
+use yoetz_core::session::{create_session_dir, session_base_dir, write_json as write_json_file};
+use yoetz_core::tokenizer::Tokenizer;
 use yoetz_core::types::{ArtifactPaths, PricingEstimate, RunResult, Usage};
 
 fn enforce_multimodal_budget_support(
@@ -51,13 +63,254 @@ fn enforce_multimodal_budget_support(
     ))
 }
 
+/// The turn recorded for this run's prompt; images are kept as data URLs so
+/// a continued session can replay them.
+fn user_turn(conversation: &Conversation, prompt: &str, images: &[MediaInput]) -> Turn {
+    let mut turn = conversation.prompt_turn(prompt);
+    turn.parts.extend(
+        images
+            .iter()
+            .filter_map(|image| image.as_data_url().ok())
+            .map(Part::Image),
+    );
+    turn
+}
+
+/// Write one streamed chunk: a `delta` event for JSONL, raw text otherwise.
+fn emit_delta(format: OutputFormat, text: &str) -> Result<()> {
+    if matches!(format, OutputFormat::Jsonl) {
+        return write_jsonl("delta", &json!({ "text": text }));
+    }
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(text.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Text-only `ask --stream` call. OpenAI-compatible providers stream over
+/// `/chat/completions`; anything else falls back to a regular call whose
+/// answer is emitted as a single delta.
+async fn stream_ask(
+    ctx: &AppContext,
+    provider: &str,
+    model: &str,
+    conversation: &Conversation,
+    prompt: &str,
+    temperature: f32,
+    max_output_tokens: Option<usize>,
+    response_format: Option<Value>,
+    format: OutputFormat,
+) -> Result<CallResult> {
+    let Some(auth) = chat_completions_auth(&ctx.config, provider) else {
+        eprintln!(
+            "warning: --stream is not supported for provider {provider}; the answer is printed when it completes"
+        );
+        let route = prompt_cache::breakpoint_route(
+            &ctx.config,
+            provider,
+            model,
+            conversation,
+            response_format.as_ref(),
+        );
+        let call = match route {
+            Some(route) => {
+                prompt_cache::complete(
+                    &route,
+                    provider,
+                    model,
+                    conversation,
+                    prompt,
+                    temperature,
+                    max_output_tokens,
+                    response_format,
+                    ctx.timeout_duration,
+                )
+                .await?
+            }
+            None => {
+                call_model(
+                    &ctx.litellm,
+                    ctx.timeout_duration,
+                    &ctx.cursor_discovery,
+                    Some(provider),
+                    model,
+                    conversation,
+                    prompt,
+                    temperature,
+                    max_output_tokens,
+                    response_format,
+                    &[],
+                    None,
+                )
+                .await?
+            }
+        };
+        emit_delta(format, &call.content)?;
+        return Ok(call);
+    };
+
+    let wire_model = wire_model(provider, model)?;
+    let mut emitted = false;
+    let result = chat_stream::stream_chat_completion(
+        &auth,
+        &wire_model,
+        prompt_cache::chat_messages(conversation, prompt, &wire_model),
+        temperature,
+        max_output_tokens,
+        response_format,
+        ctx.timeout_duration,
+        |text| {
+            emitted = true;
+            emit_delta(format, text)
+        },
+    )
+    .await
+    .map_err(|err| {
+        if emitted {
+            err.context(PartialOutput)
+        } else {
+            err
+        }
+    })?;
+    Ok(CallResult {
+        content: result.content,
+        usage: result.usage,
+        response_id: result.response_id,
+        header_cost: None,
+    })
+}
+
+/// `ask` with image or video inputs: OpenAI and Gemini are called directly,
+/// other providers through litellm.
+async fn call_media(
+    ctx: &AppContext,
+    session_dir: Option<&Path>,
+    provider: &str,
+    model: &str,
+    conversation: &Conversation,
+    prompt: &str,
+    images: &[MediaInput],
+    video: Option<&MediaInput>,
+    temperature: f32,
+    max_output_tokens: Option<usize>,
+    response_format: Option<Value>,
+) -> Result<CallResult> {
+    if video.is_some() && provider != "gemini" {
+        return Err(anyhow!(
+            "video inputs are only supported for provider gemini"
+        ));
+    }
+    if matches!(provider, "openai" | "gemini") && !conversation.turns.is_empty() {
+        return Err(anyhow!(
+            "--messages cannot be combined with --image/--video for provider {provider}; put the images in the transcript instead"
+        ));
+    }
+    let prompt = conversation.prompt_text(prompt);
+    let prompt = prompt.as_ref();
+    match provider {
+        "openai" => {
+            let auth = providers::resolve_provider_auth(&ctx.config, provider)?;
+            let result = openai::call_responses_vision(
+                &ctx.client,
+                &auth,
+                conversation.system.as_deref(),
+                prompt,
+                model,
+                images,
+                response_format,
+                temperature,
+                max_output_tokens,
+            )
+            .await?;
+            Ok(CallResult {
+                content: result.content,
+                usage: result.usage,
+                response_id: result.response_id,
+                header_cost: None,
+            })
+        }
+        "gemini" => {
+            let auth = providers::resolve_provider_auth(&ctx.config, provider)?;
+            let result = gemini::generate_content(
+                &ctx.client,
+                &auth,
+                conversation.system.as_deref(),
+                prompt,
+                model,
+                images,
+                video,
+                temperature,
+                max_output_tokens,
+            )
+            .await?;
+            if ctx.debug || env::var("YOETZ_GEMINI_DEBUG").ok().as_deref() == Some("1") {
+                if let Some(dir) = session_dir {
+                    let _ = write_json_file(&dir.join("gemini_response.json"), &result.raw);
+                }
+            }
+            Ok(CallResult {
+                content: result.content,
+                usage: result.usage,
+                response_id: None,
+                header_cost: None,
+            })
+        }
+        _ => {
+            call_model(
+                &ctx.litellm,
+                ctx.timeout_duration,
+                &ctx.cursor_discovery,
+                Some(provider),
+                model,
+                conversation,
+                prompt,
+                temperature,
+                max_output_tokens,
+                response_format,
+                images,
+                video,
+            )
+            .await
+        }
+    }
+}
+
 pub(crate) async fn handle_ask(
     ctx: &AppContext,
     args: AskArgs,
     format: OutputFormat,
 ) -> Result<()> {
+    if args.stream && matches!(format, OutputFormat::Json) {
+        return Err(anyhow!("--stream needs --format text, markdown or jsonl"));
+    }
     let started_at = Instant::now();
-    let prompt = resolve_prompt(args.prompt.clone(), args.prompt_file.clone())?;
+    let (conversation, prompt) = resolve_conversation_prompt(
+        args.prompt.clone(),
+        args.prompt_file.clone(),
+        args.system.clone(),
+        args.system_file.as_deref(),
+        args.messages.as_deref(),
+    )?;
+    let lineage = AskLineage::resolve(
+        args.continue_from.as_deref(),
+        args.thread.as_deref(),
+        &session_base_dir(),
+    )?;
+    let mut conversation = conversation;
+    if let Some(parent) = &lineage.parent {
+        let history = parent.conversation()?;
+        conversation.turns = history.turns;
+        if conversation.system.is_none() {
+            conversation.system = history.system;
+        }
+        eprintln!(
+            "Continuing session {} ({} prior messages)",
+            parent.session_id,
+            conversation.turns.len()
+        );
+    }
+    // Reuse the parent's model only when the caller did not pick one.
+    let inherited = lineage.parent.as_ref().filter(|_| args.model.is_none());
     let config = &ctx.config;
     let response_format = resolve_response_format(
         args.response_format.clone(),
@@ -77,59 +330,36 @@ pub(crate) async fn handle_ask(
         )?),
         None => None,
     };
-
-    let include_files = args.files.clone();
-    let exclude_files = args.exclude.clone();
-
-    let bundle = if include_files.is_empty() {
-        None
-    } else {
-        let options = BundleOptions {
-            include: include_files,
-            exclude: exclude_files,
-            max_file_bytes: args.max_file_bytes,
-            max_total_bytes: args.max_total_bytes,
-            ..Default::default()
-        };
-        Some(build_bundle(&prompt, options)?)
-    };
-
-    // --no-session (or `[sessions] no_session = true` from trusted config)
-    // skips the session directory and every artifact write; stdout output is
-    // unchanged apart from the empty artifact paths.
-    let no_session = args.no_session || config.sessions.no_session.unwrap_or(false);
-    let session = if no_session {
-        None
-    } else {
-        Some(create_session_dir()?)
-    };
-    let run_id = session
-        .as_ref()
-        .map(|s| s.id.clone())
-        .unwrap_or_else(yoetz_core::session::new_session_id);
-    let mut artifacts = ArtifactPaths {
-        session_dir: session
-            .as_ref()
-            .map(|s| s.path.to_string_lossy().to_string())
-            .unwrap_or_default(),
-        ..Default::default()
-    };
-
-    if let (Some(session), Some(bundle_ref)) = (&session, &bundle) {
-        let bundle_json = session.path.join("bundle.json");
-        let bundle_md = session.path.join("bundle.md");
-        write_json_file(&bundle_json, bundle_ref)?;
-        write_text(&bundle_md, &crate::render_bundle_md(bundle_ref))?;
-        artifacts.bundle_json = Some(bundle_json.to_string_lossy().to_string());
-        artifacts.bundle_md = Some(bundle_md.to_string_lossy().to_string());
+    let mut tools = args
+        .tools
+        .as_deref()
+        .map(|path| {
+            ToolSet::load(
+                path,
+                &args.allow_tool,
+                args.tool_max_steps,
+                Duration::from_secs(args.tool_timeout),
+                args.max_cost_usd,
+            )
+        })
+        .transpose()?;
+    if tools.is_some() && (!image_inputs.is_empty() || video_input.is_some()) {
+        return Err(anyhow!(
+            "--tools cannot be combined with --image or --video"
+        ));
     }
 
     let model_id = args
         .model
         .clone()
+        .or_else(|| inherited.and_then(|parent| parent.model.clone()))
         .or(config.defaults.model.clone())
         .map(|m| normalize_model_name_with_aliases(&m, &config.aliases));
-    let provider_id = args.provider.clone().or(config.defaults.provider.clone());
+    let provider_id = args
+        .provider
+        .clone()
+        .or_else(|| inherited.and_then(|parent| parent.provider.clone()))
+        .or(config.defaults.provider.clone());
     let registry_cache = registry::load_registry_with_auto_sync(&ctx.client, &ctx.config)
         .await
         .ok()
@@ -158,15 +388,103 @@ pub(crate) async fn handle_ask(
         provider_id.as_deref(),
         max_output_tokens,
         response_format.as_ref(),
-        !image_inputs.is_empty() || video_input.is_some(),
+        !image_inputs.is_empty() || video_input.is_some() || conversation.has_images(),
         args.temperature,
         args.max_cost_usd,
         args.daily_budget_usd,
     )?;
+    let token_budget = resolve_bundle_token_budget(
+        args.token_budget,
+        args.fit_context,
+        registry_cache.as_ref(),
+        registry_model_id.as_deref(),
+        max_output_tokens,
+    )?;
+
+    let tokenizer = Tokenizer::for_model(registry_model_id.as_deref().or(model_id.as_deref()));
+    let (selection, preset_format) = preset_bundle_options(
+        config,
+        args.preset.as_deref(),
+        args.files.clone(),
+        args.exclude.clone(),
+        args.max_file_bytes,
+        args.max_total_bytes,
+    )?;
+    let git_selector =
+        git_selector_from_flags(args.changed_since.clone(), args.staged, args.worktree);
+
+    let bundle = if selection.include.is_empty()
+        && !selection.include_all
+        && git_selector.is_none()
+        && args.include_cmd.is_empty()
+    {
+        None
+    } else {
+        let options = BundleOptions {
+            token_budget,
+            git_selector,
+            include_diff: args.include_diff,
+            outline_oversized: args.outline,
+            outline_globs: args.outline_glob,
+            follow_imports: args.follow_imports,
+            redact_secrets: ctx.config.redaction.enabled.unwrap_or(true),
+            secret_rules: ctx.config.redaction.rules.clone(),
+            fail_on_secrets: args.fail_on_secrets,
+            commands: run_include_cmds(
+                &args.include_cmd,
+                Duration::from_secs(args.include_cmd_timeout),
+                args.include_cmd_max_bytes,
+            )?,
+            tokenizer,
+            ..selection
+        };
+        let mut bundle = build_bundle(&prompt, options)?;
+        bundle.line_numbers = args.line_numbers;
+        warn_bundle_adjustments(&bundle);
+        apply_since_session(&mut bundle, args.since_session.as_deref())?;
+        Some(bundle)
+    };
+
+    // --no-session (or `[sessions] no_session = true` from trusted config)
+    // skips the session directory and every artifact write; stdout output is
+    // unchanged apart from the empty artifact paths.
+    let no_session = args.no_session || config.sessions.no_session.unwrap_or(false);
+    if no_session && lineage.thread_label.is_some() {
+        return Err(anyhow!(
+            "--thread records each turn in a session; drop --no-session"
+        ));
+    }
+    let session = if no_session {
+        None
+    } else {
+        Some(create_session_dir()?)
+    };
+    let run_id = session
+        .as_ref()
+        .map(|s| s.id.clone())
+        .unwrap_or_else(yoetz_core::session::new_session_id);
+    let mut artifacts = ArtifactPaths {
+        session_dir: session
+            .as_ref()
+            .map(|s| s.path.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        ..Default::default()
+    };
+
+    let bundle_format = resolve_bundle_format(
+        args.bundle_format.or(preset_format),
+        config,
+        provider_id.as_deref(),
+    );
+    if let (Some(session), Some(bundle_ref)) = (&session, &bundle) {
+        write_session_bundle(&session.path, bundle_ref, &[bundle_format], &mut artifacts)?;
+    }
+
     let input_tokens = bundle
         .as_ref()
         .map(|b| b.stats.estimated_tokens)
-        .unwrap_or_else(|| estimate_tokens(prompt.len()));
+        .unwrap_or_else(|| tokenizer.count(&prompt))
+        + tokenizer.count(&conversation.text());
     let output_tokens = max_output_tokens.unwrap_or(4096);
     let mut pricing = if let Some(model_id) = registry_model_id.as_deref() {
         registry::estimate_pricing(
@@ -194,13 +512,72 @@ pub(crate) async fn handle_ask(
         args.allow_uncosted,
         &mut pricing,
     )?;
+    // Fallbacks are compared per request, and the tool loop charges one
+    // request's estimate when no cost is reported; the budget covers the
+    // whole loop.
+    let request_estimate_usd = pricing.estimate_usd;
+    if let Some(tools) = &mut tools {
+        tools.request_estimate_usd = request_estimate_usd;
+        // Each tool step sends the conversation again, so reserve for every
+        // step plus the final answer.
+        let requests = tools.max_steps + 1;
+        pricing.estimate_usd = pricing.estimate_usd.map(|usd| usd * f64::from(requests));
+        pricing.warnings.push(format!(
+            "the estimate covers {requests} requests (--tool-max-steps plus the final answer) at the initial size; tool results make later requests larger"
+        ));
+    }
+
+    let prompt_cache = args.prompt_cache || config.defaults.prompt_cache.unwrap_or(false);
+    let model_prompt = match &bundle {
+        Some(bundle_ref) if prompt_cache => {
+            let split = bundle_format.render_split(bundle_ref);
+            conversation.context = Some(split.context);
+            split.prompt
+        }
+        Some(bundle_ref) => bundle_format.render(bundle_ref),
+        None => prompt.clone(),
+    };
+    if let (false, Some(provider), Some(model)) =
+        (args.dry_run, provider_id.as_deref(), model_id.as_deref())
+    {
+        if let Some(warning) = prompt_cache::unsent_breakpoint_warning(
+            config,
+            provider,
+            model,
+            &conversation,
+            response_format.as_ref(),
+        ) {
+            eprintln!("warning: {warning}");
+        }
+    }
+
+    let has_media = !image_inputs.is_empty() || video_input.is_some();
+    let cache = ResponseCache::new(&config.cache, args.cache);
+    // Tool results depend on the local tree, so tool runs are never cached.
+    let cache_key = if cache.enabled() && !args.dry_run && tools.is_none() {
+        let request = json!({
+            "provider": provider_id,
+            "model": model_id,
+            "messages": conversation.chat_messages(&model_prompt),
+            "images": image_inputs.iter().map(media_digest).collect::<Result<Vec<_>>>()?,
+            "video": video_input.as_ref().map(media_digest).transpose()?,
+            "temperature": args.temperature,
+            "max_output_tokens": max_output_tokens,
+            "response_format": response_format,
+        });
+        Some(request_key("ask", &request))
+    } else {
+        None
+    };
+    let cache_hit = cache_key.as_deref().and_then(|key| cache.lookup(key));
 
     let budget_enabled = args.max_cost_usd.is_some() || args.daily_budget_usd.is_some();
     // Media inputs have no pre-call cost estimate. When the caller opted in with
     // --allow-uncosted we skip the pre-call reservation entirely (ensure_budget
     // requires an estimate) and rely on recording the real provider cost later.
     let uncosted_media = (!image_inputs.is_empty() || video_input.is_some()) && args.allow_uncosted;
-    let budget_reservation = if budget_enabled && !uncosted_media {
+    // A cache hit costs nothing, so it needs no reservation.
+    let budget_reservation = if budget_enabled && !uncosted_media && cache_hit.is_none() {
         budget::ensure_budget(
             pricing.estimate_usd,
             args.max_cost_usd,
@@ -210,12 +587,8 @@ pub(crate) async fn handle_ask(
         None
     };
 
-    let model_prompt = if let Some(bundle_ref) = &bundle {
-        crate::render_bundle_md(bundle_ref)
-    } else {
-        prompt.clone()
-    };
-
+    let streamed = args.stream && !args.dry_run && !has_media && cache_hit.is_none();
+    let mut answered_by: Option<ModelTarget> = None;
     let (content, mut usage, response_id, header_cost) = if args.dry_run {
         (
             "(dry-run) no provider call executed".to_string(),
@@ -223,97 +596,173 @@ pub(crate) async fn handle_ask(
             None,
             None,
         )
-    } else if !image_inputs.is_empty() || video_input.is_some() {
+    } else if let Some(hit) = &cache_hit {
+        answered_by = Some(ModelTarget {
+            provider: hit.provider.clone(),
+            model: hit.model.clone(),
+            max_output_tokens,
+        });
+        (
+            hit.content.clone(),
+            hit.hit_usage(),
+            hit.response_id.clone(),
+            None,
+        )
+    } else {
         let provider = provider_id
             .as_deref()
             .ok_or_else(|| anyhow!("provider is required"))?;
         let model = model_id
             .as_deref()
             .ok_or_else(|| anyhow!("model is required"))?;
-        if video_input.is_some() && provider != "gemini" {
-            return Err(anyhow!(
-                "video inputs are only supported for provider gemini"
-            ));
-        }
-        match provider {
-            "openai" => {
-                if video_input.is_some() {
-                    return Err(anyhow!("openai provider does not support video inputs"));
+        let mut targets = vec![ModelTarget {
+            provider: Some(provider.to_string()),
+            model: model.to_string(),
+            max_output_tokens,
+        }];
+        targets.extend(fallback_targets(
+            ctx,
+            &args.fallback_models,
+            Some(provider),
+            &[model],
+            &FallbackLimits {
+                registry: registry_cache.as_ref(),
+                max_output_tokens: args.max_output_tokens,
+                input_tokens,
+                budget_usd: budget_enabled.then_some(request_estimate_usd),
+                response_format: response_format.as_ref(),
+                has_media: has_media || conversation.has_images(),
+                temperature: args.temperature,
+            },
+        )?);
+
+        let session_dir = session.as_ref().map(|s| s.path.as_path());
+        let (conversation, model_prompt) = (&conversation, model_prompt.as_str());
+        let (images, video) = (image_inputs.as_slice(), video_input.as_ref());
+        let (temperature, stream) = (args.temperature, args.stream);
+        let tool_log = session_dir
+            .filter(|_| tools.is_some())
+            .map(|dir| dir.join("tool_calls.jsonl"));
+        let call = |target: &ModelTarget| {
+            let provider = target.provider.clone().unwrap_or_default();
+            let model = target.model.clone();
+            let max_output_tokens = target.max_output_tokens;
+            let response_format = response_format.clone();
+            async move {
+                if has_media {
+                    call_media(
+                        ctx,
+                        session_dir,
+                        &provider,
+                        &model,
+                        conversation,
+                        model_prompt,
+                        images,
+                        video,
+                        temperature,
+                        max_output_tokens,
+                        response_format,
+                    )
+                    .await
+                } else if stream {
+                    stream_ask(
+                        ctx,
+                        &provider,
+                        &model,
+                        conversation,
+                        model_prompt,
+                        temperature,
+                        max_output_tokens,
+                        response_format,
+                        format,
+                    )
+                    .await
+                } else if let Some(route) = prompt_cache::breakpoint_route(
+                    &ctx.config,
+                    &provider,
+                    &model,
+                    conversation,
+                    response_format.as_ref(),
+                ) {
+                    prompt_cache::complete(
+                        &route,
+                        &provider,
+                        &model,
+                        conversation,
+                        model_prompt,
+                        temperature,
+                        max_output_tokens,
+                        response_format,
+                        ctx.timeout_duration,
+                    )
+                    .await
+                } else {
+                    call_model(
+                        &ctx.litellm,
+                        ctx.timeout_duration,
+                        &ctx.cursor_discovery,
+                        Some(&provider),
+                        &model,
+                        conversation,
+                        model_prompt,
+                        temperature,
+                        max_output_tokens,
+                        response_format,
+                        &[],
+                        None,
+                    )
+                    .await
                 }
-                let auth = providers::resolve_provider_auth(config, provider)?;
-                let result = openai::call_responses_vision(
-                    &ctx.client,
-                    &auth,
-                    &model_prompt,
-                    model,
-                    &image_inputs,
+            }
+        };
+        let outcome = match &tools {
+            Some(tools) => {
+                tools::run_loop(
+                    ctx,
+                    &targets,
+                    tools,
+                    prompt_cache::chat_messages(conversation, model_prompt, model),
+                    temperature,
                     response_format.clone(),
-                    args.temperature,
-                    max_output_tokens,
+                    tool_log.as_deref(),
                 )
-                .await?;
-                (result.content, result.usage, result.response_id, None)
+                .await
             }
-            "gemini" => {
-                let auth = providers::resolve_provider_auth(config, provider)?;
-                let result = gemini::generate_content(
-                    &ctx.client,
-                    &auth,
-                    &model_prompt,
-                    model,
-                    &image_inputs,
-                    video_input.as_ref(),
-                    args.temperature,
-                    max_output_tokens,
-                )
-                .await?;
-                if ctx.debug || env::var("YOETZ_GEMINI_DEBUG").ok().as_deref() == Some("1") {
-                    if let Some(session) = &session {
-                        let raw_path = session.path.join("gemini_response.json");
-                        let _ = write_json_file(&raw_path, &result.raw);
-                    }
+            None => call_with_retry(&ctx.retry, &targets, call).await,
+        };
+        if let Some(path) = tool_log.filter(|path| path.exists()) {
+            artifacts.tool_calls = Some(path.to_string_lossy().to_string());
+        }
+        let attempts = outcome.recorded_attempts();
+        let (target, mut result) = match outcome.result {
+            Ok(answered) => answered,
+            Err(err) => {
+                if let Some(session) = &session {
+                    write_json_file(&session.path.join("attempts.json"), &outcome.attempts)?;
                 }
-                (result.content, result.usage, None, None)
+                return Err(err);
             }
-            _ => {
-                let call = call_model(
-                    &ctx.litellm,
-                    ctx.timeout_duration,
-                    &ctx.cursor_discovery,
-                    Some(provider),
-                    model,
-                    &model_prompt,
-                    args.temperature,
-                    max_output_tokens,
-                    response_format.clone(),
-                    &image_inputs,
-                    video_input.as_ref(),
-                )
-                .await?;
-                (call.content, call.usage, call.response_id, call.header_cost)
+        };
+        if target.model != model {
+            pricing.warnings.push(format!(
+                "answered by fallback model {}; the estimate is for {model}",
+                target.model
+            ));
+        }
+        if let Some(key) = &cache_key {
+            let entry = CachedResponse::new(
+                target.provider.clone(),
+                target.model.clone(),
+                result.content.clone(),
+                result.usage.clone(),
+                result.response_id.clone(),
+            );
+            if let Err(e) = cache.store(key, &entry) {
+                eprintln!("warning: response cache write failed: {e}");
             }
         }
-    } else {
-        let provider = provider_id
-            .as_deref()
-            .ok_or_else(|| anyhow!("provider is required"))?;
-        let model = model_id
-            .as_deref()
-            .ok_or_else(|| anyhow!("model is required"))?;
-        let result = call_model(
-            &ctx.litellm,
-            ctx.timeout_duration,
-            &ctx.cursor_discovery,
-            Some(provider),
-            model,
-            &model_prompt,
-            args.temperature,
-            max_output_tokens,
-            response_format.clone(),
-            &[],
-            None,
-        )
-        .await?;
+        answered_by = Some(target);
+        result.usage.attempts = attempts;
         (
             result.content,
             result.usage,
@@ -321,6 +770,14 @@ pub(crate) async fn handle_ask(
             result.header_cost,
         )
     };
+    let (provider_id, model_id) = match answered_by {
+        Some(target) => (target.provider, Some(target.model)),
+        None => (provider_id, model_id),
+    };
+
+    if args.stream && !streamed {
+        emit_delta(format, &content)?;
+    }
 
     if usage.cost_usd.is_none() {
         usage.cost_usd = header_cost;
@@ -338,6 +795,19 @@ pub(crate) async fn handle_ask(
         }
     }
 
+    // The pre-call estimate prices cached input at the full rate.
+    if usage.cost_usd.is_none()
+        && (usage.cache_read_tokens.is_some() || usage.cache_write_tokens.is_some())
+    {
+        if let Some(id) = resolve_registry_model_id(
+            provider_id.as_deref(),
+            model_id.as_deref(),
+            registry_cache.as_ref(),
+        ) {
+            usage.cost_usd = registry::usage_cost(registry_cache.as_ref(), &id, &usage);
+        }
+    }
+
     if provider_id.as_deref() == Some("gemini") && content.trim().is_empty() {
         if let Some(thoughts) = usage.thoughts_tokens.filter(|t| *t > 0) {
             let model_max_hint = registry_model_id
@@ -385,12 +855,28 @@ pub(crate) async fn handle_ask(
         usage,
         content,
         artifacts,
+        cached: cache_hit.is_some(),
     };
 
     if let Some(session) = &session {
         let response_json = session.path.join("response.json");
         result.artifacts.response_json = Some(response_json.to_string_lossy().to_string());
         write_json_file(&response_json, &result)?;
+        if !args.dry_run {
+            let mut messages: Vec<Value> = conversation.turns.iter().map(Turn::to_json).collect();
+            if conversation.sends_prompt(&model_prompt) || !image_inputs.is_empty() {
+                messages.push(user_turn(&conversation, &model_prompt, &image_inputs).to_json());
+            }
+            lineage.write(
+                &session.path,
+                &result.id,
+                result.provider.as_deref(),
+                result.model.as_deref(),
+                conversation.system.as_deref(),
+                messages,
+                &result.content,
+            )?;
+        }
     }
 
     maybe_write_output(ctx, &result)?;
@@ -408,6 +894,22 @@ pub(crate) async fn handle_ask(
     // Omit bundle from stdout to keep JSON output compact (full result is in session file)
     result.bundle = None;
 
+    if args.stream {
+        // The answer has already been written as deltas.
+        return match format {
+            OutputFormat::Jsonl => {
+                write_jsonl("usage", &result.usage)?;
+                write_jsonl("final", &result)
+            }
+            _ => {
+                if !result.content.ends_with('\n') {
+                    println!();
+                }
+                Ok(())
+            }
+        };
+    }
+
     match format {
         OutputFormat::Json => write_json(&result),
         OutputFormat::Jsonl => write_jsonl("ask", &result),
@@ -1,11 +1,18 @@
 use anyhow::{bail, Result};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use time::{format_description::FormatItem, macros::format_description, OffsetDateTime};
 
-use crate::{maybe_write_output, render_bundle_md, resolve_prompt, AppContext, BundleArgs};
+use crate::include_cmd::run_include_cmds;
+use crate::{
+    apply_since_session, git_selector_from_flags, maybe_write_output, preset_bundle_options,
+    resolve_prompt, warn_bundle_adjustments, AppContext, BundleArgs,
+};
 use yoetz_core::bundle::{build_bundle, BundleOptions};
 use yoetz_core::output::{write_json, write_jsonl, OutputFormat};
+use yoetz_core::render::{split_markdown, BundleFormat};
 use yoetz_core::session::{create_session_dir, write_json as write_json_file, write_text};
-use yoetz_core::types::{ArtifactPaths, BundleResult};
+use yoetz_core::types::{ArtifactPaths, Bundle, BundlePart, BundleResult};
 
 pub(crate) fn handle_bundle(
     ctx: &AppContext,
@@ -13,17 +20,38 @@ pub(crate) fn handle_bundle(
     format: OutputFormat,
 ) -> Result<()> {
     let prompt = resolve_prompt(args.prompt, args.prompt_file)?;
+    let (selection, preset_format) = preset_bundle_options(
+        &ctx.config,
+        args.preset.as_deref(),
+        args.files,
+        args.exclude,
+        args.max_file_bytes,
+        args.max_total_bytes,
+    )?;
     let options = BundleOptions {
-        include: args.files,
-        exclude: args.exclude,
-        max_file_bytes: args.max_file_bytes,
-        max_total_bytes: args.max_total_bytes,
-        include_all: args.all,
-        include_hidden: args.include_hidden || args.all,
-        ..Default::default()
+        include_all: selection.include_all || args.all,
+        include_hidden: selection.include_hidden || args.include_hidden || args.all,
+        token_budget: args.token_budget,
+        git_selector: git_selector_from_flags(args.changed_since, args.staged, args.worktree),
+        include_diff: args.include_diff,
+        outline_oversized: args.outline,
+        outline_globs: args.outline_glob,
+        follow_imports: args.follow_imports,
+        redact_secrets: ctx.config.redaction.enabled.unwrap_or(true),
+        secret_rules: ctx.config.redaction.rules.clone(),
+        fail_on_secrets: args.fail_on_secrets,
+        commands: run_include_cmds(
+            &args.include_cmd,
+            Duration::from_secs(args.include_cmd_timeout),
+            args.include_cmd_max_bytes,
+        )?,
+        ..selection
     };
 
-    let bundle = build_bundle(&prompt, options)?;
+    let mut bundle = build_bundle(&prompt, options)?;
+    bundle.line_numbers = args.line_numbers;
+    warn_bundle_adjustments(&bundle);
+    apply_since_session(&mut bundle, args.since_session.as_deref())?;
     let session = create_session_dir()?;
 
     let bundle_json = session.path.join("bundle.json");
@@ -31,8 +59,22 @@ pub(crate) fn handle_bundle(
         .path
         .join(bundle_file_name(args.name.as_deref(), &prompt)?);
 
+    let markdown = BundleFormat::Markdown.render(&bundle);
+    if let Some(max_bytes) = args.split_max_bytes {
+        let max_bytes = usize::try_from(max_bytes).unwrap_or(usize::MAX);
+        if markdown.len() > max_bytes {
+            bundle.parts = write_bundle_parts(&bundle, &bundle_md, max_bytes)?;
+        }
+    }
     write_json_file(&bundle_json, &bundle)?;
-    write_text(&bundle_md, &render_bundle_md(&bundle))?;
+    write_text(&bundle_md, &markdown)?;
+    let mut bundle_rendered = Vec::new();
+    let bundle_format = args.bundle_format.or(preset_format);
+    if let Some(format) = bundle_format.filter(|f| *f != BundleFormat::Markdown) {
+        let path = bundle_md.with_extension(format.renderer().extension());
+        write_text(&path, &format.render(&bundle))?;
+        bundle_rendered.push(path.to_string_lossy().to_string());
+    }
 
     let result = BundleResult {
         id: session.id,
@@ -43,6 +85,8 @@ pub(crate) fn handle_bundle(
             bundle_md: Some(bundle_md.to_string_lossy().to_string()),
             response_json: None,
             media_dir: None,
+            bundle_rendered,
+            tool_calls: None,
         },
     };
 
@@ -52,16 +96,82 @@ pub(crate) fn handle_bundle(
         OutputFormat::Json => write_json(&result),
         OutputFormat::Jsonl => write_jsonl("bundle", &result),
         OutputFormat::Text => {
-            println!("Bundle created at {}", result.artifacts.session_dir);
+            println!(
+                "Bundle created at {}{}",
+                result.artifacts.session_dir,
+                parts_note(&result.bundle)
+            );
             Ok(())
         }
         OutputFormat::Markdown => {
-            println!("Bundle created at `{}`", result.artifacts.session_dir);
+            println!(
+                "Bundle created at `{}`{}",
+                result.artifacts.session_dir,
+                parts_note(&result.bundle)
+            );
             Ok(())
         }
     }
 }
 
+/// Build a bundle from the `[bundles.<preset>]` config preset alone and write
+/// it to a new session, returning the Markdown path for `browser recipe`.
+pub(crate) fn write_preset_bundle(ctx: &AppContext, preset: &str, prompt: &str) -> Result<PathBuf> {
+    let (selection, _) = preset_bundle_options(
+        &ctx.config,
+        Some(preset),
+        Vec::new(),
+        Vec::new(),
+        None,
+        None,
+    )?;
+    let options = BundleOptions {
+        redact_secrets: ctx.config.redaction.enabled.unwrap_or(true),
+        secret_rules: ctx.config.redaction.rules.clone(),
+        ..selection
+    };
+    let bundle = build_bundle(prompt, options)?;
+    warn_bundle_adjustments(&bundle);
+    let session = create_session_dir()?;
+    let bundle_md = session.path.join(bundle_file_name(Some(preset), prompt)?);
+    write_json_file(&session.path.join("bundle.json"), &bundle)?;
+    write_text(&bundle_md, &BundleFormat::Markdown.render(&bundle))?;
+    Ok(bundle_md)
+}
+
+fn parts_note(bundle: &Bundle) -> String {
+    match bundle.parts.len() {
+        0 => String::new(),
+        count => format!(" (split into {count} parts)"),
+    }
+}
+
+/// Write `<stem>.part-K-of-N.md` siblings of `bundle_md` and return their manifest.
+fn write_bundle_parts(
+    bundle: &Bundle,
+    bundle_md: &Path,
+    max_bytes: usize,
+) -> Result<Vec<BundlePart>> {
+    let parts = split_markdown(bundle, max_bytes)?;
+    let stem = bundle_md
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("bundle");
+    let count = parts.len();
+    let mut manifest = Vec::with_capacity(count);
+    for (idx, part) in parts.into_iter().enumerate() {
+        let file_name = format!("{stem}.part-{}-of-{count}.md", idx + 1);
+        write_text(&bundle_md.with_file_name(&file_name), &part.text)?;
+        manifest.push(BundlePart {
+            index: idx + 1,
+            file_name,
+            bytes: part.text.len(),
+            files: part.files,
+        });
+    }
+    Ok(manifest)
+}
+
 const BUNDLE_TIMESTAMP_FORMAT: &[FormatItem<'static>] =
     format_description!("[year][month][day]-[hour][minute][second]Z");
 
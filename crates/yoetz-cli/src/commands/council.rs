@@ -1,23 +1,37 @@
 use anyhow::{anyhow, Result};
 
+use crate::consensus::{Consensus, ConsensusSchema};
+use crate::conversation::Conversation;
+use crate::include_cmd::run_include_cmds;
 use crate::notifications;
+use crate::prompt_cache::BreakpointRoute;
+use crate::retry::{call_with_retry, fallback_targets, FallbackLimits, ModelTarget};
 use crate::{
-    add_usage, call_model, maybe_write_output, normalize_model_name_with_aliases, render_bundle_md,
-    resolve_max_output_tokens_for_provider, resolve_prompt, resolve_provider_for_model,
-    resolve_registry_model_id, resolve_response_format, validate_cursor_options, AppContext,
-    CouncilArgs, CouncilModelArtifact, CouncilModelResult, CouncilPricing, CouncilSummary,
-    ModelEstimate, PartialPolicy,
+    add_usage, apply_since_session, call_model, git_selector_from_flags, maybe_write_output,
+    normalize_model_name_with_aliases, preset_bundle_options, resolve_bundle_format,
+    resolve_bundle_token_budget, resolve_conversation_prompt,
+    resolve_max_output_tokens_for_provider, resolve_provider_for_model, resolve_registry_model_id,
+    resolve_response_format, validate_cursor_options, warn_bundle_adjustments,
+    write_session_bundle, AppContext, CallResult, CouncilArgs, CouncilModelArtifact,
+    CouncilModelResult, CouncilPricing, CouncilRound, CouncilSummary, CouncilSynthesis,
+    ModelEstimate, PartialPolicy, SynthesisSource,
 };
-use crate::{budget, registry};
+use crate::{budget, prompt_cache, registry};
 use crate::{CouncilModelError, CouncilResult};
-use std::collections::{BTreeMap, BTreeSet};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
+use std::io::{IsTerminal, Write};
 use std::path::{Path, PathBuf};
-use std::time::Instant;
-use yoetz_core::bundle::{build_bundle, estimate_tokens, BundleOptions};
+use std::time::{Duration, Instant};
+use yoetz_core::bundle::{build_bundle, count_bundle_tokens, BundleOptions};
+use yoetz_core::config::{CouncilMember, CouncilSpec};
 use yoetz_core::output::{write_json, write_jsonl, OutputFormat};
-use yoetz_core::session::{create_session_dir, write_json as write_json_file, write_text};
-use yoetz_core::types::{ArtifactPaths, Usage};
+use yoetz_core::registry::ModelRegistry;
+use yoetz_core::render::BundleFormat;
+use yoetz_core::session::{create_session_dir, write_json as write_json_file};
+use yoetz_core::tokenizer::Tokenizer;
+use yoetz_core::types::{ArtifactPaths, CallAttempt, PricingEstimate, Usage};
 
 pub(crate) async fn handle_council(
     ctx: &AppContext,
@@ -25,10 +39,35 @@ pub(crate) async fn handle_council(
     format: OutputFormat,
 ) -> Result<()> {
     let started_at = Instant::now();
-    let prompt = resolve_prompt(args.prompt.clone(), args.prompt_file.clone())?;
+    let (conversation, prompt) = resolve_conversation_prompt(
+        args.prompt.clone(),
+        args.prompt_file.clone(),
+        args.system.clone(),
+        args.system_file.as_deref(),
+        args.messages.as_deref(),
+    )?;
     let config = &ctx.config;
 
-    if args.models.is_empty() {
+    let panel: Vec<CouncilMember> = match (&args.council_file, &args.council) {
+        (Some(path), _) => CouncilSpec::load(path)?.members,
+        (None, Some(name)) => config
+            .councils
+            .get(name)
+            .ok_or_else(|| {
+                anyhow!("unknown council `{name}` (define [councils.{name}] in config)")
+            })?
+            .members
+            .clone(),
+        (None, None) => args
+            .models
+            .iter()
+            .map(|model| CouncilMember {
+                model: model.clone(),
+                ..Default::default()
+            })
+            .collect(),
+    };
+    if panel.is_empty() {
         return Err(anyhow!("at least one model is required"));
     }
 
@@ -44,13 +83,16 @@ pub(crate) async fn handle_council(
         .flatten();
     let mut resolved_models = Vec::new();
     let mut provider_keys = BTreeSet::new();
-    for model in &args.models {
-        let normalized = normalize_model_name_with_aliases(model, &config.aliases);
-        let provider = resolve_council_provider(
-            &normalized,
-            default_provider.as_deref(),
-            registry_cache.as_ref(),
-        )?;
+    for member in &panel {
+        let normalized = normalize_model_name_with_aliases(&member.model, &config.aliases);
+        let provider = match &member.provider {
+            Some(provider) => provider.to_lowercase(),
+            None => resolve_council_provider(
+                &normalized,
+                default_provider.as_deref(),
+                registry_cache.as_ref(),
+            )?,
+        };
         provider_keys.insert(provider.clone());
         resolved_models.push((normalized, provider));
     }
@@ -68,27 +110,12 @@ pub(crate) async fn handle_council(
         args.response_schema.clone(),
         args.response_schema_name.clone(),
     )?;
+    // Compiled up front so a bad schema fails before any member is paid for.
+    let consensus_schema = args
+        .consensus
+        .then(|| ConsensusSchema::from_response_format(response_format.as_ref()))
+        .transpose()?;
 
-    let include_files = args.files.clone();
-    let exclude_files = args.exclude.clone();
-
-    let bundle = if include_files.is_empty() {
-        None
-    } else {
-        let options = BundleOptions {
-            include: include_files,
-            exclude: exclude_files,
-            max_file_bytes: args.max_file_bytes,
-            max_total_bytes: args.max_total_bytes,
-            ..Default::default()
-        };
-        Some(build_bundle(&prompt, options)?)
-    };
-
-    let input_tokens = bundle
-        .as_ref()
-        .map(|b| b.stats.estimated_tokens)
-        .unwrap_or_else(|| estimate_tokens(prompt.len()));
     // Validate each model against registry
     for (model, _provider) in &resolved_models {
         let reg_id =
@@ -108,27 +135,163 @@ pub(crate) async fn handle_council(
     let per_model_max_output_tokens: Vec<Option<usize>> = resolved_models
         .iter()
         .zip(&resolved_registry_ids)
-        .map(|((_model, provider), reg_id)| {
+        .zip(&panel)
+        .map(|(((_model, provider), reg_id), member)| {
             resolve_max_output_tokens_for_provider(
                 Some(provider),
-                args.max_output_tokens,
+                member.max_output_tokens.or(args.max_output_tokens),
                 config,
                 registry_cache.as_ref(),
                 reg_id.as_deref(),
             )
         })
         .collect();
+    let per_model_temperature: Vec<f32> = panel
+        .iter()
+        .map(|member| member.temperature.unwrap_or(args.temperature))
+        .collect();
     for (idx, (_model, provider)) in resolved_models.iter().enumerate() {
         validate_cursor_options(
             Some(provider),
             per_model_max_output_tokens[idx],
             response_format.as_ref(),
-            false,
-            args.temperature,
+            conversation.has_images(),
+            per_model_temperature[idx],
             args.max_cost_usd,
             args.daily_budget_usd,
         )?;
     }
+    // The synthesizer is resolved like a member but never answers as one.
+    let synthesizer = match args.synthesize_with.as_deref() {
+        Some(model) => {
+            let model = normalize_model_name_with_aliases(model, &config.aliases);
+            let provider = resolve_council_provider(
+                &model,
+                default_provider.as_deref(),
+                registry_cache.as_ref(),
+            )?;
+            let registry_id =
+                resolve_registry_model_id(Some(&provider), Some(&model), registry_cache.as_ref());
+            if let Some(id) = &registry_id {
+                crate::validate_model_or_suggest(id, registry_cache.as_ref(), ctx.allow_unknown)?;
+            }
+            let max_output_tokens = resolve_max_output_tokens_for_provider(
+                Some(&provider),
+                args.max_output_tokens,
+                config,
+                registry_cache.as_ref(),
+                registry_id.as_deref(),
+            );
+            validate_cursor_options(
+                Some(&provider),
+                max_output_tokens,
+                response_format.as_ref(),
+                conversation.has_images(),
+                args.temperature,
+                args.max_cost_usd,
+                args.daily_budget_usd,
+            )?;
+            let registry_id = registry_id.unwrap_or_else(|| model.clone());
+            let target = ModelTarget {
+                provider: Some(provider),
+                model,
+                max_output_tokens,
+            };
+            Some((target, registry_id))
+        }
+        None => None,
+    };
+
+    // With --fit-context the bundle must fit the smallest member window.
+    let mut token_budget = args.token_budget;
+    if args.fit_context {
+        for (reg_id, max_output_tokens) in resolved_registry_ids
+            .iter()
+            .zip(&per_model_max_output_tokens)
+        {
+            if let Some(member_budget) = resolve_bundle_token_budget(
+                args.token_budget,
+                true,
+                registry_cache.as_ref(),
+                reg_id.as_deref(),
+                *max_output_tokens,
+            )? {
+                token_budget = Some(token_budget.map_or(member_budget, |b| b.min(member_budget)));
+            }
+        }
+    }
+
+    let model_tokenizers: Vec<Tokenizer> = resolved_models
+        .iter()
+        .zip(&resolved_registry_ids)
+        .map(|((model, _provider), reg_id)| {
+            Tokenizer::for_model(Some(reg_id.as_deref().unwrap_or(model)))
+        })
+        .collect();
+    let (selection, preset_format) = preset_bundle_options(
+        config,
+        args.preset.as_deref(),
+        args.files.clone(),
+        args.exclude.clone(),
+        args.max_file_bytes,
+        args.max_total_bytes,
+    )?;
+    let git_selector =
+        git_selector_from_flags(args.changed_since.clone(), args.staged, args.worktree);
+
+    let bundle = if selection.include.is_empty()
+        && !selection.include_all
+        && git_selector.is_none()
+        && args.include_cmd.is_empty()
+    {
+        None
+    } else {
+        let options = BundleOptions {
+            token_budget,
+            git_selector,
+            include_diff: args.include_diff,
+            outline_oversized: args.outline,
+            outline_globs: args.outline_glob,
+            follow_imports: args.follow_imports,
+            redact_secrets: ctx.config.redaction.enabled.unwrap_or(true),
+            secret_rules: ctx.config.redaction.rules.clone(),
+            fail_on_secrets: args.fail_on_secrets,
+            commands: run_include_cmds(
+                &args.include_cmd,
+                Duration::from_secs(args.include_cmd_timeout),
+                args.include_cmd_max_bytes,
+            )?,
+            tokenizer: model_tokenizers[0],
+            ..selection
+        };
+        let mut bundle = build_bundle(&prompt, options)?;
+        bundle.line_numbers = args.line_numbers;
+        warn_bundle_adjustments(&bundle);
+        apply_since_session(&mut bundle, args.since_session.as_deref())?;
+        Some(bundle)
+    };
+
+    // Members can tokenize differently; count once per distinct tokenizer.
+    let conversation_text = conversation.text();
+    let mut tokens_by_tokenizer = BTreeMap::new();
+    let per_model_input_tokens: Vec<usize> = model_tokenizers
+        .iter()
+        .zip(&panel)
+        .map(|(tokenizer, member)| {
+            let shared = *tokens_by_tokenizer.entry(*tokenizer).or_insert_with(|| {
+                tokenizer.count(&conversation_text)
+                    + match &bundle {
+                        Some(bundle_ref) => count_bundle_tokens(bundle_ref, *tokenizer),
+                        None => tokenizer.count(&prompt),
+                    }
+            });
+            shared
+                + member
+                    .role
+                    .as_deref()
+                    .map_or(0, |role| tokenizer.count(role))
+        })
+        .collect();
 
     let mut per_model = Vec::new();
     let mut per_model_pricing = Vec::new();
@@ -140,7 +303,7 @@ pub(crate) async fn handle_council(
         let estimate = registry::estimate_pricing(
             registry_cache.as_ref(),
             registry_id.as_deref().unwrap_or(model),
-            input_tokens,
+            per_model_input_tokens[idx],
             output_tokens,
         )?;
         per_model_pricing.push(estimate.clone());
@@ -154,6 +317,59 @@ pub(crate) async fn handle_council(
             estimate_usd: estimate.estimate_usd,
         });
     }
+    // Debate rounds and the synthesizer read every answer, each bounded by
+    // its member's output limit.
+    let answer_tokens: usize = per_model_max_output_tokens
+        .iter()
+        .map(|max| max.unwrap_or(4096))
+        .sum();
+    let debate_rounds = args.rounds - 1;
+    let debate_input_tokens: Vec<usize> = per_model_input_tokens
+        .iter()
+        .zip(&model_tokenizers)
+        .map(|(input_tokens, tokenizer)| {
+            input_tokens + tokenizer.count(DEBATE_INSTRUCTIONS) + answer_tokens
+        })
+        .collect();
+    if debate_rounds > 0 {
+        for (idx, (model, _provider)) in resolved_models.iter().enumerate() {
+            let estimate = registry::estimate_pricing(
+                registry_cache.as_ref(),
+                resolved_registry_ids[idx].as_deref().unwrap_or(model),
+                debate_input_tokens[idx],
+                per_model_max_output_tokens[idx].unwrap_or(4096),
+            )?;
+            let debate_usd = estimate
+                .estimate_usd
+                .map(|cost| cost * f64::from(debate_rounds));
+            match debate_usd {
+                Some(cost) => estimate_sum += cost,
+                None => estimate_complete = false,
+            }
+            let member = &mut per_model[idx];
+            member.estimate_usd = member.estimate_usd.zip(debate_usd).map(|(a, b)| a + b);
+        }
+    }
+    let synthesis_estimate = match &synthesizer {
+        Some((target, registry_id)) => {
+            let tokenizer = Tokenizer::for_model(Some(registry_id));
+            let estimate = registry::estimate_pricing(
+                registry_cache.as_ref(),
+                registry_id,
+                tokenizer.count(&conversation_text)
+                    + tokenizer.count(&synthesis_prompt(&prompt, &[]))
+                    + answer_tokens,
+                target.max_output_tokens.unwrap_or(4096),
+            )?;
+            if let Some(cost) = estimate.estimate_usd {
+                estimate_sum += cost;
+            } else {
+                estimate_complete = false;
+            }
+            Some(estimate)
+        }
+        None => None,
+    };
     let total_estimate = if estimate_complete {
         Some(estimate_sum)
     } else {
@@ -173,24 +389,63 @@ pub(crate) async fn handle_council(
         ..Default::default()
     };
 
+    // Members may prefer different bundle formats; each format is rendered once.
+    let member_formats: Vec<BundleFormat> = resolved_models
+        .iter()
+        .map(|(_model, provider)| {
+            resolve_bundle_format(args.bundle_format.or(preset_format), config, Some(provider))
+        })
+        .collect();
     if let Some(bundle_ref) = &bundle {
-        let bundle_json = session.path.join("bundle.json");
-        let bundle_md = session.path.join("bundle.md");
-        write_json_file(&bundle_json, bundle_ref)?;
-        write_text(&bundle_md, &render_bundle_md(bundle_ref))?;
-        artifacts.bundle_json = Some(bundle_json.to_string_lossy().to_string());
-        artifacts.bundle_md = Some(bundle_md.to_string_lossy().to_string());
+        write_session_bundle(&session.path, bundle_ref, &member_formats, &mut artifacts)?;
     }
 
     let mut results = Vec::new();
     let mut total_usage = Usage::default();
     let mut errors = Vec::new();
     let mut model_artifacts = Vec::new();
-    let model_prompt = std::sync::Arc::new(if let Some(bundle_ref) = &bundle {
-        render_bundle_md(bundle_ref)
-    } else {
-        prompt.clone()
-    });
+    // Debate rounds bill every call, not just the answers that survive.
+    let mut spend = 0.0;
+    let mut has_spend = false;
+    let mut reported_cost = 0.0;
+    let mut round_summaries = Vec::new();
+    // With --prompt-cache each format's bundle rides in its own copy of the
+    // conversation, ahead of the prompt. A member's role leads its system
+    // prompt.
+    let prompt_cache = args.prompt_cache || config.defaults.prompt_cache.unwrap_or(false);
+    let mut rendered_inputs: HashMap<(BundleFormat, Option<&str>), MemberInput> = HashMap::new();
+    let member_inputs: Vec<MemberInput> = member_formats
+        .iter()
+        .zip(&panel)
+        .map(|(&bundle_format, member)| {
+            let role = member.role.as_deref();
+            let input = rendered_inputs
+                .entry((bundle_format, role))
+                .or_insert_with(|| {
+                    let mut conversation = conversation.clone();
+                    if let Some(role) = role {
+                        conversation.system = Some(match conversation.system.take() {
+                            Some(system) => format!("{role}\n\n{system}"),
+                            None => role.to_string(),
+                        });
+                    }
+                    let prompt = match &bundle {
+                        Some(bundle_ref) if prompt_cache => {
+                            let split = bundle_format.render_split(bundle_ref);
+                            conversation.context = Some(split.context);
+                            split.prompt
+                        }
+                        Some(bundle_ref) => bundle_format.render(bundle_ref),
+                        None => prompt.clone(),
+                    };
+                    (
+                        std::sync::Arc::new(conversation),
+                        std::sync::Arc::new(prompt),
+                    )
+                });
+            input.clone()
+        })
+        .collect();
 
     if args.dry_run {
         for (idx, (model, provider)) in resolved_models.iter().enumerate() {
@@ -198,16 +453,19 @@ pub(crate) async fn handle_council(
                 resolve_registry_model_id(Some(provider), Some(model), registry_cache.as_ref());
             let output_tokens = per_model_max_output_tokens[idx].unwrap_or(4096);
             let result = CouncilModelResult {
+                member: idx,
+                name: panel[idx].name.clone(),
                 model: model.clone(),
                 content: "(dry-run) no provider call executed".to_string(),
                 usage: Usage::default(),
                 pricing: registry::estimate_pricing(
                     registry_cache.as_ref(),
                     registry_id.as_deref().unwrap_or(model),
-                    input_tokens,
+                    per_model_input_tokens[idx],
                     output_tokens,
                 )?,
                 response_id: None,
+                from_round: None,
             };
             model_artifacts.push((
                 idx,
@@ -218,136 +476,363 @@ pub(crate) async fn handle_council(
     } else {
         let max_parallel = args.max_parallel.max(1);
         let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(max_parallel));
-        let mut join_set = tokio::task::JoinSet::new();
-        for (idx, (model, provider)) in resolved_models.iter().cloned().enumerate() {
-            let prompt = std::sync::Arc::clone(&model_prompt);
-            let provider = provider.clone();
-            let litellm = ctx.litellm.clone();
-            let cursor_discovery = std::sync::Arc::clone(&ctx.cursor_discovery);
-            let cursor_timeout = ctx.timeout_duration;
-            let semaphore = std::sync::Arc::clone(&semaphore);
-            let temperature = args.temperature;
-            let response_format = response_format.clone();
-            let model_max_output_tokens = per_model_max_output_tokens[idx];
-            join_set.spawn(async move {
-                let _permit = semaphore.acquire_owned().await.map_err(|err| {
-                    (
-                        idx,
-                        model.clone(),
-                        provider.clone(),
-                        anyhow!("failed to acquire council permit: {err}"),
-                    )
-                })?;
-                let call = call_model(
-                    &litellm,
-                    cursor_timeout,
-                    &cursor_discovery,
-                    Some(&provider),
-                    &model,
-                    prompt.as_str(),
-                    temperature,
-                    model_max_output_tokens,
-                    response_format,
-                    &[],
-                    None,
-                )
-                .await;
-                match call {
-                    Ok(call) => Ok((idx, model, provider, call)),
-                    Err(err) => Err((idx, model, provider, err)),
-                }
-            });
+        let members: Vec<&str> = resolved_models
+            .iter()
+            .map(|(model, _)| model.as_str())
+            .collect();
+        let has_images = conversation.has_images();
+        // Targets and direct-call routes stay fixed across debate rounds.
+        let mut member_targets = Vec::new();
+        let mut member_routes = Vec::new();
+        for (idx, (model, provider)) in resolved_models.iter().enumerate() {
+            // Fallbacks never include another member, so each answer stays independent.
+            let mut targets = vec![ModelTarget {
+                provider: Some(provider.clone()),
+                model: model.clone(),
+                max_output_tokens: per_model_max_output_tokens[idx],
+            }];
+            targets.extend(fallback_targets(
+                ctx,
+                &args.fallback_models,
+                Some(provider),
+                &members,
+                &FallbackLimits {
+                    registry: registry_cache.as_ref(),
+                    max_output_tokens: panel[idx].max_output_tokens.or(args.max_output_tokens),
+                    input_tokens: per_model_input_tokens[idx],
+                    budget_usd: budget_enabled.then_some(per_model_pricing[idx].estimate_usd),
+                    response_format: response_format.as_ref(),
+                    has_media: has_images,
+                    temperature: per_model_temperature[idx],
+                },
+            )?);
+            let conversation = &member_inputs[idx].0;
+            if let Some(warning) = prompt_cache::unsent_breakpoint_warning(
+                config,
+                provider,
+                model,
+                conversation,
+                response_format.as_ref(),
+            ) {
+                eprintln!("warning: {warning}");
+            }
+            // Direct calls for targets whose breakpoint litellm cannot send.
+            let breakpoint_routes: HashMap<(String, String), BreakpointRoute> = targets
+                .iter()
+                .filter_map(|target| {
+                    let provider = target.provider.clone()?;
+                    let route = prompt_cache::breakpoint_route(
+                        config,
+                        &provider,
+                        &target.model,
+                        conversation,
+                        response_format.as_ref(),
+                    )?;
+                    Some(((provider, target.model.clone()), route))
+                })
+                .collect();
+            member_targets.push(targets);
+            member_routes.push(std::sync::Arc::new(breakpoint_routes));
         }
 
-        let mut ordered: Vec<Option<CouncilModelResult>> =
+        // Each member's latest answer; a member that fails sits out the
+        // remaining rounds and keeps the answer it gave before.
+        let mut answers: Vec<Option<CouncilModelResult>> =
             (0..resolved_models.len()).map(|_| None).collect();
-        while let Some(res) = join_set.join_next().await {
-            match res {
-                Ok(Ok((idx, model, provider, call))) => {
-                    let mut usage = call.usage;
-                    if usage.cost_usd.is_none() {
-                        usage.cost_usd = call.header_cost;
-                    }
-                    if usage.cost_usd.is_none() && provider == "openrouter" {
-                        if let Some(id) = call.response_id.as_deref() {
-                            if let Ok(cost) =
-                                crate::fetch_openrouter_cost(&ctx.client, config, id).await
-                            {
-                                usage.cost_usd = cost;
+        let mut active = vec![true; resolved_models.len()];
+        let mut final_artifacts: Vec<Option<CouncilModelArtifact>> =
+            (0..resolved_models.len()).map(|_| None).collect();
+        let progress = CouncilProgress::new(format);
+        for round in 1..=args.rounds {
+            let mut join_set = tokio::task::JoinSet::new();
+            // Lets a task that panicked still be reported as its member.
+            let mut task_members = HashMap::new();
+            for (idx, (model, provider)) in resolved_models.iter().cloned().enumerate() {
+                if !active[idx] {
+                    continue;
+                }
+                let name = panel[idx].name.clone();
+                let targets = member_targets[idx].clone();
+                let breakpoint_routes = std::sync::Arc::clone(&member_routes[idx]);
+                let retry = ctx.retry.clone();
+                let (conversation, prompt) = member_inputs[idx].clone();
+                let prompt = if round == 1 {
+                    prompt
+                } else {
+                    std::sync::Arc::new(debate_prompt(&prompt, round, idx, &answers))
+                };
+                let litellm = ctx.litellm.clone();
+                let cursor_discovery = std::sync::Arc::clone(&ctx.cursor_discovery);
+                let cursor_timeout = ctx.timeout_duration;
+                let semaphore = std::sync::Arc::clone(&semaphore);
+                let temperature = per_model_temperature[idx];
+                let response_format = response_format.clone();
+                let task = join_set.spawn(async move {
+                    let _permit = semaphore.acquire_owned().await.map_err(|err| {
+                        (
+                            idx,
+                            model.clone(),
+                            provider.clone(),
+                            anyhow!("failed to acquire council permit: {err}"),
+                            Vec::new(),
+                        )
+                    })?;
+                    progress.event(
+                        "member_started",
+                        json!({
+                            "member": idx,
+                            "round": round,
+                            "name": name,
+                            "model": model,
+                            "provider": provider,
+                        }),
+                    );
+                    let (litellm, cursor_discovery) = (&litellm, &cursor_discovery);
+                    let (conversation, prompt) = (&*conversation, prompt.as_str());
+                    let breakpoint_routes = &*breakpoint_routes;
+                    let outcome = call_with_retry(&retry, &targets, |target| {
+                        let provider = target.provider.clone();
+                        let model = target.model.clone();
+                        let max_output_tokens = target.max_output_tokens;
+                        let response_format = response_format.clone();
+                        async move {
+                            let key = (provider.clone().unwrap_or_default(), model.clone());
+                            if let Some(route) = breakpoint_routes.get(&key) {
+                                return prompt_cache::complete(
+                                    route,
+                                    &key.0,
+                                    &model,
+                                    conversation,
+                                    prompt,
+                                    temperature,
+                                    max_output_tokens,
+                                    response_format,
+                                    cursor_timeout,
+                                )
+                                .await;
                             }
+                            call_model(
+                                litellm,
+                                cursor_timeout,
+                                cursor_discovery,
+                                provider.as_deref(),
+                                &model,
+                                conversation,
+                                prompt,
+                                temperature,
+                                max_output_tokens,
+                                response_format,
+                                &[],
+                                None,
+                            )
+                            .await
+                        }
+                    })
+                    .await;
+                    let attempts = outcome.recorded_attempts();
+                    match outcome.result {
+                        Ok((target, mut call)) => {
+                            call.usage.attempts = attempts;
+                            Ok((idx, target, call))
                         }
+                        Err(err) => Err((idx, model, provider, err, outcome.attempts)),
                     }
+                });
+                task_members.insert(task.id(), idx);
+            }
 
-                    total_usage = add_usage(total_usage, &usage);
+            let input_tokens = if round == 1 {
+                &per_model_input_tokens
+            } else {
+                &debate_input_tokens
+            };
+            let mut ordered: Vec<Option<CouncilModelResult>> =
+                (0..resolved_models.len()).map(|_| None).collect();
+            let mut round_artifacts = Vec::new();
+            let (mut round_answered, mut round_failed) = (0, 0);
+            let round_total = join_set.len();
+            progress.status(round, args.rounds, 0, 0, round_total);
+            while let Some(res) = join_set.join_next().await {
+                let res = res.unwrap_or_else(|join| {
+                    let idx = task_members[&join.id()];
+                    let (model, provider) = resolved_models[idx].clone();
+                    Err((idx, model, provider, join.into(), Vec::new()))
+                });
+                match res {
+                    Ok((idx, target, call)) => {
+                        round_answered += 1;
+                        let model = target.model;
+                        let provider = target.provider.unwrap_or_default();
+                        let registry_id = resolve_registry_model_id(
+                            Some(&provider),
+                            Some(&model),
+                            registry_cache.as_ref(),
+                        );
+                        let usage = settled_usage(
+                            ctx,
+                            registry_cache.as_ref(),
+                            &provider,
+                            registry_id.as_deref().unwrap_or(&model),
+                            &call,
+                        )
+                        .await;
 
-                    let registry_id = resolve_registry_model_id(
-                        Some(&provider),
-                        Some(&model),
-                        registry_cache.as_ref(),
-                    );
-                    let output_tokens = per_model_max_output_tokens[idx].unwrap_or(4096);
-                    let pricing = registry::estimate_pricing(
-                        registry_cache.as_ref(),
-                        registry_id.as_deref().unwrap_or(&model),
-                        input_tokens,
-                        output_tokens,
-                    )?;
+                        total_usage = add_usage(total_usage, &usage);
+                        let output_tokens = target
+                            .max_output_tokens
+                            .or(per_model_max_output_tokens[idx])
+                            .unwrap_or(4096);
+                        let mut pricing = registry::estimate_pricing(
+                            registry_cache.as_ref(),
+                            registry_id.as_deref().unwrap_or(&model),
+                            input_tokens[idx],
+                            output_tokens,
+                        )?;
+                        let member = &resolved_models[idx].0;
+                        if *member != model {
+                            pricing
+                                .warnings
+                                .push(format!("answered as fallback for {member}"));
+                        }
+                        if let Some(cost) = usage.cost_usd {
+                            reported_cost += cost;
+                        }
+                        if let Some(cost) = usage.cost_usd.or(pricing.estimate_usd) {
+                            spend += cost;
+                            has_spend = true;
+                        }
 
-                    let result = CouncilModelResult {
-                        model: model.clone(),
-                        content: call.content,
-                        usage,
-                        pricing,
-                        response_id: call.response_id,
-                    };
-                    model_artifacts
-                        .push((idx, successful_model_artifact(model, provider, &result)));
-                    ordered[idx] = Some(result);
+                        let result = CouncilModelResult {
+                            member: idx,
+                            name: panel[idx].name.clone(),
+                            model: model.clone(),
+                            content: call.content,
+                            usage,
+                            pricing,
+                            response_id: call.response_id,
+                            from_round: None,
+                        };
+                        progress.event(
+                            "member_succeeded",
+                            json!({
+                                "member": idx,
+                                "round": round,
+                                "name": result.name,
+                                "model": model,
+                                "provider": provider,
+                                "content": result.content,
+                                "usage": result.usage,
+                            }),
+                        );
+                        round_artifacts
+                            .push((idx, successful_model_artifact(model, provider, &result)));
+                        ordered[idx] = Some(result);
+                    }
+                    Err((idx, model, provider, err, attempts)) => {
+                        round_failed += 1;
+                        let error = if args.rounds > 1 {
+                            format!("round {round}: {err}")
+                        } else {
+                            err.to_string()
+                        };
+                        progress.event(
+                            "member_failed",
+                            json!({
+                                "member": idx,
+                                "round": round,
+                                "name": panel[idx].name,
+                                "model": model,
+                                "provider": provider,
+                                "error": error,
+                            }),
+                        );
+                        round_artifacts.push((
+                            idx,
+                            failed_model_artifact(
+                                model.clone(),
+                                provider.clone(),
+                                per_model_pricing[idx].clone(),
+                                error.clone(),
+                                attempts.clone(),
+                            ),
+                        ));
+                        errors.push(CouncilModelError {
+                            model,
+                            provider,
+                            error,
+                            attempts,
+                        });
+                    }
                 }
-                Ok(Err((idx, model, provider, err))) => {
-                    let error = err.to_string();
-                    model_artifacts.push((
-                        idx,
-                        failed_model_artifact(
-                            model.clone(),
-                            provider.clone(),
-                            per_model_pricing[idx].clone(),
-                            error.clone(),
-                        ),
-                    ));
-                    errors.push(CouncilModelError {
-                        model,
-                        provider,
-                        error,
-                    });
+                progress.status(
+                    round,
+                    args.rounds,
+                    round_answered,
+                    round_failed,
+                    round_total,
+                );
+            }
+
+            round_artifacts.sort_by_key(|(index, _)| *index);
+            if args.rounds > 1 {
+                write_model_artifacts(
+                    &session.path.join(format!("round-{round}")),
+                    &round_artifacts,
+                );
+                let contents: Vec<&str> = ordered
+                    .iter()
+                    .flatten()
+                    .map(|result| result.content.as_str())
+                    .collect();
+                round_summaries.push(CouncilRound {
+                    round,
+                    succeeded: contents.len(),
+                    failed: round_failed,
+                    agreement: agreement(&contents),
+                });
+            }
+            for (idx, artifact) in round_artifacts {
+                if ordered[idx].is_some() || answers[idx].is_none() {
+                    final_artifacts[idx] = Some(artifact);
+                }
+            }
+            for (idx, answer) in ordered.into_iter().enumerate() {
+                if !active[idx] {
+                    continue;
                 }
-                Err(err) => {
-                    let error = err.to_string();
-                    model_artifacts.push((
-                        usize::MAX,
-                        failed_model_artifact(
-                            "<task>".to_string(),
-                            "internal".to_string(),
-                            Default::default(),
-                            error.clone(),
-                        ),
-                    ));
-                    errors.push(CouncilModelError {
-                        model: "<task>".to_string(),
-                        provider: "internal".to_string(),
-                        error,
-                    });
+                match answer {
+                    Some(answer) => answers[idx] = Some(answer),
+                    None => {
+                        active[idx] = false;
+                        if let Some(previous) = &mut answers[idx] {
+                            previous.from_round = Some(round - 1);
+                        }
+                    }
                 }
             }
+            if !active.contains(&true) {
+                break;
+            }
         }
 
-        results = ordered.into_iter().flatten().collect();
+        progress.clear_status();
+
+        model_artifacts.extend(
+            final_artifacts
+                .into_iter()
+                .enumerate()
+                .filter_map(|(idx, artifact)| Some((idx, artifact?))),
+        );
+        results = answers.into_iter().flatten().collect();
     }
 
     model_artifacts.sort_by_key(|(index, _)| *index);
 
     if results.is_empty() && !errors.is_empty() {
-        write_model_artifacts(&session.path, &model_artifacts);
+        write_model_artifacts(&session.path.join("models"), &model_artifacts);
+        if budget_enabled && has_spend {
+            commit_spend(budget_reservation, spend);
+        }
         let joined = errors
             .iter()
             .map(|error| format!("- {} ({}): {}", error.model, error.provider, error.error))
@@ -356,37 +841,72 @@ pub(crate) async fn handle_council(
         return Err(anyhow!("all council models failed:\n{joined}"));
     }
 
+    let consensus = consensus_schema.filter(|_| !args.dry_run).map(|schema| {
+        let keys: Vec<String> = results
+            .iter()
+            .map(|result| consensus_key(result, &results))
+            .collect();
+        let answers: Vec<(&str, &str)> = keys
+            .iter()
+            .zip(&results)
+            .map(|(key, result)| (key.as_str(), result.content.as_str()))
+            .collect();
+        schema.evaluate(&answers)
+    });
+
+    let synthesis = match &synthesizer {
+        Some((target, registry_id)) if !results.is_empty() => Some(
+            synthesize(
+                ctx,
+                target,
+                registry_id,
+                registry_cache.as_ref(),
+                &conversation,
+                &prompt,
+                &results,
+                args.temperature,
+                response_format.clone(),
+                synthesis_estimate.clone().unwrap_or_default(),
+                args.dry_run,
+            )
+            .await,
+        ),
+        _ => None,
+    };
+    if let Some(synthesis) = &synthesis {
+        total_usage = add_usage(total_usage, &synthesis.usage);
+    }
+
     if budget_enabled && !args.dry_run {
-        let mut spend = 0.0;
-        let mut has_spend = false;
-        for r in &results {
-            if let Some(cost) = r.usage.cost_usd.or(r.pricing.estimate_usd) {
+        if let Some(synthesis) = synthesis.as_ref().filter(|s| s.error.is_none()) {
+            if let Some(cost) = synthesis.usage.cost_usd.or(synthesis.pricing.estimate_usd) {
                 spend += cost;
                 has_spend = true;
             }
         }
         if has_spend {
-            if let Some(reservation) = budget_reservation {
-                if let Err(e) = reservation.commit(spend) {
-                    eprintln!("warning: budget commit failed: {e}");
-                }
-            } else if let Err(e) = budget::record_spend_standalone(spend) {
-                eprintln!("warning: budget commit failed: {e}");
-            }
+            commit_spend(budget_reservation, spend);
         }
     }
 
+    // A member that failed a later round still answered, from an earlier one.
+    let carried = results.iter().filter(|r| r.from_round.is_some()).count();
+    let failed = errors.len() - carried;
     let summary = CouncilSummary {
         succeeded: results.len(),
-        failed: errors.len(),
-        total: results.len() + errors.len(),
-        cost_usd: results
-            .iter()
-            .filter_map(|result| result.usage.cost_usd)
-            .sum(),
+        failed,
+        total: results.len() + failed,
+        cost_usd: reported_cost
+            + synthesis
+                .as_ref()
+                .and_then(|s| s.usage.cost_usd)
+                .unwrap_or_default(),
         elapsed_ms: u64::try_from(started_at.elapsed().as_millis()).unwrap_or(u64::MAX),
+        convergence: convergence(&round_summaries),
+        rounds: round_summaries,
     };
     let strict_partial_failure = matches!(args.partial, PartialPolicy::Fail) && !errors.is_empty();
+    let synthesis_failed = synthesis.as_ref().is_some_and(|s| s.error.is_some());
 
     let mut council = CouncilResult {
         id: session.id,
@@ -394,10 +914,18 @@ pub(crate) async fn handle_council(
         bundle,
         results,
         errors,
+        synthesis,
+        consensus,
         summary,
         pricing: CouncilPricing {
             estimate_usd_total: total_estimate,
             per_model,
+            synthesis: synthesizer.as_ref().zip(synthesis_estimate.as_ref()).map(
+                |((target, _), estimate)| ModelEstimate {
+                    model: target.model.clone(),
+                    estimate_usd: estimate.estimate_usd,
+                },
+            ),
         },
         usage: total_usage,
         artifacts,
@@ -408,7 +936,7 @@ pub(crate) async fn handle_council(
     write_json_file(&response_json, &council)?;
 
     maybe_write_output(ctx, &council)?;
-    write_model_artifacts(&session.path, &model_artifacts);
+    write_model_artifacts(&session.path.join("models"), &model_artifacts);
     let notified_target = if council.summary.total == 1 {
         council
             .results
@@ -448,7 +976,7 @@ pub(crate) async fn handle_council(
         OutputFormat::Jsonl => write_jsonl("council", &council),
         OutputFormat::Text => {
             for r in &council.results {
-                println!("## {}\n{}\n", r.model, r.content);
+                println!("## {}\n{}\n", result_heading(r), r.content);
             }
             if !council.errors.is_empty() {
                 println!("## Errors");
@@ -457,11 +985,13 @@ pub(crate) async fn handle_council(
                 }
                 println!();
             }
+            print_synthesis(council.synthesis.as_ref());
+            print_consensus(council.consensus.as_ref());
             Ok(())
         }
         OutputFormat::Markdown => {
             for r in &council.results {
-                println!("## {}\n{}\n", r.model, r.content);
+                println!("## {}\n{}\n", result_heading(r), r.content);
             }
             if !council.errors.is_empty() {
                 println!("## Errors");
@@ -470,6 +1000,8 @@ pub(crate) async fn handle_council(
                 }
                 println!();
             }
+            print_synthesis(council.synthesis.as_ref());
+            print_consensus(council.consensus.as_ref());
             Ok(())
         }
     }?;
@@ -480,12 +1012,383 @@ pub(crate) async fn handle_council(
             council.summary.failed
         ));
     }
+    if synthesis_failed && matches!(args.partial, PartialPolicy::Fail) {
+        return Err(anyhow!("council synthesis failed under --partial fail"));
+    }
     Ok(())
 }
 
-fn write_model_artifacts(session_dir: &Path, artifacts: &[(usize, CouncilModelArtifact)]) {
-    let models_dir = session_dir.join("models");
-    if let Err(error) = fs::create_dir_all(&models_dir) {
+const SYNTHESIS_INSTRUCTIONS: &str =
+    "Several assistants answered the question below independently. \
+Merge their responses into the single best answer: keep what they agree on, settle disagreements \
+on the merits (noting any that remain open), and drop claims that are wrong or unsupported. \
+Answer the question directly rather than describing the responses.";
+
+/// The aggregator's prompt: the question, then each answer under an
+/// anonymous label so the member models cannot sway it by name.
+fn synthesis_prompt(prompt: &str, answers: &[(String, &str)]) -> String {
+    let question = if prompt.trim().is_empty() {
+        "(the last user message above)"
+    } else {
+        prompt
+    };
+    let mut out = format!("{SYNTHESIS_INSTRUCTIONS}\n\n## Question\n\n{question}\n");
+    for (label, answer) in answers {
+        out.push_str(&format!("\n## Response {label}\n\n{}\n", answer.trim_end()));
+    }
+    out
+}
+
+const DEBATE_INSTRUCTIONS: &str =
+    "You are in a debate round. Other assistants answered the same question independently; \
+their answers are below, anonymized. Critique them and your own previous answer, then give \
+your revised answer in full. Keep what holds up, fix what does not, and only change your \
+position when an argument actually warrants it.";
+
+/// A debate round's prompt for member `member`: the original prompt, its
+/// own previous answer, and every other surviving member's answer under an
+/// anonymous label.
+fn debate_prompt(
+    prompt: &str,
+    round: u32,
+    member: usize,
+    answers: &[Option<CouncilModelResult>],
+) -> String {
+    let mut out = format!("{prompt}\n\n---\n\n# Debate round {round}\n\n{DEBATE_INSTRUCTIONS}\n");
+    if let Some(own) = answers.get(member).and_then(Option::as_ref) {
+        out.push_str(&format!(
+            "\n## Your previous answer\n\n{}\n",
+            own.content.trim_end()
+        ));
+    }
+    let others = answers
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| *idx != member)
+        .filter_map(|(_, answer)| answer.as_ref());
+    for (idx, other) in others.enumerate() {
+        out.push_str(&format!(
+            "\n## Response {}\n\n{}\n",
+            response_label(idx),
+            other.content.trim_end()
+        ));
+    }
+    out
+}
+
+/// Mean pairwise Jaccard similarity of the answers' lowercase word sets;
+/// `None` with fewer than two answers.
+fn agreement(answers: &[&str]) -> Option<f64> {
+    if answers.len() < 2 {
+        return None;
+    }
+    let words: Vec<BTreeSet<String>> = answers
+        .iter()
+        .map(|answer| {
+            answer
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .map(str::to_lowercase)
+                .collect()
+        })
+        .collect();
+    let mut total = 0.0;
+    let mut pairs = 0_u32;
+    for (idx, left) in words.iter().enumerate() {
+        for right in &words[idx + 1..] {
+            let union = left.union(right).count();
+            total += if union == 0 {
+                1.0
+            } else {
+                left.intersection(right).count() as f64 / union as f64
+            };
+            pairs += 1;
+        }
+    }
+    Some(total / f64::from(pairs))
+}
+
+/// How much agreement moved between the first and last debate rounds.
+fn convergence(rounds: &[CouncilRound]) -> Option<f64> {
+    let first = rounds.first()?.agreement?;
+    let last = rounds.last()?.agreement?;
+    (rounds.len() > 1).then_some(last - first)
+}
+
+/// `A`..`Z`, then `A2`..`Z2`, and so on.
+fn response_label(index: usize) -> String {
+    let letter = char::from(b'A' + (index % 26) as u8);
+    match index / 26 {
+        0 => letter.to_string(),
+        round => format!("{letter}{}", round + 1),
+    }
+}
+
+/// Ask the `--synthesize-with` model to merge the successful answers. A
+/// failed call is reported in the synthesis rather than failing the council.
+#[allow(clippy::too_many_arguments)]
+async fn synthesize(
+    ctx: &AppContext,
+    target: &ModelTarget,
+    registry_id: &str,
+    registry: Option<&ModelRegistry>,
+    conversation: &Conversation,
+    prompt: &str,
+    results: &[CouncilModelResult],
+    temperature: f32,
+    response_format: Option<Value>,
+    pricing: PricingEstimate,
+    dry_run: bool,
+) -> CouncilSynthesis {
+    let responses: Vec<SynthesisSource> = results
+        .iter()
+        .enumerate()
+        .map(|(idx, result)| SynthesisSource {
+            label: response_label(idx),
+            model: result.model.clone(),
+        })
+        .collect();
+    let provider = target.provider.clone().unwrap_or_default();
+    let mut synthesis = CouncilSynthesis {
+        model: target.model.clone(),
+        provider: provider.clone(),
+        responses,
+        content: None,
+        error: None,
+        usage: Usage::default(),
+        pricing,
+        response_id: None,
+    };
+    if dry_run {
+        synthesis.content = Some("(dry-run) no provider call executed".to_string());
+        return synthesis;
+    }
+
+    let answers: Vec<(String, &str)> = synthesis
+        .responses
+        .iter()
+        .zip(results)
+        .map(|(source, result)| (source.label.clone(), result.content.as_str()))
+        .collect();
+    let synthesis_prompt = synthesis_prompt(prompt, &answers);
+    eprintln!(
+        "Synthesizing {} answers with {}",
+        answers.len(),
+        target.model
+    );
+    let outcome = call_with_retry(&ctx.retry, std::slice::from_ref(target), |target| {
+        let provider = target.provider.clone();
+        let model = target.model.clone();
+        let max_output_tokens = target.max_output_tokens;
+        let response_format = response_format.clone();
+        let synthesis_prompt = synthesis_prompt.as_str();
+        async move {
+            call_model(
+                &ctx.litellm,
+                ctx.timeout_duration,
+                &ctx.cursor_discovery,
+                provider.as_deref(),
+                &model,
+                conversation,
+                synthesis_prompt,
+                temperature,
+                max_output_tokens,
+                response_format,
+                &[],
+                None,
+            )
+            .await
+        }
+    })
+    .await;
+    let attempts = outcome.recorded_attempts();
+    match outcome.result {
+        Ok((_, call)) => {
+            synthesis.usage = settled_usage(ctx, registry, &provider, registry_id, &call).await;
+            synthesis.usage.attempts = attempts;
+            synthesis.content = Some(call.content);
+            synthesis.response_id = call.response_id;
+        }
+        Err(err) => {
+            eprintln!(
+                "warning: council synthesis with {} failed: {err}",
+                target.model
+            );
+            synthesis.usage.attempts = outcome.attempts;
+            synthesis.error = Some(err.to_string());
+        }
+    }
+    synthesis
+}
+
+fn result_heading(result: &CouncilModelResult) -> String {
+    let heading = match &result.name {
+        Some(name) => format!("{name} ({})", result.model),
+        None => result.model.clone(),
+    };
+    match result.from_round {
+        Some(round) => format!("{heading} [round {round} answer]"),
+        None => heading,
+    }
+}
+
+/// How `--consensus` tells members apart: the member's name, else its model,
+/// with the member index appended when another answer came from that model.
+fn consensus_key(result: &CouncilModelResult, results: &[CouncilModelResult]) -> String {
+    if let Some(name) = &result.name {
+        return name.clone();
+    }
+    let shared = results
+        .iter()
+        .any(|other| other.member != result.member && other.model == result.model);
+    if shared {
+        format!("{}#{}", result.model, result.member)
+    } else {
+        result.model.clone()
+    }
+}
+
+/// Charge `spend` to the daily ledger, through the reservation when one was
+/// taken.
+fn commit_spend(reservation: Option<budget::BudgetReservation>, spend: f64) {
+    let committed = match reservation {
+        Some(reservation) => reservation.commit(spend),
+        None => budget::record_spend_standalone(spend),
+    };
+    if let Err(e) = committed {
+        eprintln!("warning: budget commit failed: {e}");
+    }
+}
+
+fn print_consensus(consensus: Option<&Consensus>) {
+    let Some(consensus) = consensus else {
+        return;
+    };
+    match (&consensus.value, consensus.agreement) {
+        (Some(value), Some(agreement)) => {
+            let value = serde_json::to_string_pretty(value).unwrap_or_default();
+            println!("## Consensus (agreement {agreement:.2})\n{value}\n");
+        }
+        _ => println!("## Consensus\nno answer matched the response schema\n"),
+    }
+    for (name, field) in &consensus.fields {
+        if !field.dissenters.is_empty() {
+            println!(
+                "- {name}: {:.2} ({}), dissent from {}",
+                field.agreement,
+                field.method,
+                field.dissenters.join(", ")
+            );
+        }
+    }
+    for invalid in &consensus.invalid {
+        println!("- invalid answer from {}: {}", invalid.model, invalid.error);
+    }
+    if consensus.fields.values().any(|f| !f.dissenters.is_empty()) || !consensus.invalid.is_empty()
+    {
+        println!();
+    }
+}
+
+fn print_synthesis(synthesis: Option<&CouncilSynthesis>) {
+    let Some(synthesis) = synthesis else {
+        return;
+    };
+    match (&synthesis.content, &synthesis.error) {
+        (Some(content), _) => println!("## Synthesis ({})\n{}\n", synthesis.model, content),
+        (None, error) => println!(
+            "## Synthesis ({})\nfailed: {}\n",
+            synthesis.model,
+            error.as_deref().unwrap_or("unknown error")
+        ),
+    }
+}
+
+/// The call's usage with a cost filled in when the provider reported none:
+/// the response header, OpenRouter's generation lookup, or registry rates for
+/// cached input (the estimate prices it at the full rate).
+async fn settled_usage(
+    ctx: &AppContext,
+    registry: Option<&ModelRegistry>,
+    provider: &str,
+    registry_id: &str,
+    call: &CallResult,
+) -> Usage {
+    let mut usage = call.usage.clone();
+    if usage.cost_usd.is_none() {
+        usage.cost_usd = call.header_cost;
+    }
+    if usage.cost_usd.is_none() && provider == "openrouter" {
+        if let Some(id) = call.response_id.as_deref() {
+            if let Ok(cost) = crate::fetch_openrouter_cost(&ctx.client, &ctx.config, id).await {
+                usage.cost_usd = cost;
+            }
+        }
+    }
+    if usage.cost_usd.is_none()
+        && (usage.cache_read_tokens.is_some() || usage.cache_write_tokens.is_some())
+    {
+        usage.cost_usd = registry::usage_cost(registry, registry_id, &usage);
+    }
+    usage
+}
+
+/// Per-member progress while a council runs: `member_*` events with
+/// `--format jsonl`, or a status line on an interactive stderr in text mode.
+#[derive(Clone, Copy)]
+struct CouncilProgress {
+    format: OutputFormat,
+    status_line: bool,
+}
+
+impl CouncilProgress {
+    fn new(format: OutputFormat) -> Self {
+        Self {
+            format,
+            status_line: matches!(format, OutputFormat::Text) && std::io::stderr().is_terminal(),
+        }
+    }
+
+    /// Best effort: a write error must not stop the council before its
+    /// artifacts and budget are recorded. A broken stdout still fails the
+    /// final `council` line.
+    fn event(&self, kind: &str, data: Value) {
+        if matches!(self.format, OutputFormat::Jsonl) {
+            let _ = write_jsonl(kind, &data);
+        }
+    }
+
+    fn status(&self, round: u32, rounds: u32, answered: usize, failed: usize, total: usize) {
+        if !self.status_line {
+            return;
+        }
+        let round = if rounds > 1 {
+            format!(" round {round}/{rounds},")
+        } else {
+            String::new()
+        };
+        let failed = if failed > 0 {
+            format!(", {failed} failed")
+        } else {
+            String::new()
+        };
+        eprint!("\r\x1b[2Kcouncil:{round} {answered}/{total} answered{failed}");
+        let _ = std::io::stderr().flush();
+    }
+
+    fn clear_status(&self) {
+        if self.status_line {
+            eprint!("\r\x1b[2K");
+        }
+    }
+}
+
+/// A member's conversation and prompt, shared by members rendering the bundle
+/// in the same format.
+type MemberInput = (std::sync::Arc<Conversation>, std::sync::Arc<String>);
+
+fn write_model_artifacts(models_dir: &Path, artifacts: &[(usize, CouncilModelArtifact)]) {
+    if let Err(error) = fs::create_dir_all(models_dir) {
         eprintln!(
             "warning: could not create council model artifact directory {}: {error}",
             models_dir.display()
@@ -534,13 +1437,17 @@ fn failed_model_artifact(
     provider: String,
     pricing: yoetz_core::types::PricingEstimate,
     error: String,
+    attempts: Vec<CallAttempt>,
 ) -> CouncilModelArtifact {
     CouncilModelArtifact {
         status: "failed",
         model,
         provider,
         content: None,
-        usage: Usage::default(),
+        usage: Usage {
+            attempts,
+            ..Usage::default()
+        },
         pricing,
         response_id: None,
         error: Some(error),
@@ -599,7 +1506,10 @@ fn prefixed_council_provider(model: &str) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
-    use super::{model_artifact_slug, write_model_artifacts, CouncilModelArtifact};
+    use super::{
+        agreement, consensus_key, debate_prompt, model_artifact_slug, response_label,
+        synthesis_prompt, write_model_artifacts, CouncilModelArtifact, CouncilModelResult,
+    };
     use std::fs;
     use yoetz_core::types::{PricingEstimate, Usage};
 
@@ -612,6 +1522,78 @@ mod tests {
         assert_eq!(model_artifact_slug("///"), "model");
     }
 
+    #[test]
+    fn synthesis_prompt_labels_answers_anonymously() {
+        assert_eq!(response_label(0), "A");
+        assert_eq!(response_label(25), "Z");
+        assert_eq!(response_label(27), "B2");
+
+        let prompt = synthesis_prompt(
+            "Which cache?",
+            &[("A".to_string(), "LRU\n"), ("B".to_string(), "LFU")],
+        );
+        assert!(prompt.contains("## Question\n\nWhich cache?\n"));
+        let a = prompt.find("## Response A\n\nLRU\n").unwrap();
+        let b = prompt.find("## Response B\n\nLFU\n").unwrap();
+        assert!(a < b);
+    }
+
+    fn model_result(
+        member: usize,
+        name: Option<&str>,
+        model: &str,
+        content: &str,
+    ) -> CouncilModelResult {
+        CouncilModelResult {
+            member,
+            name: name.map(str::to_string),
+            model: model.to_string(),
+            content: content.to_string(),
+            usage: Usage::default(),
+            pricing: PricingEstimate::default(),
+            response_id: None,
+            from_round: None,
+        }
+    }
+
+    #[test]
+    fn consensus_key_separates_members_sharing_a_model() {
+        let results = vec![
+            model_result(0, Some("skeptic"), "alpha", "{}"),
+            model_result(1, None, "alpha", "{}"),
+            model_result(2, None, "alpha", "{}"),
+            model_result(3, None, "beta", "{}"),
+        ];
+        let keys: Vec<String> = results
+            .iter()
+            .map(|result| consensus_key(result, &results))
+            .collect();
+        assert_eq!(keys, ["skeptic", "alpha#1", "alpha#2", "beta"]);
+    }
+
+    #[test]
+    fn debate_prompt_shows_own_answer_and_anonymized_others() {
+        let answers = vec![
+            Some(model_result(0, None, "alpha", "use LRU")),
+            None,
+            Some(model_result(2, None, "gamma", "use LFU")),
+        ];
+        let prompt = debate_prompt("Which cache?", 2, 0, &answers);
+        assert!(prompt.starts_with("Which cache?\n"));
+        assert!(prompt.contains("# Debate round 2"));
+        assert!(prompt.contains("## Your previous answer\n\nuse LRU\n"));
+        assert!(prompt.contains("## Response A\n\nuse LFU\n"));
+        assert!(!prompt.contains("## Response B"));
+        assert!(!prompt.contains("gamma"));
+    }
+
+    #[test]
+    fn agreement_is_mean_pairwise_word_overlap() {
+        assert_eq!(agreement(&["only one"]), None);
+        assert_eq!(agreement(&["Use LRU.", "use lru"]), Some(1.0));
+        assert_eq!(agreement(&["use lru", "use lfu"]), Some(1.0 / 3.0));
+    }
+
     #[test]
     fn model_artifact_write_failure_is_best_effort() {
         let session = tempfile::tempdir().unwrap();
@@ -630,7 +1612,7 @@ mod tests {
             },
         )];
 
-        write_model_artifacts(session.path(), &artifacts);
+        write_model_artifacts(&session.path().join("models"), &artifacts);
         assert!(session.path().join("models").is_file());
     }
 }
@@ -1,17 +1,22 @@
 use anyhow::{anyhow, Result};
 
+use crate::cache::{request_key, CachedResponse, ResponseCache};
+use crate::conversation::{read_system, Conversation};
+use crate::retry::{call_with_retry, ModelTarget};
 use crate::ReviewResult;
 use crate::{budget, registry};
 use crate::{
     build_review_diff_prompt, build_review_file_prompt, call_model, git_diff, maybe_write_output,
     normalize_model_name_with_aliases, read_text_file, resolve_max_output_tokens_for_provider,
     resolve_provider_for_model, resolve_registry_model_id, resolve_response_format,
-    validate_cursor_options, AppContext, ReviewArgs, ReviewCommand, ReviewDiffArgs, ReviewFileArgs,
+    validate_cursor_options, AppContext, CallResult, ReviewArgs, ReviewCommand, ReviewDiffArgs,
+    ReviewFileArgs,
 };
+use serde_json::{json, Value};
 use std::path::PathBuf;
-use yoetz_core::bundle::estimate_tokens;
 use yoetz_core::output::{write_json, write_jsonl, OutputFormat};
 use yoetz_core::session::{create_session_dir, write_json as write_json_file, write_text};
+use yoetz_core::tokenizer::count_tokens;
 use yoetz_core::types::{ArtifactPaths, Usage};
 
 const MAX_REVIEW_DIFF_TOKENS: usize = 50_000;
@@ -27,6 +32,66 @@ pub(crate) async fn handle_review(
     }
 }
 
+/// `--cache` key covering everything sent for a review.
+fn review_cache_key(
+    provider: &str,
+    model: &str,
+    conversation: &Conversation,
+    prompt: &str,
+    temperature: f32,
+    max_output_tokens: Option<usize>,
+    response_format: Option<&Value>,
+) -> String {
+    let request = json!({
+        "provider": provider,
+        "model": model,
+        "messages": conversation.chat_messages(prompt),
+        "temperature": temperature,
+        "max_output_tokens": max_output_tokens,
+        "response_format": response_format,
+    });
+    request_key("review", &request)
+}
+
+/// Send the review, retrying rate limits and outages per `[retry]`.
+async fn call_reviewer(
+    ctx: &AppContext,
+    provider: &str,
+    model: &str,
+    conversation: &Conversation,
+    prompt: &str,
+    temperature: f32,
+    max_output_tokens: Option<usize>,
+    response_format: Option<Value>,
+) -> Result<CallResult> {
+    let target = ModelTarget {
+        provider: Some(provider.to_string()),
+        model: model.to_string(),
+        max_output_tokens,
+    };
+    let outcome = call_with_retry(&ctx.retry, &[target], |_| {
+        call_model(
+            &ctx.litellm,
+            ctx.timeout_duration,
+            &ctx.cursor_discovery,
+            Some(provider),
+            model,
+            conversation,
+            prompt,
+            temperature,
+            max_output_tokens,
+            response_format.clone(),
+            &[],
+            None,
+        )
+    })
+    .await;
+    let attempts = outcome.recorded_attempts();
+    let (_, mut result) = outcome.result?;
+    result.usage.attempts = attempts;
+    Ok(result)
+}
+
 async fn handle_review_diff(
     ctx: &AppContext,
     args: ReviewDiffArgs,
@@ -99,8 +164,14 @@ async fn handle_review_diff(
         diff.push_str("\n\n... [diff truncated — exceeded --max-diff-bytes limit]");
     }
 
-    let review_prompt = build_review_diff_prompt(&diff, args.prompt.as_deref());
-    let input_tokens = estimate_tokens(review_prompt.len());
+    let (instructions, review_prompt) = build_review_diff_prompt(&diff, args.prompt.as_deref());
+    let conversation = Conversation::with_instructions(
+        instructions,
+        read_system(args.system.clone(), args.system_file.as_deref())?,
+    );
+    let token_model = Some(registry_id.as_deref().unwrap_or(&model));
+    let input_tokens =
+        count_tokens(token_model, &conversation.text()) + count_tokens(token_model, &review_prompt);
     ensure_review_diff_size(input_tokens)?;
     let output_tokens = max_output_tokens.unwrap_or(4096);
     let pricing = registry::estimate_pricing(
@@ -110,8 +181,22 @@ async fn handle_review_diff(
         output_tokens,
     )?;
 
+    let cache = ResponseCache::new(&config.cache, args.cache);
+    let cache_key = (cache.enabled() && !args.dry_run).then(|| {
+        review_cache_key(
+            &provider,
+            &model,
+            &conversation,
+            &review_prompt,
+            args.temperature,
+            max_output_tokens,
+            response_format.as_ref(),
+        )
+    });
+    let cache_hit = cache_key.as_deref().and_then(|key| cache.lookup(key));
+
     let budget_enabled = args.max_cost_usd.is_some() || args.daily_budget_usd.is_some();
-    let budget_reservation = if budget_enabled {
+    let budget_reservation = if budget_enabled && cache_hit.is_none() {
         budget::ensure_budget(
             pricing.estimate_usd,
             args.max_cost_usd,
@@ -127,7 +212,7 @@ async fn handle_review_diff(
         ..Default::default()
     };
     let review_input_path = session.path.join("review_input.txt");
-    write_text(&review_input_path, &review_prompt)?;
+    write_text(&review_input_path, &conversation.flatten(&review_prompt)?)?;
 
     let (content, mut usage, response_id, header_cost) = if args.dry_run {
         (
@@ -136,21 +221,37 @@ async fn handle_review_diff(
             None,
             None,
         )
+    } else if let Some(hit) = &cache_hit {
+        (
+            hit.content.clone(),
+            hit.hit_usage(),
+            hit.response_id.clone(),
+            None,
+        )
     } else {
-        let result = call_model(
-            &ctx.litellm,
-            ctx.timeout_duration,
-            &ctx.cursor_discovery,
-            Some(&provider),
+        let result = call_reviewer(
+            ctx,
+            &provider,
             &model,
+            &conversation,
             &review_prompt,
             args.temperature,
             max_output_tokens,
             response_format.clone(),
-            &[],
-            None,
         )
         .await?;
+        if let Some(key) = &cache_key {
+            let entry = CachedResponse::new(
+                Some(provider.clone()),
+                model.clone(),
+                result.content.clone(),
+                result.usage.clone(),
+                result.response_id.clone(),
+            );
+            if let Err(e) = cache.store(key, &entry) {
+                eprintln!("warning: response cache write failed: {e}");
+            }
+        }
         (
             result.content,
             result.usage,
@@ -189,7 +290,9 @@ async fn handle_review_diff(
         pricing,
         usage,
         content,
+        findings: None,
         artifacts,
+        cached: cache_hit.is_some(),
     };
 
     let response_json = PathBuf::from(&result.artifacts.session_dir).join("review.json");
@@ -221,10 +324,111 @@ fn ensure_review_diff_size(input_tokens: usize) -> Result<()> {
     Ok(())
 }
 
+/// Parse `findings` out of a JSON review of a file whose shown lines start
+/// at `first_line` (the gutter's first number), normalizing each `line` to a
+/// file line number. Models often answer `"12"`, `"L12"`, `"12-14"`, or echo
+/// the gutter (`"12 | ..."`); ranges also get an `end_line`. A line outside the
+/// shown range becomes `null`, with the model's value kept as `reported_line`.
+fn review_findings(content: &str, first_line: usize, line_count: usize) -> Option<Vec<Value>> {
+    let review = review_json(content)?;
+    let findings = review.get("findings")?.as_array()?;
+    let shown = first_line..first_line + line_count;
+    Some(
+        findings
+            .iter()
+            .map(|finding| {
+                let mut finding = finding.clone();
+                let Some(object) = finding.as_object_mut() else {
+                    return finding;
+                };
+                let Some(reported) = object.get("line").cloned() else {
+                    return finding;
+                };
+                match reported_lines(&reported) {
+                    Some((start, end)) if shown.contains(&start) => {
+                        object.insert("line".to_string(), start.into());
+                        if let Some(end) = end.filter(|end| *end > start) {
+                            let end = end.min(shown.end - 1);
+                            object.insert("end_line".to_string(), end.into());
+                        }
+                    }
+                    _ if reported.is_null() => {}
+                    _ => {
+                        object.insert("line".to_string(), Value::Null);
+                        object.insert("reported_line".to_string(), reported);
+                    }
+                }
+                finding
+            })
+            .collect(),
+    )
+}
+
+/// The JSON object of a review, allowing a Markdown fence or prose around it.
+fn review_json(content: &str) -> Option<Value> {
+    let start = content.find('{')?;
+    let end = content.rfind('}')?;
+    serde_json::from_str(content.get(start..=end)?).ok()
+}
+
+/// Start and optional end line of a finding's `line` value.
+fn reported_lines(value: &Value) -> Option<(usize, Option<usize>)> {
+    if let Some(line) = value.as_u64() {
+        return Some((line as usize, None));
+    }
+    let text = value.as_str()?.trim();
+    let text = text
+        .strip_prefix("line")
+        .or_else(|| text.strip_prefix("Line"))
+        .or_else(|| text.strip_prefix('L'))
+        .unwrap_or(text)
+        .trim_start();
+    let mut numbers = text
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty());
+    let start = numbers.next()?.parse().ok()?;
+    let is_range = text
+        .trim_start_matches(|c: char| c.is_ascii_digit())
+        .trim_start()
+        .starts_with(['-', '–']);
+    let end = if is_range {
+        numbers.next().and_then(|end| end.parse().ok())
+    } else {
+        None
+    };
+    Some((start, end))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn review_findings_map_reported_lines_to_file_lines() {
+        let content = r#"```json
+{"summary": "s", "findings": [
+  {"line": 3, "message": "a"},
+  {"line": "L4-6", "message": "b"},
+  {"line": " 7 | let x = 1;", "message": "c"},
+  {"line": 42, "message": "d"},
+  {"line": null, "message": "e"},
+  {"line": "5-99", "message": "f"}
+]}
+```"#;
+        let findings = review_findings(content, 1, 8).unwrap();
+        assert_eq!(findings[0]["line"], 3);
+        assert_eq!(findings[1]["line"], 4);
+        assert_eq!(findings[1]["end_line"], 6);
+        assert_eq!(findings[2]["line"], 7);
+        assert!(findings[2].get("end_line").is_none());
+        assert!(findings[3]["line"].is_null());
+        assert_eq!(findings[3]["reported_line"], 42);
+        assert!(findings[4]["line"].is_null());
+        assert!(findings[4].get("reported_line").is_none());
+        assert_eq!(findings[5]["end_line"], 8);
+        assert!(review_findings("no json here", 1, 8).is_none());
+    }
+
     #[test]
     fn review_diff_guardrail_rejects_large_prompts() {
         let err = ensure_review_diff_size(MAX_REVIEW_DIFF_TOKENS + 1).unwrap_err();
@@ -293,13 +497,21 @@ async fn handle_review_file(
     let max_total_bytes = args.max_total_bytes.unwrap_or(max_file_bytes);
     let max_bytes = max_file_bytes.min(max_total_bytes);
     let (content, truncated) = read_text_file(args.path.as_path(), max_bytes)?;
-    let review_prompt = build_review_file_prompt(
+    let line_numbers = !args.no_line_numbers;
+    let (instructions, review_prompt) = build_review_file_prompt(
         args.path.as_path(),
         &content,
         truncated,
+        line_numbers,
         args.prompt.as_deref(),
     );
-    let input_tokens = estimate_tokens(review_prompt.len());
+    let conversation = Conversation::with_instructions(
+        instructions,
+        read_system(args.system.clone(), args.system_file.as_deref())?,
+    );
+    let token_model = Some(registry_id.as_deref().unwrap_or(&model));
+    let input_tokens =
+        count_tokens(token_model, &conversation.text()) + count_tokens(token_model, &review_prompt);
     let output_tokens = max_output_tokens.unwrap_or(4096);
     let pricing = registry::estimate_pricing(
         registry_cache.as_ref(),
@@ -308,8 +520,22 @@ async fn handle_review_file(
         output_tokens,
     )?;
 
+    let cache = ResponseCache::new(&config.cache, args.cache);
+    let cache_key = (cache.enabled() && !args.dry_run).then(|| {
+        review_cache_key(
+            &provider,
+            &model,
+            &conversation,
+            &review_prompt,
+            args.temperature,
+            max_output_tokens,
+            response_format.as_ref(),
+        )
+    });
+    let cache_hit = cache_key.as_deref().and_then(|key| cache.lookup(key));
+
     let budget_enabled = args.max_cost_usd.is_some() || args.daily_budget_usd.is_some();
-    let budget_reservation = if budget_enabled {
+    let budget_reservation = if budget_enabled && cache_hit.is_none() {
         budget::ensure_budget(
             pricing.estimate_usd,
             args.max_cost_usd,
@@ -325,7 +551,7 @@ async fn handle_review_file(
         ..Default::default()
     };
     let review_input_path = session.path.join("review_input.txt");
-    write_text(&review_input_path, &review_prompt)?;
+    write_text(&review_input_path, &conversation.flatten(&review_prompt)?)?;
 
     let (output, mut usage, response_id, header_cost) = if args.dry_run {
         (
@@ -334,21 +560,37 @@ async fn handle_review_file(
             None,
             None,
         )
+    } else if let Some(hit) = &cache_hit {
+        (
+            hit.content.clone(),
+            hit.hit_usage(),
+            hit.response_id.clone(),
+            None,
+        )
     } else {
-        let result = call_model(
-            &ctx.litellm,
-            ctx.timeout_duration,
-            &ctx.cursor_discovery,
-            Some(&provider),
+        let result = call_reviewer(
+            ctx,
+            &provider,
             &model,
+            &conversation,
             &review_prompt,
             args.temperature,
             max_output_tokens,
             response_format.clone(),
-            &[],
-            None,
         )
         .await?;
+        if let Some(key) = &cache_key {
+            let entry = CachedResponse::new(
+                Some(provider.clone()),
+                model.clone(),
+                result.content.clone(),
+                result.usage.clone(),
+                result.response_id.clone(),
+            );
+            if let Err(e) = cache.store(key, &entry) {
+                eprintln!("warning: response cache write failed: {e}");
+            }
+        }
         (
             result.content,
             result.usage,
@@ -386,8 +628,12 @@ async fn handle_review_file(
         model,
         pricing,
         usage,
+        findings: (!args.dry_run)
+            .then(|| review_findings(&output, 1, content.lines().count()))
+            .flatten(),
         content: output,
         artifacts,
+        cached: cache_hit.is_some(),
     };
 
     let response_json = PathBuf::from(&result.artifacts.session_dir).join("review.json");
@@ -0,0 +1,428 @@
+//! Field-level agreement for `council --consensus`.
+//!
+//! Each member answer is parsed as JSON and validated against the
+//! `--response-schema`; answers that fail are reported as `invalid` and left
+//! out. The remaining answers are compared field by field over the schema's
+//! top-level `properties` (or as a single `$` field when the schema has none):
+//!
+//! - arrays by set overlap: the consensus keeps the items most answers
+//!   contain, scored by mean pairwise Jaccard similarity;
+//! - numbers by spread: the consensus is the median, scored by how small the
+//!   range is relative to the largest magnitude;
+//! - everything else (enums, booleans, strings, objects) by majority vote,
+//!   scored by the winning share. A missing field votes for `null`.
+
+use anyhow::{anyhow, Result};
+use jsonschema::Validator;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A number within this fraction of the median still agrees with it.
+const NUMERIC_TOLERANCE: f64 = 0.1;
+
+/// Field name used when the schema has no top-level `properties`.
+const ROOT_FIELD: &str = "$";
+
+/// The `--response-schema`, compiled once before any member is called.
+pub(crate) struct ConsensusSchema {
+    validator: Validator,
+    fields: Vec<(String, FieldKind)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FieldKind {
+    Vote,
+    Set,
+    Numeric,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Consensus {
+    /// Mean of the field agreements, 0 to 1; absent without a valid answer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) agreement: Option<f64>,
+    /// The consensus answer assembled from each field's consensus value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) value: Option<Value>,
+    pub(crate) fields: BTreeMap<String, FieldConsensus>,
+    /// Members that dissent on at least one field.
+    pub(crate) dissenting_models: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) invalid: Vec<InvalidAnswer>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct FieldConsensus {
+    /// `majority`, `set_overlap`, or `numeric_spread`.
+    pub(crate) method: &'static str,
+    pub(crate) value: Value,
+    pub(crate) agreement: f64,
+    /// Largest minus smallest value, for numeric fields.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) spread: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) dissenters: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct InvalidAnswer {
+    pub(crate) model: String,
+    pub(crate) error: String,
+}
+
+impl ConsensusSchema {
+    /// The schema inside a resolved `json_schema` response format.
+    pub(crate) fn from_response_format(response_format: Option<&Value>) -> Result<Self> {
+        let schema = response_format
+            .and_then(|format| format.pointer("/json_schema/schema"))
+            .ok_or_else(|| anyhow!("--consensus requires --response-schema"))?;
+        let validator =
+            Validator::new(schema).map_err(|e| anyhow!("invalid response schema: {e}"))?;
+        let fields = match schema.get("properties").and_then(Value::as_object) {
+            Some(properties) => properties
+                .iter()
+                .map(|(name, property)| (name.clone(), field_kind(property)))
+                .collect(),
+            None => vec![(ROOT_FIELD.to_string(), field_kind(schema))],
+        };
+        Ok(Self { validator, fields })
+    }
+
+    /// Compare `(model, content)` answers field by field.
+    pub(crate) fn evaluate(&self, answers: &[(&str, &str)]) -> Consensus {
+        let mut valid = Vec::new();
+        let mut invalid = Vec::new();
+        for (model, content) in answers {
+            match self.parse(content) {
+                Ok(value) => valid.push((*model, value)),
+                Err(error) => invalid.push(InvalidAnswer {
+                    model: model.to_string(),
+                    error,
+                }),
+            }
+        }
+        if valid.is_empty() {
+            return Consensus {
+                agreement: None,
+                value: None,
+                fields: BTreeMap::new(),
+                dissenting_models: Vec::new(),
+                invalid,
+            };
+        }
+
+        let mut fields = BTreeMap::new();
+        for (name, kind) in &self.fields {
+            let values: Vec<(&str, Value)> = valid
+                .iter()
+                .map(|(model, answer)| {
+                    let value = if name == ROOT_FIELD && self.fields.len() == 1 {
+                        answer.clone()
+                    } else {
+                        answer.get(name).cloned().unwrap_or(Value::Null)
+                    };
+                    (*model, value)
+                })
+                .collect();
+            fields.insert(name.clone(), field_consensus(*kind, &values));
+        }
+
+        let agreement =
+            fields.values().map(|field| field.agreement).sum::<f64>() / fields.len().max(1) as f64;
+        let value = match fields.get(ROOT_FIELD) {
+            Some(root) if fields.len() == 1 => root.value.clone(),
+            _ => Value::Object(
+                fields
+                    .iter()
+                    .filter(|(_, field)| !field.value.is_null())
+                    .map(|(name, field)| (name.clone(), field.value.clone()))
+                    .collect::<Map<_, _>>(),
+            ),
+        };
+        let dissenting: BTreeSet<&str> = fields
+            .values()
+            .flat_map(|field| field.dissenters.iter().map(String::as_str))
+            .collect();
+        let dissenting_models = valid
+            .iter()
+            .map(|(model, _)| *model)
+            .filter(|model| dissenting.contains(model))
+            .map(str::to_string)
+            .collect();
+
+        Consensus {
+            agreement: Some(agreement),
+            value: Some(value),
+            fields,
+            dissenting_models,
+            invalid,
+        }
+    }
+
+    fn parse(&self, content: &str) -> std::result::Result<Value, String> {
+        let value = parse_json(content).ok_or_else(|| "answer is not JSON".to_string())?;
+        self.validator
+            .validate(&value)
+            .map_err(|e| format!("answer does not match schema: {e}"))?;
+        Ok(value)
+    }
+}
+
+/// The JSON answer, allowing a Markdown fence or prose around an object.
+fn parse_json(content: &str) -> Option<Value> {
+    if let Ok(value) = serde_json::from_str(content.trim()) {
+        return Some(value);
+    }
+    let start = content.find('{')?;
+    let end = content.rfind('}')?;
+    serde_json::from_str(content.get(start..=end)?).ok()
+}
+
+fn field_kind(schema: &Value) -> FieldKind {
+    if schema.get("enum").is_some() || schema.get("const").is_some() {
+        return FieldKind::Vote;
+    }
+    let declared = match schema.get("type") {
+        Some(Value::String(kind)) => Some(kind.as_str()),
+        Some(Value::Array(kinds)) => kinds
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|kind| *kind != "null"),
+        _ => None,
+    };
+    match declared {
+        Some("array") => FieldKind::Set,
+        Some("number" | "integer") => FieldKind::Numeric,
+        _ => FieldKind::Vote,
+    }
+}
+
+fn field_consensus(kind: FieldKind, values: &[(&str, Value)]) -> FieldConsensus {
+    let numbers: Option<Vec<f64>> = values.iter().map(|(_, value)| value.as_f64()).collect();
+    let arrays: Option<Vec<&Vec<Value>>> =
+        values.iter().map(|(_, value)| value.as_array()).collect();
+    // Answers that leave a field out or null it fall back to a vote.
+    match (kind, numbers, arrays) {
+        (FieldKind::Numeric, Some(numbers), _) => numeric_spread(values, &numbers),
+        (FieldKind::Set, _, Some(arrays)) => set_overlap(values, &arrays),
+        _ => majority(values),
+    }
+}
+
+fn majority(values: &[(&str, Value)]) -> FieldConsensus {
+    let mut tallies: Vec<(&Value, usize)> = Vec::new();
+    for (_, value) in values {
+        match tallies.iter_mut().find(|(seen, _)| *seen == value) {
+            Some((_, count)) => *count += 1,
+            None => tallies.push((value, 1)),
+        }
+    }
+    // The first-seen value wins a tie.
+    let (winner, count) = tallies
+        .iter()
+        .fold(
+            None,
+            |best: Option<(&Value, usize)>, &(value, count)| match best {
+                Some((_, best_count)) if best_count >= count => best,
+                _ => Some((value, count)),
+            },
+        )
+        .unwrap_or((&Value::Null, 0));
+    FieldConsensus {
+        method: "majority",
+        value: winner.clone(),
+        agreement: count as f64 / values.len() as f64,
+        spread: None,
+        dissenters: dissenters(values, |value| value != winner),
+    }
+}
+
+fn set_overlap(values: &[(&str, Value)], arrays: &[&Vec<Value>]) -> FieldConsensus {
+    let sets: Vec<BTreeSet<String>> = arrays
+        .iter()
+        .map(|items| items.iter().map(Value::to_string).collect())
+        .collect();
+    let mut kept = Vec::new();
+    let mut kept_keys = BTreeSet::new();
+    for items in arrays {
+        for item in items.iter() {
+            let key = item.to_string();
+            let support = sets.iter().filter(|set| set.contains(&key)).count();
+            if support * 2 > sets.len() && kept_keys.insert(key) {
+                kept.push(item.clone());
+            }
+        }
+    }
+    let mut total = 0.0;
+    let mut pairs = 0_usize;
+    for (idx, left) in sets.iter().enumerate() {
+        for right in &sets[idx + 1..] {
+            total += jaccard(left, right);
+            pairs += 1;
+        }
+    }
+    let agreement = if pairs == 0 {
+        1.0
+    } else {
+        total / pairs as f64
+    };
+    let mut differs = sets.iter().map(|set| *set != kept_keys);
+    FieldConsensus {
+        method: "set_overlap",
+        value: Value::Array(kept),
+        agreement,
+        spread: None,
+        dissenters: dissenters(values, |_| differs.next().unwrap_or(false)),
+    }
+}
+
+fn jaccard(left: &BTreeSet<String>, right: &BTreeSet<String>) -> f64 {
+    let union = left.union(right).count();
+    if union == 0 {
+        return 1.0;
+    }
+    left.intersection(right).count() as f64 / union as f64
+}
+
+fn numeric_spread(values: &[(&str, Value)], numbers: &[f64]) -> FieldConsensus {
+    let mut sorted = numbers.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    let median = if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    };
+    let (min, max) = (sorted[0], sorted[sorted.len() - 1]);
+    let spread = max - min;
+    let scale = min.abs().max(max.abs());
+    let agreement = if spread == 0.0 || scale == 0.0 {
+        1.0
+    } else {
+        (1.0 - spread / scale).clamp(0.0, 1.0)
+    };
+    let tolerance = NUMERIC_TOLERANCE * median.abs();
+    // Integer answers keep an integer median when it is whole.
+    let value = if values.iter().all(|(_, value)| value.is_i64()) && median.fract() == 0.0 {
+        Value::from(median as i64)
+    } else {
+        Value::from(median)
+    };
+    FieldConsensus {
+        method: "numeric_spread",
+        value,
+        agreement,
+        spread: Some(spread),
+        dissenters: dissenters(values, |value| {
+            value
+                .as_f64()
+                .is_some_and(|number| (number - median).abs() > tolerance)
+        }),
+    }
+}
+
+fn dissenters(values: &[(&str, Value)], mut dissents: impl FnMut(&Value) -> bool) -> Vec<String> {
+    values
+        .iter()
+        .filter(|(_, value)| dissents(value))
+        .map(|(model, _)| model.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConsensusSchema;
+    use serde_json::json;
+
+    fn schema() -> ConsensusSchema {
+        let format = json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": "triage",
+                "schema": {
+                    "type": "object",
+                    "properties": {
+                        "severity": {"type": "string", "enum": ["low", "high"]},
+                        "duplicate": {"type": "boolean"},
+                        "labels": {"type": "array", "items": {"type": "string"}},
+                        "priority": {"type": "integer"}
+                    },
+                    "required": ["severity"]
+                }
+            }
+        });
+        ConsensusSchema::from_response_format(Some(&format)).unwrap()
+    }
+
+    #[test]
+    fn consensus_votes_overlaps_and_measures_spread_per_field() {
+        let consensus = schema().evaluate(&[
+            (
+                "a",
+                r#"{"severity":"high","duplicate":false,"labels":["bug","ui"],"priority":2}"#,
+            ),
+            (
+                "b",
+                "```json\n{\"severity\":\"high\",\"duplicate\":false,\"labels\":[\"bug\"],\"priority\":2}\n```",
+            ),
+            (
+                "c",
+                r#"{"severity":"low","duplicate":false,"labels":["bug","ui"],"priority":4}"#,
+            ),
+            ("d", r#"{"severity":"urgent"}"#),
+            ("e", "no idea"),
+        ]);
+
+        let severity = &consensus.fields["severity"];
+        assert_eq!(severity.method, "majority");
+        assert_eq!(severity.value, json!("high"));
+        assert!((severity.agreement - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(severity.dissenters, ["c"]);
+
+        assert_eq!(consensus.fields["duplicate"].agreement, 1.0);
+
+        let labels = &consensus.fields["labels"];
+        assert_eq!(labels.method, "set_overlap");
+        assert_eq!(labels.value, json!(["bug", "ui"]));
+        assert!((labels.agreement - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(labels.dissenters, ["b"]);
+
+        let priority = &consensus.fields["priority"];
+        assert_eq!(priority.method, "numeric_spread");
+        assert_eq!(priority.value, json!(2));
+        assert_eq!(priority.spread, Some(2.0));
+        assert_eq!(priority.agreement, 0.5);
+        assert_eq!(priority.dissenters, ["c"]);
+
+        assert_eq!(consensus.dissenting_models, ["b", "c"]);
+        assert_eq!(
+            consensus.value,
+            Some(json!({
+                "severity": "high",
+                "duplicate": false,
+                "labels": ["bug", "ui"],
+                "priority": 2
+            }))
+        );
+        let invalid: Vec<_> = consensus.invalid.iter().map(|i| i.model.as_str()).collect();
+        assert_eq!(invalid, ["d", "e"]);
+        let expected = (2.0 / 3.0 + 1.0 + 2.0 / 3.0 + 0.5) / 4.0;
+        assert!((consensus.agreement.unwrap() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn consensus_without_valid_answers_has_no_agreement() {
+        let consensus = schema().evaluate(&[("a", "{}")]);
+        assert_eq!(consensus.agreement, None);
+        assert_eq!(consensus.invalid.len(), 1);
+    }
+
+    #[test]
+    fn consensus_requires_a_json_schema_format() {
+        let error = ConsensusSchema::from_response_format(Some(&json!({"type": "json_object"})))
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("--response-schema"));
+    }
+}
@@ -0,0 +1,625 @@
+//! System prompts and prior turns sent ahead of the prompt.
+//!
+//! `--system`/`--system-file` set the system message and `--messages` loads a
+//! JSONL transcript, one `{"role": ..., "content": ...}` object per line, where
+//! `content` is a string or an array of OpenAI-style `text` and `image_url`
+//! parts. Image URLs that are neither `http(s)://` nor `data:` are read as
+//! files relative to the transcript.
+//!
+//! With `--prompt-cache` the rendered bundle travels as `context`: its own
+//! text part ahead of the prompt in the final user turn, so calls over the
+//! same bundle share a prefix that providers can serve from their prompt
+//! caches.
+//!
+//! Each `ask` session also records what it sent and received in
+//! `conversation.json`, which `ask --continue` and `ask --thread` replay.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::borrow::Cow;
+use std::fs;
+use std::path::{Path, PathBuf};
+use yoetz_core::media::MediaType;
+use yoetz_core::session::{list_sessions_in, write_json};
+
+use crate::followup::validate_thread_label;
+use crate::parse_media_input;
+
+const ROLES: &[&str] = &["system", "developer", "user", "assistant"];
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Conversation {
+    pub(crate) system: Option<String>,
+    pub(crate) turns: Vec<Turn>,
+    /// Bundle context sent ahead of the prompt (`--prompt-cache`).
+    pub(crate) context: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Turn {
+    pub(crate) role: String,
+    pub(crate) parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Part {
+    Text(String),
+    /// An `http(s)://` or `data:` URL.
+    Image(String),
+}
+
+impl Turn {
+    /// The `--messages` line / chat message form of this turn.
+    pub(crate) fn to_json(&self) -> Value {
+        let content = match self.parts.as_slice() {
+            [Part::Text(text)] => json!(text),
+            parts => Value::Array(
+                parts
+                    .iter()
+                    .map(|part| match part {
+                        Part::Text(text) => json!({ "type": "text", "text": text }),
+                        Part::Image(url) => {
+                            json!({ "type": "image_url", "image_url": { "url": url } })
+                        }
+                    })
+                    .collect(),
+            ),
+        };
+        json!({ "role": self.role, "content": content })
+    }
+}
+
+#[derive(Deserialize)]
+struct RawTurn {
+    role: String,
+    content: RawContent,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawContent {
+    Text(String),
+    Parts(Vec<RawPart>),
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum RawPart {
+    Text { text: String },
+    ImageUrl { image_url: RawImageUrl },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawImageUrl {
+    Url(String),
+    Object { url: String },
+}
+
+impl Conversation {
+    pub(crate) fn load(
+        system: Option<String>,
+        system_file: Option<&Path>,
+        messages: Option<&Path>,
+    ) -> Result<Self> {
+        let system = read_system(system, system_file)?;
+        let turns = match messages {
+            Some(path) => load_messages(path)?,
+            None => Vec::new(),
+        };
+        Ok(Self {
+            system,
+            turns,
+            context: None,
+        })
+    }
+
+    /// Append `extra` (e.g. the user's `--system`) after built-in instructions.
+    pub(crate) fn with_instructions(instructions: String, extra: Option<String>) -> Self {
+        let system = match extra {
+            Some(extra) if !extra.trim().is_empty() => format!("{instructions}\n{extra}"),
+            _ => instructions,
+        };
+        Self {
+            system: Some(system),
+            turns: Vec::new(),
+            context: None,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.system.is_none() && self.turns.is_empty()
+    }
+
+    /// A transcript that already ends with a user turn can stand in for the prompt.
+    pub(crate) fn ends_with_user_turn(&self) -> bool {
+        self.turns.last().is_some_and(|turn| turn.role == "user")
+    }
+
+    pub(crate) fn has_images(&self) -> bool {
+        self.turns
+            .iter()
+            .flat_map(|turn| &turn.parts)
+            .any(|part| matches!(part, Part::Image(_)))
+    }
+
+    /// All text sent besides the prompt and bundle context, for token estimates.
+    pub(crate) fn text(&self) -> String {
+        let mut text = self.system.clone().unwrap_or_default();
+        for part in self.turns.iter().flat_map(|turn| &turn.parts) {
+            if let Part::Text(part) = part {
+                text.push('\n');
+                text.push_str(part);
+            }
+        }
+        text
+    }
+
+    /// OpenAI chat `messages`, ending with `prompt` as the user turn. An empty
+    /// prompt is skipped when the transcript already ends with a user turn.
+    pub(crate) fn chat_messages(&self, prompt: &str) -> Vec<Value> {
+        let mut messages = Vec::with_capacity(self.turns.len() + 2);
+        if let Some(system) = &self.system {
+            messages.push(json!({ "role": "system", "content": system }));
+        }
+        messages.extend(self.turns.iter().map(Turn::to_json));
+        if self.sends_prompt(prompt) {
+            messages.push(self.prompt_turn(prompt).to_json());
+        }
+        messages
+    }
+
+    /// The final user turn: the bundle context as its own part, when split
+    /// out, then the prompt.
+    pub(crate) fn prompt_turn(&self, prompt: &str) -> Turn {
+        let mut parts = Vec::with_capacity(2);
+        if let Some(context) = &self.context {
+            parts.push(Part::Text(context.clone()));
+        }
+        if !prompt.is_empty() {
+            parts.push(Part::Text(prompt.to_string()));
+        }
+        Turn {
+            role: "user".to_string(),
+            parts,
+        }
+    }
+
+    /// The prompt with the bundle context in front, for backends that take a
+    /// single text.
+    pub(crate) fn prompt_text<'a>(&self, prompt: &'a str) -> Cow<'a, str> {
+        match &self.context {
+            Some(context) => Cow::Owned(format!("{context}{prompt}")),
+            None => Cow::Borrowed(prompt),
+        }
+    }
+
+    pub(crate) fn sends_prompt(&self, prompt: &str) -> bool {
+        !prompt.is_empty() || !self.ends_with_user_turn()
+    }
+
+    /// Single-string form for backends without message roles. Returns the
+    /// prompt (after any bundle context) unlabelled when there is nothing to
+    /// prepend.
+    pub(crate) fn flatten<'a>(&self, prompt: &'a str) -> Result<Cow<'a, str>> {
+        if self.is_empty() {
+            return Ok(self.prompt_text(prompt));
+        }
+        let mut out = String::new();
+        if let Some(system) = &self.system {
+            push_section(&mut out, "system", system);
+        }
+        for turn in &self.turns {
+            let mut text = String::new();
+            for part in &turn.parts {
+                match part {
+                    Part::Text(part) => text.push_str(part),
+                    Part::Image(_) => {
+                        bail!("image parts in --messages need a provider with image input")
+                    }
+                }
+            }
+            push_section(&mut out, &turn.role, &text);
+        }
+        if self.sends_prompt(prompt) {
+            push_section(&mut out, "user", &self.prompt_text(prompt));
+        }
+        Ok(Cow::Owned(out))
+    }
+}
+
+/// `--system`, or the contents of `--system-file`.
+pub(crate) fn read_system(
+    system: Option<String>,
+    system_file: Option<&Path>,
+) -> Result<Option<String>> {
+    match system_file {
+        Some(path) => fs::read_to_string(path)
+            .map(Some)
+            .with_context(|| format!("read {}", path.display())),
+        None => Ok(system),
+    }
+}
+
+fn push_section(out: &mut String, role: &str, text: &str) {
+    if !out.is_empty() {
+        out.push_str("\n\n");
+    }
+    out.push_str(&format!("[{role}]\n{text}"));
+}
+
+fn load_messages(path: &Path) -> Result<Vec<Turn>> {
+    let text = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let base = path.parent().unwrap_or(Path::new("."));
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            parse_turn(line, base)
+                .with_context(|| format!("{}:{}: invalid message", path.display(), idx + 1))
+        })
+        .collect()
+}
+
+fn parse_turn(line: &str, base: &Path) -> Result<Turn> {
+    turn_from_raw(serde_json::from_str(line)?, base)
+}
+
+fn turn_from_raw(raw: RawTurn, base: &Path) -> Result<Turn> {
+    if !ROLES.contains(&raw.role.as_str()) {
+        bail!(
+            "unsupported role `{}` (expected one of {})",
+            raw.role,
+            ROLES.join(", ")
+        );
+    }
+    let parts = match raw.content {
+        RawContent::Text(text) => vec![Part::Text(text)],
+        RawContent::Parts(parts) => parts
+            .into_iter()
+            .map(|part| match part {
+                RawPart::Text { text } => Ok(Part::Text(text)),
+                RawPart::ImageUrl { image_url } => image_part(image_url, base),
+            })
+            .collect::<Result<_>>()?,
+    };
+    if parts.is_empty() {
+        return Err(anyhow!("message content is empty"));
+    }
+    Ok(Turn {
+        role: raw.role,
+        parts,
+    })
+}
+
+fn image_part(image_url: RawImageUrl, base: &Path) -> Result<Part> {
+    let (RawImageUrl::Url(url) | RawImageUrl::Object { url }) = image_url;
+    if url.starts_with("data:") || url.starts_with("http://") || url.starts_with("https://") {
+        return Ok(Part::Image(url));
+    }
+    let path = base.join(&url);
+    let media = parse_media_input(&path.to_string_lossy(), None, MediaType::Image)?;
+    Ok(Part::Image(media.as_data_url()?))
+}
+
+const TRANSCRIPT_FILE: &str = "conversation.json";
+
+/// The turns an `ask` session sent and the answer it got, written as
+/// `conversation.json` so `ask --continue` and `ask --thread` can replay them.
+/// Lineage follows browser followups: a labeled thread gains one revision per
+/// turn, and continuing an older session records the thread it forked from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub(crate) struct SessionTranscript {
+    pub session_id: String,
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub system: Option<String>,
+    pub messages: Vec<Value>,
+    #[serde(default)]
+    pub parent_session_id: Option<String>,
+    #[serde(default)]
+    pub thread_label: Option<String>,
+    #[serde(default)]
+    pub thread_revision: u64,
+    #[serde(default)]
+    pub forked_from_label: Option<String>,
+}
+
+impl SessionTranscript {
+    pub(crate) fn conversation(&self) -> Result<Conversation> {
+        let turns = self
+            .messages
+            .iter()
+            .enumerate()
+            .map(|(idx, message)| {
+                turn_from_raw(serde_json::from_value(message.clone())?, Path::new("."))
+                    .with_context(|| {
+                        format!("session `{}` message {}: invalid", self.session_id, idx + 1)
+                    })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Conversation {
+            system: self.system.clone(),
+            turns,
+            context: None,
+        })
+    }
+}
+
+/// Where an `ask` run picks up its history and which thread it belongs to.
+#[derive(Debug, Default)]
+pub(crate) struct AskLineage {
+    pub parent: Option<SessionTranscript>,
+    pub thread_label: Option<String>,
+    pub forked_from_label: Option<String>,
+}
+
+impl AskLineage {
+    /// Resolve `--continue <session-id|label>` and `--thread <label>`.
+    /// Continuing the newest turn of a thread (by label or session id) extends
+    /// that thread; continuing an older session forks from it.
+    pub(crate) fn resolve(
+        continue_from: Option<&str>,
+        thread: Option<&str>,
+        sessions_base: &Path,
+    ) -> Result<Self> {
+        if let Some(label) = thread {
+            validate_thread_label(label)?;
+            return Ok(Self {
+                parent: latest_in_thread(label, sessions_base)?,
+                thread_label: Some(label.to_string()),
+                forked_from_label: None,
+            });
+        }
+        let Some(target) = continue_from else {
+            return Ok(Self::default());
+        };
+        if let Some(session) = list_sessions_in(sessions_base)?
+            .into_iter()
+            .find(|session| session.id == target)
+        {
+            let parent = read_transcript(&session.path)?.ok_or_else(|| {
+                anyhow!(
+                    "session `{target}` has no {TRANSCRIPT_FILE}; only `ask` sessions can be continued"
+                )
+            })?;
+            let (thread_label, forked_from_label) = match parent.thread_label.clone() {
+                Some(label) => {
+                    let tip = latest_in_thread(&label, sessions_base)?;
+                    if tip.is_some_and(|tip| tip.session_id == parent.session_id) {
+                        (Some(label), None)
+                    } else {
+                        (None, Some(label))
+                    }
+                }
+                None => (None, None),
+            };
+            return Ok(Self {
+                parent: Some(parent),
+                thread_label,
+                forked_from_label,
+            });
+        }
+        if validate_thread_label(target).is_ok() {
+            if let Some(parent) = latest_in_thread(target, sessions_base)? {
+                return Ok(Self {
+                    parent: Some(parent),
+                    thread_label: Some(target.to_string()),
+                    forked_from_label: None,
+                });
+            }
+        }
+        bail!("no ask session or thread named `{target}`")
+    }
+
+    /// Record this run's turn in `session_dir`. `messages` are all turns sent,
+    /// including the new prompt, and `answer` is appended as the reply.
+    pub(crate) fn write(
+        &self,
+        session_dir: &Path,
+        session_id: &str,
+        provider: Option<&str>,
+        model: Option<&str>,
+        system: Option<&str>,
+        mut messages: Vec<Value>,
+        answer: &str,
+    ) -> Result<PathBuf> {
+        messages.push(json!({ "role": "assistant", "content": answer }));
+        let thread_revision = match &self.thread_label {
+            Some(label) => latest_in_thread(label, sessions_base_of(session_dir)?)?
+                .map_or(0, |tip| tip.thread_revision)
+                .checked_add(1)
+                .ok_or_else(|| anyhow!("thread `{label}` revision overflow"))?,
+            None => 0,
+        };
+        let transcript = SessionTranscript {
+            session_id: session_id.to_string(),
+            provider: provider.map(str::to_string),
+            model: model.map(str::to_string),
+            system: system.map(str::to_string),
+            messages,
+            parent_session_id: self.parent.as_ref().map(|p| p.session_id.clone()),
+            thread_label: self.thread_label.clone(),
+            thread_revision,
+            forked_from_label: self.forked_from_label.clone(),
+        };
+        let path = session_dir.join(TRANSCRIPT_FILE);
+        write_json(&path, &transcript)?;
+        Ok(path)
+    }
+}
+
+fn sessions_base_of(session_dir: &Path) -> Result<&Path> {
+    session_dir
+        .parent()
+        .ok_or_else(|| anyhow!("session directory has no sessions parent"))
+}
+
+pub(crate) fn read_transcript(session_dir: &Path) -> Result<Option<SessionTranscript>> {
+    let path = session_dir.join(TRANSCRIPT_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let raw = fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+    let transcript =
+        serde_json::from_str(&raw).with_context(|| format!("parse {}", path.display()))?;
+    Ok(Some(transcript))
+}
+
+fn latest_in_thread(label: &str, sessions_base: &Path) -> Result<Option<SessionTranscript>> {
+    let mut latest: Option<SessionTranscript> = None;
+    for session in list_sessions_in(sessions_base)? {
+        let transcript = match read_transcript(&session.path) {
+            Ok(Some(transcript)) => transcript,
+            Ok(None) => continue,
+            Err(err) => {
+                eprintln!(
+                    "warning: skipping unreadable {TRANSCRIPT_FILE} in session `{}`: {err:#}",
+                    session.id
+                );
+                continue;
+            }
+        };
+        if transcript.thread_label.as_deref() != Some(label)
+            || latest
+                .as_ref()
+                .is_some_and(|current| current.thread_revision >= transcript.thread_revision)
+        {
+            continue;
+        }
+        latest = Some(transcript);
+    }
+    Ok(latest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_file_parses_text_and_image_parts() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("pixel.png"), b"\x89PNG\r\n\x1a\n").unwrap();
+        let messages = dir.path().join("messages.jsonl");
+        fs::write(
+            &messages,
+            concat!(
+                r#"{"role":"user","content":"first question"}"#,
+                "\n\n",
+                r#"{"role":"assistant","content":"first answer"}"#,
+                "\n",
+                r#"{"role":"user","content":[{"type":"text","text":"and this?"},{"type":"image_url","image_url":{"url":"pixel.png"}},{"type":"image_url","image_url":"https://example.com/a.png"}]}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let conversation =
+            Conversation::load(Some("be terse".into()), None, Some(&messages)).unwrap();
+        assert_eq!(conversation.turns.len(), 3);
+        assert!(conversation.ends_with_user_turn());
+        assert!(conversation.has_images());
+        let Part::Image(url) = &conversation.turns[2].parts[1] else {
+            panic!("expected an image part");
+        };
+        assert!(url.starts_with("data:image/png;base64,"));
+
+        let chat = conversation.chat_messages("");
+        assert_eq!(chat.len(), 4);
+        assert_eq!(chat[0], json!({ "role": "system", "content": "be terse" }));
+        assert_eq!(chat[1]["content"], "first question");
+        assert_eq!(
+            chat[3]["content"][2]["image_url"]["url"],
+            "https://example.com/a.png"
+        );
+        assert_eq!(conversation.chat_messages("follow-up").len(), 5);
+    }
+
+    #[test]
+    fn messages_file_rejects_unknown_roles() {
+        let dir = tempfile::tempdir().unwrap();
+        let messages = dir.path().join("messages.jsonl");
+        fs::write(&messages, "{\"role\":\"tool\",\"content\":\"x\"}\n").unwrap();
+        let err = Conversation::load(None, None, Some(&messages)).unwrap_err();
+        assert!(format!("{err:#}").contains("messages.jsonl:1"));
+        assert!(format!("{err:#}").contains("unsupported role `tool`"));
+    }
+
+    #[test]
+    fn ask_lineage_extends_threads_and_forks_older_turns() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path();
+        let record = |id: &str, lineage: &AskLineage, prompt: &str| {
+            let session_dir = base.join(id);
+            fs::create_dir_all(&session_dir).unwrap();
+            let mut messages: Vec<Value> = match &lineage.parent {
+                Some(parent) => parent.messages.clone(),
+                None => Vec::new(),
+            };
+            messages.push(json!({ "role": "user", "content": prompt }));
+            lineage
+                .write(
+                    &session_dir,
+                    id,
+                    Some("mock"),
+                    Some("m"),
+                    None,
+                    messages,
+                    "ok",
+                )
+                .unwrap();
+        };
+
+        let first = AskLineage::resolve(None, Some("design"), base).unwrap();
+        assert!(first.parent.is_none());
+        record("s1", &first, "draft it");
+
+        let second = AskLineage::resolve(Some("design"), None, base).unwrap();
+        assert_eq!(second.parent.as_ref().unwrap().session_id, "s1");
+        assert_eq!(second.thread_label.as_deref(), Some("design"));
+        record("s2", &second, "refine it");
+        let tip = read_transcript(&base.join("s2")).unwrap().unwrap();
+        assert_eq!(tip.thread_revision, 2);
+        assert_eq!(tip.parent_session_id.as_deref(), Some("s1"));
+        assert_eq!(tip.conversation().unwrap().turns.len(), 4);
+
+        let fork = AskLineage::resolve(Some("s1"), None, base).unwrap();
+        assert_eq!(fork.thread_label, None);
+        assert_eq!(fork.forked_from_label.as_deref(), Some("design"));
+
+        let err = AskLineage::resolve(Some("missing"), None, base).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("no ask session or thread named `missing`"));
+    }
+
+    #[test]
+    fn flatten_labels_turns_and_keeps_plain_prompts_unchanged() {
+        assert_eq!(Conversation::default().flatten("hi").unwrap(), "hi");
+        let conversation = Conversation::with_instructions("review".into(), Some("be kind".into()));
+        assert_eq!(
+            conversation.flatten("diff").unwrap(),
+            "[system]\nreview\nbe kind\n\n[user]\ndiff"
+        );
+    }
+
+    #[test]
+    fn context_leads_the_final_user_turn() {
+        let conversation = Conversation {
+            context: Some("# Bundle\n".into()),
+            ..Conversation::default()
+        };
+        let messages = conversation.chat_messages("## Prompt");
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0]["content"][0]["text"], "# Bundle\n");
+        assert_eq!(messages[0]["content"][1]["text"], "## Prompt");
+        assert_eq!(
+            conversation.flatten("## Prompt").unwrap(),
+            "# Bundle\n## Prompt"
+        );
+    }
+}
@@ -1,16 +1,219 @@
-use anyhow::{anyhow, Result};
-use reqwest::{header::HeaderMap, RequestBuilder};
+use anyhow::Result;
+use reqwest::{header::HeaderMap, RequestBuilder, StatusCode};
 use serde::de::DeserializeOwned;
+use std::time::Duration;
 
 pub async fn send_json<T: DeserializeOwned>(req: RequestBuilder) -> Result<(T, HeaderMap)> {
-    let resp = req.send().await?;
+    let resp = req.send().await.map_err(ProviderHttpError::from_send)?;
     let status = resp.status();
     let headers = resp.headers().clone();
     if !status.is_success() {
         let text = resp.text().await?;
-        let trimmed = text.lines().take(20).collect::<Vec<_>>().join("\n");
-        return Err(anyhow!("http {}: {}", status.as_u16(), trimmed));
+        return Err(ProviderHttpError::from_response(status, &headers, &text).into());
     }
     let parsed = resp.json().await?;
     Ok((parsed, headers))
 }
+
+/// A provider request that failed before producing an answer, with what the
+/// retry loop needs to decide whether to try again.
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+pub struct ProviderHttpError {
+    /// HTTP status, or `None` when the provider could not be reached.
+    pub status: Option<u16>,
+    /// Wait requested by the provider (`Retry-After` or an equivalent hint).
+    pub retry_after: Option<Duration>,
+    message: String,
+}
+
+impl ProviderHttpError {
+    pub fn from_response(status: StatusCode, headers: &HeaderMap, body: &str) -> Self {
+        let trimmed = body.lines().take(20).collect::<Vec<_>>().join("\n");
+        let message = format!("http {}: {}", status.as_u16(), trimmed);
+        let retry_after = retry_after_header(headers).or_else(|| retry_hint(&message));
+        Self {
+            status: Some(status.as_u16()),
+            retry_after,
+            message,
+        }
+    }
+
+    /// Connection failures are worth retrying; anything else reqwest reports
+    /// while sending (timeouts included) is passed through unchanged.
+    pub fn from_send(err: reqwest::Error) -> anyhow::Error {
+        if err.is_connect() {
+            Self {
+                status: None,
+                retry_after: None,
+                message: format!("{:#}", anyhow::Error::from(err)),
+            }
+            .into()
+        } else {
+            err.into()
+        }
+    }
+
+    /// Recover status and retry hints from an error that only survives as
+    /// text (litellm errors). Returns `None` when the text does not look like
+    /// a provider HTTP or connection failure.
+    pub fn from_message(message: String) -> Option<Self> {
+        let lower = message.to_ascii_lowercase();
+        let status = ["http", "status"]
+            .iter()
+            .find_map(|marker| number_after(&lower, marker))
+            .map(|n| n as u16)
+            .filter(|n| (100..600).contains(n))
+            .or_else(|| status_from_keywords(&lower));
+        // reqwest timeouts also read "error sending request"; like
+        // `from_send`, pass them through so they are never retried.
+        if status.is_none() && (lower.contains("timed out") || lower.contains("timeout")) {
+            return None;
+        }
+        let unreachable = [
+            "error sending request",
+            "connection refused",
+            "connection reset",
+            "connection closed",
+            "dns error",
+        ]
+        .iter()
+        .any(|needle| lower.contains(needle));
+        if status.is_none() && !unreachable {
+            return None;
+        }
+        Some(Self {
+            status,
+            retry_after: retry_hint(&message),
+            message,
+        })
+    }
+
+    /// Rate limits, overload, server errors, and unreachable providers.
+    pub fn is_retryable(&self) -> bool {
+        match self.status {
+            None => true,
+            Some(status) => status == 408 || status == 429 || status >= 500,
+        }
+    }
+}
+
+fn retry_after_header(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name)?.to_str().ok()?.trim().parse::<f64>().ok();
+    header("retry-after-ms")
+        .map(|ms| Duration::from_secs_f64(ms.max(0.0) / 1000.0))
+        .or_else(|| header("retry-after").map(|secs| Duration::from_secs_f64(secs.max(0.0))))
+}
+
+/// `retry-after: 12` or `try again in 1.5s` / `in 200ms` inside an error body.
+fn retry_hint(message: &str) -> Option<Duration> {
+    let lower = message.to_ascii_lowercase();
+    if let Some(secs) = decimal_after(&lower, "retry-after") {
+        return Some(Duration::from_secs_f64(secs.0));
+    }
+    let (value, rest) = decimal_after(&lower, "try again in")?;
+    if rest.starts_with("ms") {
+        Some(Duration::from_secs_f64(value / 1000.0))
+    } else if rest.starts_with('s') {
+        Some(Duration::from_secs_f64(value))
+    } else {
+        None
+    }
+}
+
+fn status_from_keywords(lower: &str) -> Option<u16> {
+    [
+        ("too many requests", 429),
+        ("rate limit", 429),
+        ("overloaded", 529),
+        ("service unavailable", 503),
+        ("bad gateway", 502),
+        ("gateway timeout", 504),
+    ]
+    .iter()
+    .find(|(needle, _)| lower.contains(needle))
+    .map(|(_, status)| *status)
+}
+
+const SEPARATORS: &[char] = &[' ', ':', '=', '"', '\'', '(', '['];
+
+fn number_after(lower: &str, marker: &str) -> Option<u64> {
+    lower.match_indices(marker).find_map(|(idx, _)| {
+        let rest = lower[idx + marker.len()..].trim_start_matches(SEPARATORS);
+        let rest = rest
+            .strip_prefix("code")
+            .unwrap_or(rest)
+            .trim_start_matches(SEPARATORS);
+        let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+        digits.parse().ok()
+    })
+}
+
+fn decimal_after<'a>(lower: &'a str, marker: &str) -> Option<(f64, &'a str)> {
+    let idx = lower.find(marker)?;
+    let rest = lower[idx + marker.len()..].trim_start_matches(SEPARATORS);
+    let end = rest
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(rest.len());
+    let value = rest[..end].parse::<f64>().ok()?;
+    Some((value, rest[end..].trim_start()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn from_response_reads_retry_after_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("7"));
+        let err =
+            ProviderHttpError::from_response(StatusCode::TOO_MANY_REQUESTS, &headers, "slow down");
+        assert_eq!(err.to_string(), "http 429: slow down");
+        assert_eq!(err.retry_after, Some(Duration::from_secs(7)));
+        assert!(err.is_retryable());
+
+        headers.insert("retry-after-ms", HeaderValue::from_static("250"));
+        let err = ProviderHttpError::from_response(StatusCode::SERVICE_UNAVAILABLE, &headers, "");
+        assert_eq!(err.retry_after, Some(Duration::from_millis(250)));
+
+        let err = ProviderHttpError::from_response(StatusCode::BAD_REQUEST, &HeaderMap::new(), "");
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn from_message_classifies_litellm_errors() {
+        let err = ProviderHttpError::from_message(
+            "provider returned status code 429: Rate limit reached. Please try again in 1.5s."
+                .to_string(),
+        )
+        .unwrap();
+        assert_eq!(err.status, Some(429));
+        assert_eq!(err.retry_after, Some(Duration::from_millis(1500)));
+
+        let err =
+            ProviderHttpError::from_message("anthropic error: Overloaded".to_string()).unwrap();
+        assert_eq!(err.status, Some(529));
+        assert!(err.is_retryable());
+
+        let err = ProviderHttpError::from_message("http 401: invalid api key".to_string()).unwrap();
+        assert!(!err.is_retryable());
+
+        let err = ProviderHttpError::from_message(
+            "error sending request for url (https://api.example.com): connection refused"
+                .to_string(),
+        )
+        .unwrap();
+        assert_eq!(err.status, None);
+        assert!(err.is_retryable());
+
+        assert!(ProviderHttpError::from_message(
+            "error sending request for url (http://localhost:4000/chat/completions): operation timed out"
+                .to_string(),
+        )
+        .is_none());
+
+        assert!(ProviderHttpError::from_message("invalid model name".to_string()).is_none());
+    }
+}
@@ -0,0 +1,240 @@
+//! Running `--include-cmd` commands so their output can be bundled.
+//!
+//! Each command runs through the platform shell in its own process group with
+//! stdin closed. Only the tail of each stream is kept, since test runners and
+//! compilers print their summary last. When the command exits or times out the
+//! whole group is killed so a leftover child cannot hold the pipes open.
+//! `ask --tools` runs its tool commands through the same [`run_captured`].
+
+use anyhow::{anyhow, Context, Result};
+use std::io::{self, Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use yoetz_core::bundle::CommandOutput;
+
+use crate::process_group;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+const OMITTED_MARKER: &str = "... [earlier output omitted]\n";
+
+/// Reader thread result: the kept tail and whether anything was dropped.
+type TailReader = JoinHandle<io::Result<(Vec<u8>, bool)>>;
+
+/// Run every command in order, echoing progress to stderr.
+pub(crate) fn run_include_cmds(
+    commands: &[String],
+    timeout: Duration,
+    max_bytes: usize,
+) -> Result<Vec<CommandOutput>> {
+    commands
+        .iter()
+        .map(|command| {
+            eprintln!("Running --include-cmd: {command}");
+            let output = run_include_cmd(command, timeout, max_bytes)?;
+            if let Some(limit) = output.timed_out_after {
+                eprintln!(
+                    "warning: --include-cmd `{command}` timed out after {}s; bundling partial output",
+                    limit.as_secs()
+                );
+            }
+            Ok(output)
+        })
+        .collect()
+}
+
+fn run_include_cmd(command: &str, timeout: Duration, max_bytes: usize) -> Result<CommandOutput> {
+    let output = run_captured(shell(command), None, timeout, max_bytes)
+        .with_context(|| format!("run --include-cmd `{command}`"))?;
+    Ok(CommandOutput {
+        command: command.to_string(),
+        truncated: output.stdout_truncated || output.stderr_truncated,
+        stdout: output.stdout,
+        stderr: output.stderr,
+        exit_code: output.exit_code,
+        timed_out_after: output.timed_out.then_some(timeout),
+    })
+}
+
+/// What a command run by [`run_captured`] left behind.
+pub(crate) struct Captured {
+    pub(crate) stdout: String,
+    pub(crate) stderr: String,
+    pub(crate) exit_code: Option<i32>,
+    pub(crate) timed_out: bool,
+    /// The stream lost its head to `max_bytes`.
+    pub(crate) stdout_truncated: bool,
+    pub(crate) stderr_truncated: bool,
+}
+
+/// Run `command` in its own process group with `input` on stdin (closed when
+/// `None`), keeping the last `max_bytes` of each output stream and killing
+/// the group at exit or after `timeout`.
+pub(crate) fn run_captured(
+    mut command: Command,
+    input: Option<Vec<u8>>,
+    timeout: Duration,
+    max_bytes: usize,
+) -> Result<Captured> {
+    own_process_group(&mut command);
+    let mut child = command
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let (Some(mut pipe), Some(input)) = (child.stdin.take(), input) {
+        // A command that exits without reading its input is not an error.
+        thread::spawn(move || pipe.write_all(&input));
+    }
+    let stdout = child.stdout.take().map(|pipe| read_tail(pipe, max_bytes));
+    let stderr = child.stderr.take().map(|pipe| read_tail(pipe, max_bytes));
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if Instant::now() >= deadline {
+            break None;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    kill_process_group(&mut child).context("stop command process group")?;
+    let timed_out = status.is_none();
+    let status = match status {
+        Some(status) => status,
+        None => child.wait()?,
+    };
+
+    let (stdout, stdout_truncated) = join_tail(stdout)?;
+    let (stderr, stderr_truncated) = join_tail(stderr)?;
+    Ok(Captured {
+        stdout,
+        stderr,
+        exit_code: status.code(),
+        timed_out,
+        stdout_truncated,
+        stderr_truncated,
+    })
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(not(unix))]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+fn own_process_group(command: &mut Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+
+        command.process_group(0);
+    }
+    #[cfg(not(unix))]
+    let _ = command;
+}
+
+/// Kill the command's process group (or just the child off Unix). Harmless
+/// when everything has already exited.
+fn kill_process_group(child: &mut Child) -> io::Result<()> {
+    process_group::kill(child.id())?;
+    match child.kill() {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == io::ErrorKind::InvalidInput => Ok(()),
+        Err(error) => Err(error),
+    }
+}
+
+/// Drain `pipe` on a thread, keeping only its last `max_bytes` bytes.
+fn read_tail<R: Read + Send + 'static>(mut pipe: R, max_bytes: usize) -> TailReader {
+    thread::spawn(move || {
+        let mut kept = Vec::new();
+        let mut cut = false;
+        let mut buf = [0u8; 8192];
+        loop {
+            let read = match pipe.read(&mut buf) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            };
+            kept.extend_from_slice(&buf[..read]);
+            // Trim in batches so long outputs are not shifted on every read.
+            if kept.len() > max_bytes.saturating_mul(2).max(buf.len()) {
+                kept.drain(..kept.len() - max_bytes);
+                cut = true;
+            }
+        }
+        if kept.len() > max_bytes {
+            kept.drain(..kept.len() - max_bytes);
+            cut = true;
+        }
+        Ok((kept, cut))
+    })
+}
+
+fn join_tail(handle: Option<TailReader>) -> Result<(String, bool)> {
+    let Some(handle) = handle else {
+        return Ok((String::new(), false));
+    };
+    let (bytes, cut) = handle
+        .join()
+        .map_err(|_| anyhow!("command output reader panicked"))?
+        .context("read command output")?;
+    let text = String::from_utf8_lossy(&bytes);
+    if !cut {
+        return Ok((text.into_owned(), false));
+    }
+    // The cut may land inside a UTF-8 sequence; drop the replacement char.
+    let text = text.trim_start_matches('\u{fffd}');
+    Ok((format!("{OMITTED_MARKER}{text}"), true))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::run_include_cmd;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn captures_streams_exit_code_and_tail() {
+        let output = run_include_cmd(
+            "printf 'abcdefghij'; echo oop >&2; exit 3",
+            Duration::from_secs(10),
+            4,
+        )
+        .unwrap();
+        assert_eq!(output.exit_code, Some(3));
+        assert_eq!(output.stdout, "... [earlier output omitted]\nghij");
+        assert_eq!(output.stderr, "oop\n");
+        assert!(output.truncated);
+        assert!(output.timed_out_after.is_none());
+    }
+
+    #[test]
+    fn timeout_kills_the_process_group() {
+        let started = Instant::now();
+        let output = run_include_cmd(
+            "echo started; sleep 30 & sleep 30",
+            Duration::from_millis(300),
+            1024,
+        )
+        .unwrap();
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(output.timed_out_after, Some(Duration::from_millis(300)));
+        assert_eq!(output.exit_code, None);
+        assert_eq!(output.stdout, "started\n");
+    }
+}
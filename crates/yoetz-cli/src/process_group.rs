@@ -0,0 +1,26 @@
+//! Process-group teardown shared by `--include-cmd` (and `ask --tools`)
+//! commands and the Cursor CLI provider, whose children may outlive the
+//! process that started them.
+
+use std::io;
+
+/// SIGKILL every process in group `pgid`; a leader spawned with
+/// `process_group(0)` has its PID as the group ID. A group that has already
+/// exited is not an error. Off Unix there are no process groups, so this
+/// does nothing.
+pub(crate) fn kill(pgid: u32) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        #[allow(unsafe_code)]
+        let result = unsafe { libc::kill(-(pgid as libc::pid_t), libc::SIGKILL) };
+        if result != 0 {
+            let error = io::Error::last_os_error();
+            if error.raw_os_error() != Some(libc::ESRCH) {
+                return Err(error);
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = pgid;
+    Ok(())
+}
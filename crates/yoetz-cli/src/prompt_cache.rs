@@ -0,0 +1,221 @@
+//! Provider prompt caching for bundles (`--prompt-cache`).
+//!
+//! The rendered bundle goes ahead of the prompt as its own part of the final
+//! user turn (`Conversation::context`), so repeated calls over one bundle
+//! share a prefix. OpenAI, DeepSeek and Gemini cache long shared prefixes on
+//! their own. Anthropic models, and Gemini models on OpenRouter, cache only up
+//! to an explicit `cache_control` breakpoint; litellm requests cannot carry
+//! one, so those calls go straight to the provider with the breakpoint on the
+//! bundle part: the Messages API for the native `anthropic` provider, the
+//! OpenAI-compatible `/chat/completions` endpoint otherwise. Gemini's explicit
+//! `cachedContents` API is not used; native Gemini relies on its implicit
+//! prefix cache.
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::time::Duration;
+use yoetz_core::config::Config;
+
+use crate::conversation::Conversation;
+use crate::providers::{anthropic, chat_stream, ProviderAuth};
+use crate::{anthropic_messages_auth, chat_completions_auth, wire_model, CallResult};
+
+/// Models that cache nothing without an explicit breakpoint.
+pub(crate) fn wants_breakpoint(model: &str) -> bool {
+    let model = model.to_ascii_lowercase();
+    model.starts_with("anthropic/")
+        || model.contains("claude")
+        || model.starts_with("google/gemini")
+}
+
+/// Where a call carrying a breakpoint goes.
+pub(crate) enum BreakpointRoute {
+    ChatCompletions(ProviderAuth),
+    AnthropicMessages(ProviderAuth),
+}
+
+/// Route for a direct call carrying a breakpoint: the bundle was split out,
+/// the model needs one, and the provider speaks the OpenAI-compatible API or
+/// is Anthropic's own. The Messages path takes neither image parts nor a
+/// `response_format`, so those requests stay with litellm.
+pub(crate) fn breakpoint_route(
+    config: &Config,
+    provider: &str,
+    model: &str,
+    conversation: &Conversation,
+    response_format: Option<&Value>,
+) -> Option<BreakpointRoute> {
+    if conversation.context.is_none() || !wants_breakpoint(model) {
+        return None;
+    }
+    if let Some(auth) = chat_completions_auth(config, provider) {
+        return Some(BreakpointRoute::ChatCompletions(auth));
+    }
+    if conversation.has_images() || response_format.is_some() {
+        return None;
+    }
+    anthropic_messages_auth(config, provider).map(BreakpointRoute::AnthropicMessages)
+}
+
+/// Warning for a `--prompt-cache` call whose model needs a breakpoint that
+/// [`breakpoint_route`] cannot send, so nothing is cached.
+pub(crate) fn unsent_breakpoint_warning(
+    config: &Config,
+    provider: &str,
+    model: &str,
+    conversation: &Conversation,
+    response_format: Option<&Value>,
+) -> Option<String> {
+    if conversation.context.is_none()
+        || !wants_breakpoint(model)
+        || breakpoint_route(config, provider, model, conversation, response_format).is_some()
+    {
+        return None;
+    }
+    Some(format!(
+        "--prompt-cache has no effect for {model} on provider {provider}: the cache breakpoint it needs cannot be sent on this path"
+    ))
+}
+
+/// Chat `messages` for `model`, with a breakpoint after the bundle context
+/// when the model needs one.
+pub(crate) fn chat_messages(conversation: &Conversation, prompt: &str, model: &str) -> Vec<Value> {
+    let mut messages = conversation.chat_messages(prompt);
+    if conversation.context.is_some()
+        && conversation.sends_prompt(prompt)
+        && wants_breakpoint(model)
+    {
+        let context = messages
+            .last_mut()
+            .and_then(|message| message.get_mut("content"))
+            .and_then(Value::as_array_mut)
+            .and_then(|parts| parts.first_mut());
+        if let Some(part) = context {
+            part["cache_control"] = json!({ "type": "ephemeral" });
+        }
+    }
+    messages
+}
+
+/// A regular (non-streamed) answer over `route`. The chat path reuses the
+/// streaming endpoint, which is the direct chat path this crate already has.
+pub(crate) async fn complete(
+    route: &BreakpointRoute,
+    provider: &str,
+    model: &str,
+    conversation: &Conversation,
+    prompt: &str,
+    temperature: f32,
+    max_output_tokens: Option<usize>,
+    response_format: Option<Value>,
+    timeout: Duration,
+) -> Result<CallResult> {
+    let wire_model = wire_model(provider, model)?;
+    let messages = chat_messages(conversation, prompt, &wire_model);
+    let (content, usage, response_id) = match route {
+        BreakpointRoute::ChatCompletions(auth) => {
+            let result = chat_stream::stream_chat_completion(
+                auth,
+                &wire_model,
+                messages,
+                temperature,
+                max_output_tokens,
+                response_format,
+                timeout,
+                |_| Ok(()),
+            )
+            .await?;
+            (result.content, result.usage, result.response_id)
+        }
+        BreakpointRoute::AnthropicMessages(auth) => {
+            let result = anthropic::create_message(
+                auth,
+                &wire_model,
+                messages,
+                temperature,
+                max_output_tokens,
+                timeout,
+            )
+            .await?;
+            (result.content, result.usage, result.response_id)
+        }
+    };
+    Ok(CallResult {
+        content,
+        usage,
+        response_id,
+        header_cost: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breakpoint_marks_the_bundle_part_for_models_that_need_it() {
+        let conversation = Conversation {
+            context: Some("bundle".to_string()),
+            ..Conversation::default()
+        };
+        let messages = chat_messages(&conversation, "prompt", "anthropic/claude-sonnet-4.5");
+        let parts = messages[0]["content"].as_array().unwrap();
+        assert_eq!(parts[0]["cache_control"]["type"], "ephemeral");
+        assert!(parts[1].get("cache_control").is_none());
+
+        let messages = chat_messages(&conversation, "prompt", "gpt-5.2");
+        assert!(messages[0]["content"][0].get("cache_control").is_none());
+
+        let messages = chat_messages(&Conversation::default(), "prompt", "claude-opus");
+        assert_eq!(messages[0]["content"], "prompt");
+    }
+
+    #[test]
+    fn native_anthropic_gets_a_messages_route_or_a_warning() {
+        let mut config = Config::default();
+        config.providers.insert(
+            "anthropic".to_string(),
+            yoetz_core::config::ProviderConfig {
+                api_key_env: Some("YOETZ_TEST_PROMPT_CACHE_ANTHROPIC_KEY".to_string()),
+                ..Default::default()
+            },
+        );
+        std::env::set_var("YOETZ_TEST_PROMPT_CACHE_ANTHROPIC_KEY", "sk-test");
+        let conversation = Conversation {
+            context: Some("bundle".to_string()),
+            ..Conversation::default()
+        };
+        let route = breakpoint_route(
+            &config,
+            "anthropic",
+            "claude-sonnet-4-5",
+            &conversation,
+            None,
+        );
+        match route {
+            Some(BreakpointRoute::AnthropicMessages(auth)) => {
+                assert_eq!(auth.base_url, "https://api.anthropic.com/v1");
+                assert_eq!(auth.api_key, "sk-test");
+            }
+            _ => panic!("expected the Messages route"),
+        }
+        let warning = unsent_breakpoint_warning(
+            &config,
+            "anthropic",
+            "claude-sonnet-4-5",
+            &conversation,
+            None,
+        );
+        assert!(warning.is_none());
+
+        let schema = json!({ "type": "json_object" });
+        let warning = unsent_breakpoint_warning(
+            &config,
+            "anthropic",
+            "claude-sonnet-4-5",
+            &conversation,
+            Some(&schema),
+        );
+        assert!(warning.unwrap().contains("--prompt-cache has no effect"));
+    }
+}
@@ -0,0 +1,159 @@
+//! Direct calls to the Anthropic Messages API.
+//!
+//! litellm covers regular requests to the `anthropic` provider; this path
+//! exists for requests it cannot shape, such as a prompt-cache breakpoint on
+//! one content block of the final user turn.
+
+use anyhow::Result;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::time::Duration;
+use yoetz_core::types::Usage;
+
+use crate::http::send_json;
+use crate::providers::ProviderAuth;
+
+const API_VERSION: &str = "2023-06-01";
+
+/// The Messages API requires `max_tokens`; this fits every current model.
+const DEFAULT_MAX_TOKENS: usize = 4096;
+
+#[derive(Debug, Clone, Default)]
+pub struct MessageResult {
+    pub content: String,
+    pub usage: Usage,
+    pub response_id: Option<String>,
+}
+
+/// Send OpenAI-style chat `messages` (text parts only) to `/messages`.
+/// Content parts keep any `cache_control` they carry.
+pub async fn create_message(
+    auth: &ProviderAuth,
+    model: &str,
+    messages: Vec<Value>,
+    temperature: f32,
+    max_output_tokens: Option<usize>,
+    timeout: Duration,
+) -> Result<MessageResult> {
+    let client = Client::builder().timeout(timeout).build()?;
+    let url = format!("{}/messages", auth.base_url.trim_end_matches('/'));
+    let body = request_body(model, messages, temperature, max_output_tokens);
+    let req = client
+        .post(url)
+        .header("x-api-key", &auth.api_key)
+        .header("anthropic-version", API_VERSION)
+        .json(&body);
+    let (resp, _headers): (Value, _) = send_json(req).await?;
+    Ok(parse_response(&resp))
+}
+
+/// System messages move to the top-level `system` field; the other turns
+/// keep their content, since text parts share the chat shape.
+fn request_body(
+    model: &str,
+    messages: Vec<Value>,
+    temperature: f32,
+    max_output_tokens: Option<usize>,
+) -> Value {
+    let mut system = Vec::new();
+    let mut turns = Vec::new();
+    for message in messages {
+        if message["role"] == "system" {
+            if let Some(text) = message["content"].as_str() {
+                system.push(text.to_string());
+            }
+        } else {
+            turns.push(message);
+        }
+    }
+    let mut body = json!({
+        "model": model,
+        "messages": turns,
+        "temperature": temperature,
+        "max_tokens": max_output_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+    });
+    if !system.is_empty() {
+        body["system"] = json!(system.join("\n\n"));
+    }
+    body
+}
+
+fn parse_response(resp: &Value) -> MessageResult {
+    let content = resp["content"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|block| block["type"] == "text")
+        .filter_map(|block| block["text"].as_str())
+        .collect::<String>();
+    MessageResult {
+        content,
+        usage: parse_usage(&resp["usage"]),
+        response_id: resp["id"].as_str().map(str::to_string),
+    }
+}
+
+/// Anthropic counts cached tokens apart from `input_tokens`; [`Usage`]
+/// includes them.
+fn parse_usage(usage: &Value) -> Usage {
+    let count = |key: &str| usage.get(key).and_then(Value::as_u64);
+    let cache_read_tokens = count("cache_read_input_tokens");
+    let cache_write_tokens = count("cache_creation_input_tokens");
+    let input_tokens = count("input_tokens").map(|uncached| {
+        uncached + cache_read_tokens.unwrap_or(0) + cache_write_tokens.unwrap_or(0)
+    });
+    let output_tokens = count("output_tokens");
+    Usage {
+        input_tokens,
+        output_tokens,
+        total_tokens: input_tokens.zip(output_tokens).map(|(i, o)| i + o),
+        cache_read_tokens,
+        cache_write_tokens,
+        ..Usage::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_body_lifts_system_and_keeps_cache_control() {
+        let messages = vec![
+            json!({ "role": "system", "content": "be brief" }),
+            json!({ "role": "user", "content": [
+                { "type": "text", "text": "bundle", "cache_control": { "type": "ephemeral" } },
+                { "type": "text", "text": "prompt" },
+            ] }),
+        ];
+        let body = request_body("claude-sonnet-4-5", messages, 0.1, None);
+        assert_eq!(body["system"], "be brief");
+        assert_eq!(body["max_tokens"], DEFAULT_MAX_TOKENS);
+        assert_eq!(body["messages"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            body["messages"][0]["content"][0]["cache_control"]["type"],
+            "ephemeral"
+        );
+    }
+
+    #[test]
+    fn parse_response_folds_cached_tokens_into_input() {
+        let resp = json!({
+            "id": "msg_1",
+            "content": [{ "type": "text", "text": "Hel" }, { "type": "text", "text": "lo" }],
+            "usage": {
+                "input_tokens": 20,
+                "output_tokens": 5,
+                "cache_read_input_tokens": 9000,
+                "cache_creation_input_tokens": 0,
+            },
+        });
+        let result = parse_response(&resp);
+        assert_eq!(result.content, "Hello");
+        assert_eq!(result.response_id.as_deref(), Some("msg_1"));
+        assert_eq!(result.usage.input_tokens, Some(9020));
+        assert_eq!(result.usage.total_tokens, Some(9025));
+        assert_eq!(result.usage.cache_read_tokens, Some(9000));
+        assert_eq!(result.usage.cache_write_tokens, Some(0));
+    }
+}
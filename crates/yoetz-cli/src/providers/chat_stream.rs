@@ -0,0 +1,238 @@
+//! Streaming chat completions from OpenAI-compatible providers.
+//!
+//! Sends `stream: true` to `/chat/completions` and hands each content delta to
+//! a callback as server-sent events arrive. The request has no overall
+//! deadline: each read instead waits at most `idle_timeout` for the next
+//! chunk, so a long answer keeps streaming while a stalled connection fails.
+
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::time::Duration;
+use yoetz_core::types::Usage;
+
+use super::ProviderAuth;
+use crate::http::ProviderHttpError;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Default)]
+pub struct StreamResult {
+    pub content: String,
+    pub usage: Usage,
+    pub response_id: Option<String>,
+}
+
+pub async fn stream_chat_completion(
+    auth: &ProviderAuth,
+    model: &str,
+    messages: Vec<Value>,
+    temperature: f32,
+    max_output_tokens: Option<usize>,
+    response_format: Option<Value>,
+    idle_timeout: Duration,
+    mut on_delta: impl FnMut(&str) -> Result<()>,
+) -> Result<StreamResult> {
+    let client = Client::builder().connect_timeout(CONNECT_TIMEOUT).build()?;
+    let url = format!("{}/chat/completions", auth.base_url.trim_end_matches('/'));
+    let mut body = json!({
+        "model": model,
+        "messages": messages,
+        "temperature": temperature,
+        "stream": true,
+        "stream_options": { "include_usage": true },
+    });
+    if let Some(max) = max_output_tokens {
+        body["max_tokens"] = json!(max);
+    }
+    if let Some(format) = response_format {
+        body["response_format"] = format;
+    }
+
+    let send = client
+        .post(url)
+        .bearer_auth(&auth.api_key)
+        .json(&body)
+        .send();
+    let mut resp = tokio::time::timeout(idle_timeout, send)
+        .await
+        .map_err(|_| stalled(idle_timeout))?
+        .map_err(ProviderHttpError::from_send)?;
+    let status = resp.status();
+    if !status.is_success() {
+        let headers = resp.headers().clone();
+        let text = resp.text().await?;
+        return Err(ProviderHttpError::from_response(status, &headers, &text).into());
+    }
+
+    let mut events = SseEvents::default();
+    let mut result = StreamResult::default();
+    loop {
+        let chunk = tokio::time::timeout(idle_timeout, resp.chunk())
+            .await
+            .map_err(|_| stalled(idle_timeout))?
+            .context("read response stream")?;
+        let Some(chunk) = chunk else {
+            break;
+        };
+        for data in events.push(&chunk) {
+            if data == "[DONE]" {
+                return Ok(result);
+            }
+            if let Some(text) = apply_event(&data, &mut result)? {
+                on_delta(&text)?;
+                result.content.push_str(&text);
+            }
+        }
+    }
+    Ok(result)
+}
+
+fn stalled(idle_timeout: Duration) -> anyhow::Error {
+    anyhow!(
+        "response stream stalled: no data for {}s (--timeout-secs)",
+        idle_timeout.as_secs()
+    )
+}
+
+/// Reassembles server-sent events from arbitrarily split chunks and yields
+/// the `data` payload of each complete event.
+#[derive(Default)]
+struct SseEvents {
+    pending: Vec<u8>,
+}
+
+impl SseEvents {
+    fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(chunk);
+        let mut payloads = Vec::new();
+        while let Some((end, separator)) = event_end(&self.pending) {
+            let event: Vec<u8> = self.pending.drain(..end + separator).collect();
+            let event = String::from_utf8_lossy(&event[..end]);
+            let data: Vec<&str> = event
+                .lines()
+                .filter_map(|line| line.strip_prefix("data:"))
+                .map(|data| data.strip_prefix(' ').unwrap_or(data))
+                .collect();
+            if !data.is_empty() {
+                payloads.push(data.join("\n"));
+            }
+        }
+        payloads
+    }
+}
+
+/// Offset and length of the first blank-line event separator.
+fn event_end(buf: &[u8]) -> Option<(usize, usize)> {
+    let lf = buf.windows(2).position(|w| w == b"\n\n").map(|i| (i, 2));
+    let crlf = buf
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .map(|i| (i, 4));
+    match (lf, crlf) {
+        (Some(a), Some(b)) => Some(if a.0 <= b.0 { a } else { b }),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Fold one `chat.completion.chunk` into `result`, returning its content delta.
+fn apply_event(data: &str, result: &mut StreamResult) -> Result<Option<String>> {
+    let event: Value =
+        serde_json::from_str(data).with_context(|| format!("parse stream event: {data}"))?;
+    if let Some(error) = event.get("error") {
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| error.to_string());
+        bail!("provider stream error: {message}");
+    }
+    if result.response_id.is_none() {
+        result.response_id = event.get("id").and_then(Value::as_str).map(str::to_string);
+    }
+    if let Some(usage) = event.get("usage").filter(|usage| usage.is_object()) {
+        result.usage = parse_usage(usage);
+    }
+    let text = event
+        .pointer("/choices/0/delta/content")
+        .and_then(Value::as_str)
+        .filter(|text| !text.is_empty());
+    Ok(text.map(str::to_string))
+}
+
+pub fn parse_usage(usage: &Value) -> Usage {
+    let cost_usd = usage
+        .get("cost")
+        .and_then(Value::as_f64)
+        .or_else(|| usage.get("cost").and_then(Value::as_str)?.parse().ok());
+    Usage {
+        input_tokens: usage.get("prompt_tokens").and_then(Value::as_u64),
+        output_tokens: usage.get("completion_tokens").and_then(Value::as_u64),
+        thoughts_tokens: usage
+            .pointer("/completion_tokens_details/reasoning_tokens")
+            .and_then(Value::as_u64),
+        total_tokens: usage.get("total_tokens").and_then(Value::as_u64),
+        cost_usd,
+        // OpenAI and OpenRouter report cache hits under `prompt_tokens_details`;
+        // Anthropic-style counts (litellm proxy) sit at the top level.
+        cache_read_tokens: usage
+            .pointer("/prompt_tokens_details/cached_tokens")
+            .or_else(|| usage.get("cache_read_input_tokens"))
+            .and_then(Value::as_u64),
+        cache_write_tokens: usage
+            .pointer("/prompt_tokens_details/cache_write_tokens")
+            .or_else(|| usage.get("cache_creation_input_tokens"))
+            .and_then(Value::as_u64),
+        ..Usage::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sse_events_survive_arbitrary_chunking() {
+        let stream =
+            ": keep-alive\n\ndata: {\"a\":1}\r\n\r\ndata: first\ndata: second\n\ndata: [DONE]\n\n";
+        let mut events = SseEvents::default();
+        let mut payloads = Vec::new();
+        for chunk in stream.as_bytes().chunks(3) {
+            payloads.extend(events.push(chunk));
+        }
+        assert_eq!(payloads, vec!["{\"a\":1}", "first\nsecond", "[DONE]"]);
+    }
+
+    #[test]
+    fn apply_event_collects_deltas_usage_and_errors() {
+        let mut result = StreamResult::default();
+        let delta = apply_event(
+            r#"{"id":"chatcmpl-1","choices":[{"delta":{"content":"Hel"}}]}"#,
+            &mut result,
+        )
+        .unwrap();
+        assert_eq!(delta.as_deref(), Some("Hel"));
+        let last = apply_event(
+            r#"{"id":"chatcmpl-1","choices":[],"usage":{"prompt_tokens":5,"completion_tokens":2,"total_tokens":7,"cost":0.01,"completion_tokens_details":{"reasoning_tokens":1}}}"#,
+            &mut result,
+        )
+        .unwrap();
+        assert_eq!(last, None);
+        assert_eq!(result.response_id.as_deref(), Some("chatcmpl-1"));
+        assert_eq!(result.usage.input_tokens, Some(5));
+        assert_eq!(result.usage.thoughts_tokens, Some(1));
+        assert_eq!(result.usage.cost_usd, Some(0.01));
+        assert_eq!(result.usage.cache_read_tokens, None);
+
+        apply_event(
+            r#"{"choices":[],"usage":{"prompt_tokens":9000,"completion_tokens":2,"prompt_tokens_details":{"cached_tokens":8000,"cache_write_tokens":0}}}"#,
+            &mut result,
+        )
+        .unwrap();
+        assert_eq!(result.usage.cache_read_tokens, Some(8000));
+        assert_eq!(result.usage.cache_write_tokens, Some(0));
+
+        let err = apply_event(r#"{"error":{"message":"overloaded"}}"#, &mut result).unwrap_err();
+        assert!(err.to_string().contains("overloaded"));
+    }
+}
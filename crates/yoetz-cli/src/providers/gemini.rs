@@ -61,6 +61,7 @@ pub struct GeminiVideoResult {
 pub async fn generate_content(
     client: &Client,
     auth: &ProviderAuth,
+    system: Option<&str>,
     prompt: &str,
     model: &str,
     images: &[MediaInput],
@@ -82,10 +83,13 @@ pub async fn generate_content(
     if let Some(max) = max_output_tokens {
         gen_config["maxOutputTokens"] = serde_json::json!(max);
     }
-    let body = serde_json::json!({
+    let mut body = serde_json::json!({
         "contents": [{ "role": "user", "parts": parts }],
         "generationConfig": gen_config
     });
+    if let Some(system) = system {
+        body["systemInstruction"] = serde_json::json!({ "parts": [{ "text": system }] });
+    }
 
     let url = format!(
         "{}/models/{}:generateContent",
@@ -474,6 +478,8 @@ fn parse_usage(resp: &Value) -> Usage {
             thoughts_tokens: meta.get("thoughtsTokenCount").and_then(|v| v.as_u64()),
             total_tokens: meta.get("totalTokenCount").and_then(|v| v.as_u64()),
             cost_usd: None,
+            cache_read_tokens: meta.get("cachedContentTokenCount").and_then(|v| v.as_u64()),
+            ..Usage::default()
         };
     }
     Usage::default()
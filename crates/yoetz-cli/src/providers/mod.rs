@@ -3,6 +3,8 @@ use std::env;
 
 use yoetz_core::config::Config;
 
+pub mod anthropic;
+pub mod chat_stream;
 pub mod cursor;
 pub mod gemini;
 pub mod openai;
@@ -36,6 +38,7 @@ pub fn default_base_url(provider: &str) -> Option<String> {
     match provider {
         "openrouter" => Some("https://openrouter.ai/api/v1".to_string()),
         "openai" => Some("https://api.openai.com/v1".to_string()),
+        "anthropic" => Some("https://api.anthropic.com/v1".to_string()),
         "gemini" => Some("https://generativelanguage.googleapis.com/v1beta".to_string()),
         _ => None,
     }
@@ -45,6 +48,7 @@ pub fn default_api_key_env(provider: &str) -> Option<String> {
     match provider {
         "openrouter" => Some("OPENROUTER_API_KEY".to_string()),
         "openai" => Some("OPENAI_API_KEY".to_string()),
+        "anthropic" => Some("ANTHROPIC_API_KEY".to_string()),
         "litellm" => Some("LITELLM_API_KEY".to_string()),
         "gemini" => Some("GEMINI_API_KEY".to_string()),
         _ => None,
@@ -121,6 +121,7 @@ pub struct OpenAIVideoResult {
 pub async fn call_responses_vision(
     client: &Client,
     auth: &ProviderAuth,
+    system: Option<&str>,
     prompt: &str,
     model: &str,
     images: &[MediaInput],
@@ -150,6 +151,9 @@ pub async fn call_responses_vision(
         "input": [{ "role": "user", "content": content }],
         "temperature": temperature,
     });
+    if let Some(system) = system {
+        body["instructions"] = serde_json::json!(system);
+    }
     if let Some(max) = max_output_tokens {
         body["max_output_tokens"] = serde_json::json!(max);
     }
@@ -561,6 +565,11 @@ fn parse_usage(resp: &Value) -> Usage {
             thoughts_tokens: None,
             total_tokens: usage.get("total_tokens").and_then(|v| v.as_u64()),
             cost_usd,
+            cache_read_tokens: usage
+                .get("input_tokens_details")
+                .and_then(|v| v.get("cached_tokens"))
+                .and_then(|v| v.as_u64()),
+            ..Usage::default()
         }
     } else {
         Usage::default()
@@ -583,6 +592,9 @@ fn fill_missing_usage(mut usage: Usage, fallback: Usage) -> Usage {
     if usage.cost_usd.is_none() {
         usage.cost_usd = fallback.cost_usd;
     }
+    if usage.cache_read_tokens.is_none() {
+        usage.cache_read_tokens = fallback.cache_read_tokens;
+    }
     usage
 }
 
@@ -756,6 +768,7 @@ mod tests {
                 thoughts_tokens: None,
                 total_tokens: None,
                 cost_usd: None,
+                ..Usage::default()
             },
             Usage {
                 input_tokens: Some(1),
@@ -763,6 +776,7 @@ mod tests {
                 thoughts_tokens: Some(3),
                 total_tokens: Some(4),
                 cost_usd: Some(5.0),
+                ..Usage::default()
             },
         );
         assert_eq!(usage.input_tokens, Some(10));
@@ -230,7 +230,7 @@ fn embedded_gemini_registry() -> Result<ModelRegistry> {
             pricing: ModelPricing {
                 prompt_per_1k: pricing.input_cost_per_1k,
                 completion_per_1k: pricing.output_cost_per_1k,
-                request: None,
+                ..Default::default()
             },
             provider: pricing.provider.clone(),
             capability: pricing
@@ -340,6 +340,12 @@ fn parse_openrouter_models(value: &Value) -> OpenRouterCatalog {
                 .and_then(|p| parse_price(p.get("completion")))
                 .map(|v| v * 1000.0),
             request: pricing_obj.and_then(|p| parse_price(p.get("request"))),
+            cache_read_per_1k: pricing_obj
+                .and_then(|p| parse_price(p.get("input_cache_read")))
+                .map(|v| v * 1000.0),
+            cache_write_per_1k: pricing_obj
+                .and_then(|p| parse_price(p.get("input_cache_write")))
+                .map(|v| v * 1000.0),
         };
 
         let max_output_tokens = item
@@ -422,6 +428,8 @@ fn parse_litellm_models(value: &Value) -> ModelRegistry {
 
         let input_cost = parse_price(model_info.get("input_cost_per_token"));
         let output_cost = parse_price(model_info.get("output_cost_per_token"));
+        let cache_read_cost = parse_price(model_info.get("cache_read_input_token_cost"));
+        let cache_write_cost = parse_price(model_info.get("cache_creation_input_token_cost"));
         let max_tokens = model_info
             .get("max_input_tokens")
             .or_else(|| model_info.get("max_tokens"))
@@ -441,6 +449,8 @@ fn parse_litellm_models(value: &Value) -> ModelRegistry {
                 prompt_per_1k: input_cost.map(|v| v * 1000.0),
                 completion_per_1k: output_cost.map(|v| v * 1000.0),
                 request: None,
+                cache_read_per_1k: cache_read_cost.map(|v| v * 1000.0),
+                cache_write_per_1k: cache_write_cost.map(|v| v * 1000.0),
             },
             provider: Some("litellm".to_string()),
             capability: None,
@@ -494,6 +504,16 @@ pub fn estimate_pricing(
     Ok(estimate)
 }
 
+/// Cost of a finished call priced from the registry, for providers that do
+/// not report one. Cached input is charged at the model's cached rates.
+pub fn usage_cost(
+    registry: Option<&ModelRegistry>,
+    model_id: &str,
+    usage: &yoetz_core::types::Usage,
+) -> Option<f64> {
+    registry?.find(model_id)?.pricing.cost(usage)
+}
+
 fn parse_openrouter_capability(item: &Value) -> Option<ModelCapability> {
     let mut cap = ModelCapability::default();
 
@@ -773,6 +793,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn pricing_parsers_read_cached_input_rates() {
+        let catalog = parse_openrouter_models(&json!({
+            "data": [{
+                "id": "anthropic/claude-sonnet-4.5",
+                "pricing": {
+                    "prompt": "0.000003",
+                    "completion": "0.000015",
+                    "input_cache_read": "0.0000003",
+                    "input_cache_write": "0.00000375"
+                }
+            }]
+        }));
+        let pricing = &catalog
+            .registry
+            .find("anthropic/claude-sonnet-4.5")
+            .unwrap()
+            .pricing;
+        assert!((pricing.cache_read_per_1k.unwrap() - 0.0003).abs() < 1e-12);
+        assert!((pricing.cache_write_per_1k.unwrap() - 0.00375).abs() < 1e-12);
+
+        let registry = parse_litellm_models(&json!({
+            "data": [{
+                "model_name": "claude-sonnet",
+                "model_info": {
+                    "input_cost_per_token": 0.000003,
+                    "output_cost_per_token": 0.000015,
+                    "cache_read_input_token_cost": 0.0000003
+                }
+            }]
+        }));
+        let pricing = &registry.find("claude-sonnet").unwrap().pricing;
+        assert!((pricing.cache_read_per_1k.unwrap() - 0.0003).abs() < 1e-12);
+        assert_eq!(pricing.cache_write_per_1k, None);
+    }
+
     #[test]
     fn openrouter_reconcile_prunes_dead_provider_rows_after_later_merges() {
         let mut registry = registry_with_models(vec![
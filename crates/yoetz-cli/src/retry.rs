@@ -0,0 +1,433 @@
+//! Retries and model fallback around provider calls.
+//!
+//! A call is retried on the same model when the provider reports a rate
+//! limit, overload, or server error, or cannot be reached, waiting with
+//! jittered exponential backoff or for the provider's `Retry-After`. Once a
+//! model is exhausted (or fails in a way retrying cannot fix) the next
+//! fallback model is tried. Timeouts are not retried: the provider may still
+//! be working on, and billing, the first request.
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::Value;
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+use yoetz_core::config::RetryConfig;
+use yoetz_core::registry::ModelRegistry;
+use yoetz_core::types::CallAttempt;
+
+use crate::http::ProviderHttpError;
+use crate::{
+    normalize_model_name_with_aliases, registry, resolve_max_output_tokens_for_provider,
+    resolve_provider_for_model, resolve_registry_model_id, validate_cursor_options,
+    validate_model_or_suggest, AppContext,
+};
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub(crate) struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// `[retry]` from config, with `--max-attempts` taking precedence.
+    pub(crate) fn from_config(config: &RetryConfig, max_attempts: Option<u32>) -> Result<Self> {
+        let max_attempts = max_attempts
+            .or(config.max_attempts)
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS);
+        if max_attempts == 0 {
+            bail!("--max-attempts must be at least 1");
+        }
+        let initial_backoff = config
+            .initial_backoff_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_INITIAL_BACKOFF);
+        let max_backoff = config
+            .max_backoff_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_MAX_BACKOFF)
+            .max(initial_backoff);
+        Ok(Self {
+            max_attempts,
+            initial_backoff,
+            max_backoff,
+        })
+    }
+
+    /// Exponential backoff for the `retry`th retry (0-based), jittered to
+    /// between half and all of the nominal delay.
+    fn backoff(&self, retry: u32) -> Duration {
+        let nominal = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff);
+        nominal.mul_f64(0.5 + rand::random::<f64>() / 2.0)
+    }
+
+    /// How long to wait before retrying, or `None` when the model should be
+    /// given up on: attempts are used up, or the provider asks for a longer
+    /// wait than `max_backoff`.
+    fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        match retry_after {
+            Some(wait) if wait > self.max_backoff => None,
+            Some(wait) => Some(wait),
+            None => Some(self.backoff(attempt - 1)),
+        }
+    }
+}
+
+/// A model the call can be sent to: the requested one or a fallback.
+#[derive(Debug, Clone)]
+pub(crate) struct ModelTarget {
+    pub(crate) provider: Option<String>,
+    pub(crate) model: String,
+    pub(crate) max_output_tokens: Option<usize>,
+}
+
+/// Context for an error raised after part of the answer was already shown,
+/// e.g. a stream that broke mid-response. Such calls are neither retried nor
+/// handed to a fallback model, since the output cannot be taken back.
+#[derive(Debug)]
+pub(crate) struct PartialOutput;
+
+impl fmt::Display for PartialOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("response interrupted after partial output")
+    }
+}
+
+pub(crate) struct RetryOutcome<T> {
+    /// The target that answered, with its result, or the last error.
+    pub(crate) result: Result<(ModelTarget, T)>,
+    pub(crate) attempts: Vec<CallAttempt>,
+}
+
+impl<T> RetryOutcome<T> {
+    /// Attempts worth recording in `Usage`: only when something failed.
+    pub(crate) fn recorded_attempts(&self) -> Vec<CallAttempt> {
+        if self.attempts.iter().any(|attempt| attempt.error.is_some()) {
+            self.attempts.clone()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Call `targets` in order until one answers, retrying each per `policy`.
+pub(crate) async fn call_with_retry<T, F, Fut>(
+    policy: &RetryPolicy,
+    targets: &[ModelTarget],
+    mut call: F,
+) -> RetryOutcome<T>
+where
+    F: FnMut(&ModelTarget) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempts = Vec::new();
+    let mut last_error = None;
+    for (idx, target) in targets.iter().enumerate() {
+        let mut attempt = 1;
+        loop {
+            let err = match call(target).await {
+                Ok(value) => {
+                    attempts.push(record(target, None, None));
+                    return RetryOutcome {
+                        result: Ok((target.clone(), value)),
+                        attempts,
+                    };
+                }
+                Err(err) => err,
+            };
+            let summary = summarize(&err);
+            if err.downcast_ref::<PartialOutput>().is_some() {
+                attempts.push(record(target, Some(summary), None));
+                return RetryOutcome {
+                    result: Err(err),
+                    attempts,
+                };
+            }
+            let delay = retryable(&err).and_then(|hint| policy.delay(attempt, hint));
+            attempts.push(record(target, Some(summary.clone()), delay));
+            if let Some(delay) = delay {
+                eprintln!(
+                    "warning: {} failed ({summary}); retrying in {:.1}s (attempt {}/{})",
+                    target.model,
+                    delay.as_secs_f64(),
+                    attempt + 1,
+                    policy.max_attempts
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+                continue;
+            }
+            if let Some(next) = targets.get(idx + 1) {
+                eprintln!(
+                    "warning: {} failed ({summary}); falling back to {}",
+                    target.model, next.model
+                );
+            }
+            last_error = Some(err);
+            break;
+        }
+    }
+    RetryOutcome {
+        result: Err(last_error.unwrap_or_else(|| anyhow!("no model to call"))),
+        attempts,
+    }
+}
+
+/// `Some(retry_after)` when the error is worth retrying.
+fn retryable(err: &anyhow::Error) -> Option<Option<Duration>> {
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<ProviderHttpError>())
+        .filter(|http| http.is_retryable())
+        .map(|http| http.retry_after)
+}
+
+fn summarize(err: &anyhow::Error) -> String {
+    err.to_string()
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn record(target: &ModelTarget, error: Option<String>, delay: Option<Duration>) -> CallAttempt {
+    CallAttempt {
+        provider: target.provider.clone(),
+        model: target.model.clone(),
+        error,
+        retry_delay_ms: delay.map(|d| d.as_millis().min(u128::from(u64::MAX)) as u64),
+    }
+}
+
+/// What a fallback model must satisfy to stand in for the requested one.
+pub(crate) struct FallbackLimits<'a> {
+    pub(crate) registry: Option<&'a ModelRegistry>,
+    /// `--max-output-tokens` as given; each fallback resolves its own default.
+    pub(crate) max_output_tokens: Option<usize>,
+    pub(crate) input_tokens: usize,
+    /// Set when a budget is enforced: the estimate reserved for the requested
+    /// model. Fallbacks that would cost more, or cannot be priced, are skipped.
+    pub(crate) budget_usd: Option<Option<f64>>,
+    pub(crate) response_format: Option<&'a Value>,
+    pub(crate) has_media: bool,
+    pub(crate) temperature: f32,
+}
+
+/// Resolve `--fallback-models` (or `[defaults] fallback_models`) into call
+/// targets, skipping `exclude`d models.
+pub(crate) fn fallback_targets(
+    ctx: &AppContext,
+    requested: &[String],
+    primary_provider: Option<&str>,
+    exclude: &[&str],
+    limits: &FallbackLimits<'_>,
+) -> Result<Vec<ModelTarget>> {
+    let names = if requested.is_empty() {
+        ctx.config
+            .defaults
+            .fallback_models
+            .as_deref()
+            .unwrap_or(&[])
+    } else {
+        requested
+    };
+    let mut targets: Vec<ModelTarget> = Vec::new();
+    for name in names {
+        let model = normalize_model_name_with_aliases(name.trim(), &ctx.config.aliases);
+        if model.is_empty()
+            || exclude.contains(&model.as_str())
+            || targets.iter().any(|t| t.model == model)
+        {
+            continue;
+        }
+        let provider = resolve_provider_for_model(&model, limits.registry)
+            .or_else(|| primary_provider.map(str::to_string))
+            .ok_or_else(|| anyhow!("fallback model {model} needs a provider"))?;
+        let registry_id = resolve_registry_model_id(Some(&provider), Some(&model), limits.registry);
+        if let Some(id) = registry_id.as_deref() {
+            validate_model_or_suggest(id, limits.registry, ctx.allow_unknown)?;
+        }
+        let max_output_tokens = resolve_max_output_tokens_for_provider(
+            Some(&provider),
+            limits.max_output_tokens,
+            &ctx.config,
+            limits.registry,
+            registry_id.as_deref(),
+        );
+        validate_cursor_options(
+            Some(&provider),
+            max_output_tokens,
+            limits.response_format,
+            limits.has_media,
+            limits.temperature,
+            None,
+            None,
+        )?;
+        if let Some(reserved) = limits.budget_usd {
+            let estimate = registry_id.as_deref().and_then(|id| {
+                registry::estimate_pricing(
+                    limits.registry,
+                    id,
+                    limits.input_tokens,
+                    max_output_tokens.unwrap_or(4096),
+                )
+                .ok()?
+                .estimate_usd
+            });
+            match (estimate, reserved) {
+                (Some(estimate), Some(reserved)) if estimate <= reserved => {}
+                (Some(estimate), _) => {
+                    eprintln!(
+                        "warning: skipping fallback model {model}: estimated ${estimate:.4} exceeds the budget reserved for the requested model"
+                    );
+                    continue;
+                }
+                (None, _) => {
+                    eprintln!(
+                        "warning: skipping fallback model {model}: no cost estimate to check against the budget"
+                    );
+                    continue;
+                }
+            }
+        }
+        targets.push(ModelTarget {
+            provider: Some(provider),
+            model,
+            max_output_tokens,
+        });
+    }
+    Ok(targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn instant_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::from_millis(10),
+        }
+    }
+
+    fn target(model: &str) -> ModelTarget {
+        ModelTarget {
+            provider: Some("openrouter".to_string()),
+            model: model.to_string(),
+            max_output_tokens: None,
+        }
+    }
+
+    fn http_error(message: &str) -> anyhow::Error {
+        ProviderHttpError::from_message(message.to_string())
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn backoff_grows_with_jitter_and_caps() {
+        let policy = RetryPolicy::default();
+        for retry in 0..3 {
+            let nominal = Duration::from_secs(1 << retry);
+            let delay = policy.backoff(retry);
+            assert!(delay >= nominal / 2 && delay <= nominal, "{delay:?}");
+        }
+        assert!(policy.backoff(20) <= DEFAULT_MAX_BACKOFF);
+        assert_eq!(policy.delay(3, None), None);
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(5))),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(policy.delay(1, Some(Duration::from_secs(60))), None);
+    }
+
+    #[test]
+    fn from_config_prefers_flag_and_rejects_zero() {
+        let config = RetryConfig {
+            max_attempts: Some(5),
+            initial_backoff_ms: Some(100),
+            max_backoff_ms: None,
+        };
+        let policy = RetryPolicy::from_config(&config, Some(2)).unwrap();
+        assert_eq!(policy.max_attempts, 2);
+        assert_eq!(policy.initial_backoff, Duration::from_millis(100));
+        assert!(RetryPolicy::from_config(&config, Some(0)).is_err());
+    }
+
+    #[tokio::test]
+    async fn retries_rate_limits_then_falls_back() {
+        let calls = RefCell::new(Vec::new());
+        let outcome = call_with_retry(
+            &instant_policy(2),
+            &[target("primary"), target("backup")],
+            |target| {
+                calls.borrow_mut().push(target.model.clone());
+                let model = target.model.clone();
+                async move {
+                    match model.as_str() {
+                        "primary" => Err(http_error("http 429: rate limit exceeded")),
+                        _ => Ok("answer"),
+                    }
+                }
+            },
+        )
+        .await;
+
+        let (used, answer) = outcome.result.as_ref().unwrap();
+        assert_eq!(used.model, "backup");
+        assert_eq!(*answer, "answer");
+        assert_eq!(*calls.borrow(), ["primary", "primary", "backup"]);
+        let attempts = outcome.recorded_attempts();
+        assert_eq!(attempts.len(), 3);
+        assert!(attempts[0].retry_delay_ms.is_some());
+        assert_eq!(attempts[1].retry_delay_ms, None);
+        assert_eq!(attempts[2].error, None);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_client_errors_or_partial_output() {
+        let calls = RefCell::new(0);
+        let outcome = call_with_retry(&instant_policy(3), &[target("primary")], |_| {
+            *calls.borrow_mut() += 1;
+            async { Err::<(), _>(http_error("http 400: bad request")) }
+        })
+        .await;
+        assert!(outcome.result.is_err());
+        assert_eq!(*calls.borrow(), 1);
+
+        let calls = RefCell::new(0);
+        let outcome = call_with_retry(
+            &instant_policy(3),
+            &[target("primary"), target("backup")],
+            |_| {
+                *calls.borrow_mut() += 1;
+                async { Err::<(), _>(http_error("http 503: unavailable").context(PartialOutput)) }
+            },
+        )
+        .await;
+        assert!(outcome.result.is_err());
+        assert_eq!(*calls.borrow(), 1);
+    }
+}
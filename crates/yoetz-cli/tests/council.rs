@@ -4,6 +4,7 @@ use std::fs;
 use std::io::{Read, Write};
 use std::net::{TcpListener, TcpStream};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use tempfile::TempDir;
 
@@ -16,17 +17,25 @@ struct CouncilFixture {
     _dir: TempDir,
     config_path: PathBuf,
     state_dir: PathBuf,
+    requests: Arc<Mutex<Vec<String>>>,
 }
 
 impl CouncilFixture {
     fn new() -> Self {
         let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
         let address = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&requests);
         thread::spawn(move || {
             for stream in listener.incoming() {
                 let mut stream = stream.unwrap();
                 let request = read_http_request(&mut stream);
-                let (status, body) = if request.contains("fail-model") {
+                seen.lock().unwrap().push(request.clone());
+                // flaky-model answers the first round but fails debate rounds.
+                let fails = request.contains("fail-model")
+                    || (request.contains("flaky-model")
+                        && request.contains("## Your previous answer"));
+                let (status, body) = if fails {
                     (
                         "400 Bad Request",
                         serde_json::json!({
@@ -91,6 +100,7 @@ auto_sync_secs = 0
             _dir: dir,
             config_path,
             state_dir,
+            requests,
         }
     }
 
@@ -251,3 +261,297 @@ fn all_models_failed_remains_a_hard_error_under_partial_ok() {
     assert_eq!(failed["status"], "failed");
     assert!(!failed["error"].as_str().unwrap().is_empty());
 }
+
+#[test]
+fn synthesize_with_merges_anonymized_answers() {
+    let fixture = CouncilFixture::new();
+    let output = fixture
+        .command()
+        .args(["--synthesize-with", "judge-model"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let payload = parse_stdout(&output);
+    let synthesis = &payload["synthesis"];
+    assert_eq!(synthesis["model"], "judge-model");
+    assert_eq!(synthesis["content"], "successful answer");
+    assert_eq!(synthesis["responses"][0]["label"], "A");
+    assert_eq!(synthesis["responses"][0]["model"], "success-model");
+    assert_eq!(synthesis["responses"].as_array().unwrap().len(), 1);
+    assert_eq!(payload["summary"]["cost_usd"], 0.5);
+    assert_eq!(payload["usage"]["total_tokens"], 20);
+    assert_eq!(payload["pricing"]["synthesis"]["model"], "judge-model");
+
+    let requests = fixture.requests.lock().unwrap();
+    let judge = requests
+        .iter()
+        .find(|request| request.contains("judge-model"))
+        .unwrap();
+    assert!(judge.contains("## Response A"));
+    assert!(judge.contains("successful answer"));
+    assert!(judge.contains("compare"));
+    assert!(!judge.contains("success-model"));
+}
+
+#[test]
+fn rounds_debate_revises_answers_and_reports_agreement() {
+    let fixture = CouncilFixture::new();
+    let output = fixture
+        .command_with_models("success-model,second-model")
+        .args(["--rounds", "2"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let payload = parse_stdout(&output);
+    let summary = &payload["summary"];
+    assert_eq!(summary["succeeded"], 2);
+    assert_eq!(summary["cost_usd"], 1.0);
+    assert_eq!(summary["rounds"].as_array().unwrap().len(), 2);
+    assert_eq!(summary["rounds"][1]["round"], 2);
+    assert_eq!(summary["rounds"][1]["agreement"], 1.0);
+    assert_eq!(summary["convergence"], 0.0);
+
+    let session_dir = Path::new(payload["artifacts"]["session_dir"].as_str().unwrap());
+    for dir in ["round-1", "round-2", "models"] {
+        assert!(session_dir.join(dir).join("second-model.json").is_file());
+    }
+
+    let requests = fixture.requests.lock().unwrap();
+    assert_eq!(requests.len(), 4);
+    let debates: Vec<_> = requests
+        .iter()
+        .filter(|request| request.contains("## Your previous answer"))
+        .collect();
+    assert_eq!(debates.len(), 2);
+    assert!(debates
+        .iter()
+        .all(|request| request.contains("## Response A")));
+}
+
+#[test]
+fn rounds_keep_the_earlier_answer_of_a_member_that_fails_a_later_round() {
+    let fixture = CouncilFixture::new();
+    let output = fixture
+        .command_with_models("success-model,flaky-model")
+        .args(["--rounds", "2"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let payload = parse_stdout(&output);
+    let results = payload["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].get("from_round").is_none());
+    assert_eq!(results[1]["model"], "flaky-model");
+    assert_eq!(results[1]["from_round"], 1);
+    assert_eq!(results[1]["content"], "successful answer");
+    assert!(payload["errors"][0]["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("round 2: "));
+    assert_eq!(payload["summary"]["succeeded"], 2);
+    assert_eq!(payload["summary"]["failed"], 0);
+    assert_eq!(payload["summary"]["total"], 2);
+    assert_eq!(payload["summary"]["rounds"][1]["failed"], 1);
+
+    let session_dir = Path::new(payload["artifacts"]["session_dir"].as_str().unwrap());
+    let artifact: Value = serde_json::from_str(
+        &fs::read_to_string(session_dir.join("models").join("flaky-model.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(artifact["status"], "succeeded");
+}
+
+#[test]
+fn consensus_reports_answers_that_do_not_match_the_schema() {
+    let fixture = CouncilFixture::new();
+    let schema_path = fixture._dir.path().join("schema.json");
+    fs::write(
+        &schema_path,
+        r#"{"type":"object","properties":{"verdict":{"type":"string"}},"required":["verdict"]}"#,
+    )
+    .unwrap();
+    let output = fixture
+        .command_with_models("success-model")
+        .arg("--response-schema")
+        .arg(&schema_path)
+        .arg("--consensus")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let consensus = &parse_stdout(&output)["consensus"];
+    assert!(consensus.get("agreement").is_none());
+    assert_eq!(consensus["invalid"][0]["model"], "success-model");
+    assert_eq!(consensus["invalid"][0]["error"], "answer is not JSON");
+
+    let rejected = fixture
+        .command_with_models("success-model")
+        .arg("--consensus")
+        .output()
+        .unwrap();
+    assert!(!rejected.status.success());
+    assert!(String::from_utf8_lossy(&rejected.stderr).contains("--response-schema"));
+}
+
+#[test]
+fn council_file_gives_members_their_own_role_and_settings() {
+    let fixture = CouncilFixture::new();
+    let council_path = fixture._dir.path().join("council.toml");
+    fs::write(
+        &council_path,
+        r#"
+[[members]]
+name = "security"
+model = "success-model"
+provider = "mock"
+temperature = 0.5
+role = "You are a security reviewer."
+
+[[members]]
+name = "performance"
+model = "second-model"
+provider = "mock"
+max_output_tokens = 321
+role = "You are a performance skeptic."
+"#,
+    )
+    .unwrap();
+    let output = yoetz()
+        .env("YOETZ_CONFIG_PATH", &fixture.config_path)
+        .env("YOETZ_DIR", &fixture.state_dir)
+        .env("MOCK_API_KEY", "test-key")
+        .args([
+            "--format",
+            "json",
+            "--allow-unknown",
+            "council",
+            "--prompt",
+            "compare",
+            "--council-file",
+        ])
+        .arg(&council_path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let payload = parse_stdout(&output);
+    assert_eq!(payload["results"][0]["name"], "security");
+    assert_eq!(payload["results"][1]["name"], "performance");
+
+    let requests = fixture.requests.lock().unwrap();
+    let security = requests
+        .iter()
+        .find(|request| request.contains("security reviewer"))
+        .unwrap();
+    assert!(security.contains(r#""role":"system""#));
+    assert!(security.contains(r#""temperature":0.5"#));
+    assert!(!security.contains("performance skeptic"));
+    let performance = requests
+        .iter()
+        .find(|request| request.contains("performance skeptic"))
+        .unwrap();
+    assert!(performance.contains(r#""max_tokens":321"#));
+
+    let unknown = yoetz()
+        .env("YOETZ_CONFIG_PATH", &fixture.config_path)
+        .env("YOETZ_DIR", &fixture.state_dir)
+        .args(["council", "--prompt", "compare", "--council", "missing"])
+        .output()
+        .unwrap();
+    assert!(!unknown.status.success());
+    assert!(String::from_utf8_lossy(&unknown.stderr).contains("unknown council `missing`"));
+}
+
+#[test]
+fn jsonl_streams_member_events_before_the_final_council() {
+    let fixture = CouncilFixture::new();
+    let output = yoetz()
+        .env("YOETZ_CONFIG_PATH", &fixture.config_path)
+        .env("YOETZ_DIR", &fixture.state_dir)
+        .env("MOCK_API_KEY", "test-key")
+        .args([
+            "--format",
+            "jsonl",
+            "--allow-unknown",
+            "council",
+            "--prompt",
+            "compare",
+            "--provider",
+            "mock",
+            "--models",
+            "success-model,fail-model",
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let events: Vec<Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let kinds: Vec<&str> = events
+        .iter()
+        .map(|event| event["type"].as_str().unwrap())
+        .collect();
+    assert_eq!(kinds.len(), 5, "events: {kinds:?}");
+    assert_eq!(
+        kinds
+            .iter()
+            .filter(|kind| **kind == "member_started")
+            .count(),
+        2
+    );
+    assert_eq!(kinds.last(), Some(&"council"));
+
+    let succeeded = events
+        .iter()
+        .find(|event| event["type"] == "member_succeeded")
+        .unwrap();
+    assert_eq!(succeeded["data"]["model"], "success-model");
+    assert_eq!(succeeded["data"]["content"], "successful answer");
+    assert_eq!(succeeded["data"]["usage"]["total_tokens"], 10);
+    let failed = events
+        .iter()
+        .find(|event| event["type"] == "member_failed")
+        .unwrap();
+    assert_eq!(failed["data"]["model"], "fail-model");
+    assert_eq!(failed["data"]["member"], 1);
+    assert!(failed["data"]["error"]
+        .as_str()
+        .unwrap()
+        .contains("forced model failure"));
+    // The event reports the same error the council result records.
+    assert_eq!(
+        failed["data"]["error"],
+        events[4]["data"]["errors"][0]["error"]
+    );
+    assert_eq!(events[4]["data"]["summary"]["succeeded"], 1);
+}
@@ -0,0 +1,499 @@
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn yoetz() -> Command {
+    #[allow(deprecated)]
+    Command::cargo_bin("yoetz").unwrap()
+}
+
+struct StreamFixture {
+    _dir: TempDir,
+    config_path: PathBuf,
+    state_dir: PathBuf,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl StreamFixture {
+    fn new() -> Self {
+        Self::rate_limited(0)
+    }
+
+    /// Answers the first `failures` requests with `429 Too Many Requests`.
+    fn rate_limited(failures: usize) -> Self {
+        Self::serve(failures, Vec::new())
+    }
+
+    /// Answers each request with the next of `replies` as a regular (not
+    /// streamed) JSON response.
+    fn json_replies(replies: Vec<Value>) -> Self {
+        Self::serve(0, replies)
+    }
+
+    fn serve(failures: usize, replies: Vec<Value>) -> Self {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let address = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&requests);
+        thread::spawn(move || {
+            for (index, stream) in listener.incoming().enumerate() {
+                let mut stream = stream.unwrap();
+                seen.lock().unwrap().push(read_http_request(&mut stream));
+                if index < failures {
+                    let body = r#"{"error":{"message":"rate limited"}}"#;
+                    write!(
+                        stream,
+                        "HTTP/1.1 429 Too Many Requests\r\nretry-after: 0\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                        body.len()
+                    )
+                    .unwrap();
+                    continue;
+                }
+                if let Some(reply) = replies.get(index - failures) {
+                    let body = reply.to_string();
+                    write!(
+                        stream,
+                        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                        body.len()
+                    )
+                    .unwrap();
+                    continue;
+                }
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\nconnection: close\r\n\r\n"
+                )
+                .unwrap();
+                for event in [
+                    r#"{"id":"stream-1","choices":[{"index":0,"delta":{"role":"assistant","content":"Hel"}}]}"#,
+                    r#"{"id":"stream-1","choices":[{"index":0,"delta":{"content":"lo"}}]}"#,
+                    r#"{"id":"stream-1","choices":[],"usage":{"prompt_tokens":7,"completion_tokens":2,"total_tokens":9,"prompt_tokens_details":{"cached_tokens":5}}}"#,
+                    "[DONE]",
+                ] {
+                    write!(stream, "data: {event}\n\n").unwrap();
+                    stream.flush().unwrap();
+                    thread::sleep(Duration::from_millis(10));
+                }
+            }
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("config.toml");
+        let state_dir = dir.path().join("state");
+        fs::write(
+            &config_path,
+            format!(
+                r#"
+[providers.mock]
+base_url = "http://{address}/v1"
+api_key_env = "MOCK_API_KEY"
+kind = "openai_compatible"
+
+[registry]
+auto_sync_secs = 0
+"#
+            ),
+        )
+        .unwrap();
+
+        Self {
+            _dir: dir,
+            config_path,
+            state_dir,
+            requests,
+        }
+    }
+
+    fn command(&self, format: &str) -> Command {
+        let mut command = self.ask(format, "stream-model");
+        command.arg("--stream");
+        command
+    }
+
+    /// `ask` against the mock provider without `--stream`.
+    fn ask(&self, format: &str, model: &str) -> Command {
+        let mut command = yoetz();
+        command
+            .env("YOETZ_CONFIG_PATH", &self.config_path)
+            .env("YOETZ_DIR", &self.state_dir)
+            .env("MOCK_API_KEY", "test-key")
+            .args([
+                "--format",
+                format,
+                "--allow-unknown",
+                "ask",
+                "--prompt",
+                "greet",
+                "--provider",
+                "mock",
+                "--model",
+                model,
+            ]);
+        command
+    }
+}
+
+fn read_http_request(stream: &mut TcpStream) -> String {
+    let mut bytes = Vec::new();
+    let mut chunk = [0_u8; 4096];
+    loop {
+        let read = stream.read(&mut chunk).unwrap();
+        if read == 0 {
+            break;
+        }
+        bytes.extend_from_slice(&chunk[..read]);
+        let Some(header_end) = bytes.windows(4).position(|window| window == b"\r\n\r\n") else {
+            continue;
+        };
+        let header_end = header_end + 4;
+        let headers = String::from_utf8_lossy(&bytes[..header_end]);
+        let content_length = headers
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("content-length")
+                    .then(|| value.trim().parse::<usize>().unwrap())
+            })
+            .unwrap_or(0);
+        if bytes.len() >= header_end + content_length {
+            break;
+        }
+    }
+    String::from_utf8(bytes).unwrap()
+}
+
+#[test]
+fn ask_stream_jsonl_emits_delta_usage_and_final_events() {
+    let fixture = StreamFixture::new();
+    let output = fixture.command("jsonl").output().unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let events: Vec<Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let kinds: Vec<&str> = events.iter().map(|e| e["type"].as_str().unwrap()).collect();
+    assert_eq!(kinds, ["delta", "delta", "usage", "final"]);
+    assert_eq!(events[0]["data"]["text"], "Hel");
+    assert_eq!(events[1]["data"]["text"], "lo");
+    assert_eq!(events[2]["data"]["input_tokens"], 7);
+    let result = &events[3]["data"];
+    assert_eq!(result["content"], "Hello");
+    assert_eq!(result["usage"]["total_tokens"], 9);
+
+    let response_json = result["artifacts"]["response_json"].as_str().unwrap();
+    let saved: Value = serde_json::from_str(&fs::read_to_string(response_json).unwrap()).unwrap();
+    assert_eq!(saved["content"], "Hello");
+
+    let request = fixture.requests.lock().unwrap()[0].clone();
+    assert!(request.starts_with("POST /v1/chat/completions"));
+    assert!(request.contains(r#""stream":true"#));
+    assert!(request.contains(r#""model":"stream-model""#));
+}
+
+#[test]
+fn ask_stream_text_prints_tokens_and_rejects_json() {
+    let fixture = StreamFixture::new();
+    fixture.command("text").assert().success().stdout("Hello\n");
+
+    fixture
+        .command("json")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("--stream needs --format"));
+}
+
+#[test]
+fn ask_stream_sends_system_prompt_and_transcript() {
+    let fixture = StreamFixture::new();
+    let messages = fixture._dir.path().join("messages.jsonl");
+    fs::write(
+        &messages,
+        concat!(
+            r#"{"role":"user","content":"earlier question"}"#,
+            "\n",
+            r#"{"role":"assistant","content":"earlier answer"}"#,
+            "\n",
+        ),
+    )
+    .unwrap();
+    fixture
+        .command("text")
+        .args(["--system", "answer tersely", "--messages"])
+        .arg(&messages)
+        .assert()
+        .success();
+
+    let request = fixture.requests.lock().unwrap()[0].clone();
+    let body = &request[request.find("\r\n\r\n").unwrap() + 4..];
+    let body: Value = serde_json::from_str(body).unwrap();
+    let roles: Vec<&str> = body["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|message| message["role"].as_str().unwrap())
+        .collect();
+    assert_eq!(roles, ["system", "user", "assistant", "user"]);
+    assert_eq!(body["messages"][0]["content"], "answer tersely");
+    assert_eq!(body["messages"][3]["content"], "greet");
+}
+
+#[test]
+fn ask_thread_replays_previous_turns() {
+    let fixture = StreamFixture::new();
+    for _ in 0..2 {
+        fixture
+            .command("text")
+            .args(["--thread", "design"])
+            .assert()
+            .success();
+    }
+
+    let request = fixture.requests.lock().unwrap()[1].clone();
+    let body = &request[request.find("\r\n\r\n").unwrap() + 4..];
+    let body: Value = serde_json::from_str(body).unwrap();
+    let messages = body["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[0]["content"], "greet");
+    assert_eq!(messages[1]["role"], "assistant");
+    assert_eq!(messages[1]["content"], "Hello");
+    assert_eq!(messages[2]["content"], "greet");
+}
+
+fn final_result(output: &std::process::Output) -> Value {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let last = stdout.lines().last().unwrap();
+    let event: Value = serde_json::from_str(last).unwrap();
+    assert_eq!(event["type"], "final");
+    event["data"].clone()
+}
+
+#[test]
+fn ask_retries_rate_limited_calls_and_records_attempts() {
+    let fixture = StreamFixture::rate_limited(1);
+    let output = fixture.command("jsonl").output().unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("retrying in"));
+
+    let result = final_result(&output);
+    assert_eq!(result["content"], "Hello");
+    let attempts = result["usage"]["attempts"].as_array().unwrap();
+    assert_eq!(attempts.len(), 2);
+    assert!(attempts[0]["error"].as_str().unwrap().contains("http 429"));
+    assert_eq!(attempts[1]["model"], "stream-model");
+    assert!(attempts[1].get("error").is_none());
+    assert_eq!(fixture.requests.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn ask_falls_back_to_next_model_when_retries_run_out() {
+    let fixture = StreamFixture::rate_limited(2);
+    let output = fixture
+        .command("jsonl")
+        .args(["--max-attempts", "2", "--fallback-models", "backup-model"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("falling back to backup-model"));
+
+    let result = final_result(&output);
+    assert_eq!(result["model"], "backup-model");
+    assert_eq!(result["usage"]["attempts"].as_array().unwrap().len(), 3);
+    let requests = fixture.requests.lock().unwrap();
+    assert!(requests[1].contains(r#""model":"stream-model""#));
+    assert!(requests[2].contains(r#""model":"backup-model""#));
+}
+
+#[test]
+fn ask_cache_replays_identical_requests_without_calling_provider() {
+    let fixture = StreamFixture::new();
+    let first = fixture
+        .command("jsonl")
+        .args(["--cache", "read"])
+        .output()
+        .unwrap();
+    assert!(first.status.success());
+    assert!(final_result(&first).get("cached").is_none());
+
+    let replay = fixture
+        .command("jsonl")
+        .args(["--cache", "read"])
+        .output()
+        .unwrap();
+    assert!(
+        replay.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&replay.stderr)
+    );
+    let result = final_result(&replay);
+    assert_eq!(result["cached"], true);
+    assert_eq!(result["content"], "Hello");
+    assert_eq!(result["usage"]["cost_usd"], 0.0);
+    assert_eq!(result["usage"]["input_tokens"], 7);
+    assert_eq!(fixture.requests.lock().unwrap().len(), 1);
+
+    // A different temperature misses; `off` bypasses the stored entry.
+    fixture
+        .command("text")
+        .args(["--cache", "read", "--temperature", "0.5"])
+        .assert()
+        .success();
+    fixture
+        .command("text")
+        .args(["--cache", "off"])
+        .assert()
+        .success();
+    assert_eq!(fixture.requests.lock().unwrap().len(), 3);
+    assert!(fixture.state_dir.join("cache").is_dir());
+}
+
+#[test]
+fn ask_prompt_cache_sends_bundle_ahead_of_prompt_with_breakpoint() {
+    let fixture = StreamFixture::new();
+    let workdir = fixture._dir.path();
+    fs::write(workdir.join("notes.txt"), "shared bundle context\n").unwrap();
+    let output = fixture
+        .ask("json", "claude-test")
+        .current_dir(workdir)
+        .args(["--files", "notes.txt", "--prompt-cache"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["content"], "Hello");
+    assert_eq!(result["usage"]["cache_read_tokens"], 5);
+
+    // Claude models cannot be marked through litellm, so the call went
+    // straight to the mock's chat endpoint.
+    let request = fixture.requests.lock().unwrap()[0].clone();
+    let body = &request[request.find("\r\n\r\n").unwrap() + 4..];
+    let body: Value = serde_json::from_str(body).unwrap();
+    assert_eq!(body["model"], "claude-test");
+    let parts = body["messages"][0]["content"].as_array().unwrap();
+    assert_eq!(parts.len(), 2);
+    let context = parts[0]["text"].as_str().unwrap();
+    assert!(context.contains("shared bundle context"));
+    assert!(!context.contains("greet"));
+    assert_eq!(parts[0]["cache_control"]["type"], "ephemeral");
+    assert!(parts[1]["text"].as_str().unwrap().contains("greet"));
+    assert!(parts[1].get("cache_control").is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn ask_tools_runs_requested_tools_and_records_calls() {
+    let tool_call = |id: &str, name: &str, arguments: &str| {
+        serde_json::json!({
+            "id": id,
+            "type": "function",
+            "function": { "name": name, "arguments": arguments },
+        })
+    };
+    let usage =
+        serde_json::json!({ "prompt_tokens": 10, "completion_tokens": 2, "total_tokens": 12 });
+    let fixture = StreamFixture::json_replies(vec![
+        serde_json::json!({
+            "id": "tools-1",
+            "choices": [{ "message": {
+                "role": "assistant",
+                "content": null,
+                "tool_calls": [
+                    tool_call("call_1", "echo_args", r#"{"path":"src"}"#),
+                    tool_call("call_2", "fail", "{}"),
+                ],
+            }}],
+            "usage": usage,
+        }),
+        serde_json::json!({
+            "id": "tools-2",
+            "choices": [{ "message": { "role": "assistant", "content": "src holds the code" } }],
+            "usage": usage,
+        }),
+    ]);
+    let spec = fixture._dir.path().join("tools.json");
+    fs::write(
+        &spec,
+        serde_json::json!({ "tools": [
+            { "name": "echo_args", "description": "Echo the arguments", "command": ["cat"] },
+            { "name": "fail", "command": ["sh", "-c", "exit 3"] },
+            { "name": "hidden", "command": ["true"] },
+        ]})
+        .to_string(),
+    )
+    .unwrap();
+
+    let output = fixture
+        .ask("json", "tool-model")
+        .arg("--tools")
+        .arg(&spec)
+        .args(["--allow-tool", "echo_args,fail"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["content"], "src holds the code");
+    assert_eq!(result["usage"]["input_tokens"], 20);
+
+    let requests = fixture.requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    let body = |request: &str| -> Value {
+        serde_json::from_str(&request[request.find("\r\n\r\n").unwrap() + 4..]).unwrap()
+    };
+    let first = body(&requests[0]);
+    let offered: Vec<&str> = first["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["function"]["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(offered, ["echo_args", "fail"]);
+    let second = body(&requests[1]);
+    let messages = second["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 4);
+    assert_eq!(messages[1]["tool_calls"][0]["id"], "call_1");
+    assert_eq!(messages[2]["role"], "tool");
+    assert_eq!(messages[2]["tool_call_id"], "call_1");
+    assert_eq!(messages[2]["content"], r#"{"path":"src"}"#);
+    assert_eq!(messages[3]["content"], r#"{"error":"exited with 3"}"#);
+
+    let log = result["artifacts"]["tool_calls"].as_str().unwrap();
+    let records: Vec<Value> = fs::read_to_string(log)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["name"], "echo_args");
+    assert_eq!(records[0]["arguments"]["path"], "src");
+    assert_eq!(records[0]["result"]["path"], "src");
+    assert_eq!(records[1]["exit_code"], 3);
+    assert_eq!(records[1]["error"], "exited with 3");
+}
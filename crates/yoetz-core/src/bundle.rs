@@ -1,12 +1,21 @@
-use crate::types::{Bundle, BundleFile, BundleStats};
+use crate::extract;
+use crate::git::{self, GitSelector};
+use crate::imports;
+use crate::outline;
+use crate::redact::{Redactor, SecretRule};
+use crate::tokenizer::Tokenizer;
+use crate::types::{
+    Bundle, BundleFile, BundleStats, DropReason, DroppedFile, Redaction, Representation,
+};
 use anyhow::{anyhow, Context, Result};
 use ignore::overrides::OverrideBuilder;
 use ignore::WalkBuilder;
 use sha2::{Digest, Sha256};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Options for building a file bundle for LLM context.
 #[derive(Debug, Clone)]
@@ -19,20 +28,116 @@ pub struct BundleOptions {
     pub include_all: bool,
     pub include_hidden: bool,
     pub include_binary: bool,
+    /// Pack files by priority into this many estimated tokens (prompt included).
+    pub token_budget: Option<usize>,
+    /// Tokenizer used for `estimated_tokens` and budget packing.
+    pub tokenizer: Tokenizer,
+    /// Restrict walked files to those touched by a git selection.
+    pub git_selector: Option<GitSelector>,
+    /// Attach the selection's unified diff to the bundle.
+    pub include_diff: bool,
+    /// Outline files over `max_file_bytes` instead of truncating them.
+    pub outline_oversized: bool,
+    /// Globs whose matching files are always outlined, whatever their size.
+    pub outline_globs: Vec<String>,
+    /// Replace detected secrets in file contents and the diff with placeholders.
+    pub redact_secrets: bool,
+    /// User redaction rules added to the built-in ruleset.
+    pub secret_rules: Vec<SecretRule>,
+    /// Fail instead of bundling when any secret is detected.
+    pub fail_on_secrets: bool,
+    /// Captured command output added as `cmd://` files ahead of walked files.
+    pub commands: Vec<CommandOutput>,
+    /// Also bundle local Rust and JS/TS files imported by the selection, up
+    /// to this many hops from the selected files.
+    pub follow_imports: Option<usize>,
 }
 
+/// Default per-file byte cap; larger files are truncated or outlined.
+pub const DEFAULT_MAX_FILE_BYTES: usize = 200_000;
+/// Default byte cap for all bundled file contents together.
+pub const DEFAULT_MAX_TOTAL_BYTES: usize = 5_000_000;
+
 impl Default for BundleOptions {
     fn default() -> Self {
         Self {
             root: PathBuf::from("."),
             include: Vec::new(),
             exclude: Vec::new(),
-            max_file_bytes: 200_000,
-            max_total_bytes: 5_000_000,
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+            max_total_bytes: DEFAULT_MAX_TOTAL_BYTES,
             include_all: false,
             include_hidden: false,
             include_binary: false,
+            token_budget: None,
+            tokenizer: Tokenizer::default(),
+            git_selector: None,
+            include_diff: false,
+            outline_oversized: false,
+            outline_globs: Vec::new(),
+            redact_secrets: true,
+            secret_rules: Vec::new(),
+            fail_on_secrets: false,
+            commands: Vec::new(),
+            follow_imports: None,
+        }
+    }
+}
+
+/// Path prefix of bundle files holding captured command output.
+pub const COMMAND_PATH_PREFIX: &str = "cmd://";
+
+/// Output of a command run while building a bundle (`--include-cmd`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandOutput {
+    pub command: String,
+    pub stdout: String,
+    pub stderr: String,
+    /// Exit code, or `None` when the process was killed by a signal.
+    pub exit_code: Option<i32>,
+    /// Set when the command was killed after running this long.
+    pub timed_out_after: Option<Duration>,
+    /// Earlier output was dropped to stay within the output cap.
+    pub truncated: bool,
+}
+
+impl CommandOutput {
+    /// Bundle path of the captured output: `cmd://<command>`.
+    pub fn path(&self) -> String {
+        format!("{COMMAND_PATH_PREFIX}{}", self.command)
+    }
+
+    /// Plain-text transcript: the command line, how it ended, then each stream.
+    pub fn transcript(&self) -> String {
+        let status = match (self.timed_out_after, self.exit_code) {
+            (Some(limit), _) => format!("timed out after {}s", limit.as_secs()),
+            (None, Some(code)) => format!("exit code {code}"),
+            (None, None) => "killed by signal".to_string(),
+        };
+        let mut out = format!("$ {}\n[{status}]\n", self.command);
+        for (name, text) in [("stdout", &self.stdout), ("stderr", &self.stderr)] {
+            out.push_str(&format!("--- {name} ---\n"));
+            out.push_str(text);
+            if !text.is_empty() && !text.ends_with('\n') {
+                out.push('\n');
+            }
         }
+        out
+    }
+}
+
+fn command_file(output: &CommandOutput) -> BundleFile {
+    let content = output.transcript();
+    BundleFile {
+        path: output.path(),
+        bytes: content.len(),
+        sha256: hex::encode(Sha256::digest(content.as_bytes())),
+        truncated: output.truncated,
+        is_binary: false,
+        content: Some(content),
+        representation: Representation::CommandOutput,
+        included_by: None,
+        extractor: None,
     }
 }
 
@@ -55,9 +160,31 @@ fn has_glob_chars(s: &str) -> bool {
     s.contains('*') || s.contains('?') || s.contains('[') || s.contains('{')
 }
 
+/// When a file's content is replaced by its outline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutlineMode {
+    Never,
+    Oversized,
+    Always,
+}
+
+/// Cap on source bytes read to outline an oversized file.
+const OUTLINE_SOURCE_MAX_BYTES: usize = 16_000_000;
+/// Cap on notebook or document bytes read for text extraction; larger files
+/// are bundled like any other file.
+const EXTRACT_SOURCE_MAX_BYTES: usize = 64_000_000;
+
+/// Bytes read past `max_file_bytes` so a secret straddling the cut is still
+/// seen whole by the redactor.
+const REDACTION_LOOKAHEAD_BYTES: usize = 64 * 1024;
+
 /// Process a single file into a [`BundleFile`] entry.
 ///
+/// Content is redacted before it is outlined or truncated, so a cut cannot
+/// leave half a secret.
+///
 /// Returns `(BundleFile, content_bytes_consumed, content_chars)`.
+#[allow(clippy::too_many_arguments)]
 fn process_file(
     path: &Path,
     display_path: String,
@@ -65,12 +192,75 @@ fn process_file(
     max_total_bytes: usize,
     current_total: usize,
     include_binary: bool,
+    outline_mode: OutlineMode,
+    mut redactor: Option<&mut Redactor>,
+    redactions: &mut Vec<Redaction>,
 ) -> Result<(BundleFile, usize, usize)> {
-    let (data, sha256, file_size) = read_prefix_and_hash(path, max_file_bytes)
+    let can_outline = outline_mode != OutlineMode::Never && outline::is_supported(path);
+    let extractor = extract::extractor_for(path);
+    let read_limit = if can_outline {
+        OUTLINE_SOURCE_MAX_BYTES.max(max_file_bytes)
+    } else if extractor.is_some() {
+        EXTRACT_SOURCE_MAX_BYTES.max(max_file_bytes)
+    } else if redactor.is_some() {
+        max_file_bytes.saturating_add(REDACTION_LOOKAHEAD_BYTES)
+    } else {
+        max_file_bytes
+    };
+    let mut redact = |text: &mut String| {
+        if let Some(redactor) = redactor.as_deref_mut() {
+            redactions.extend(redactor.redact(&display_path, text));
+        }
+    };
+    let (data, sha256, file_size) = read_prefix_and_hash(path, read_limit)
         .with_context(|| format!("read file {display_path}"))?;
     let truncated_by_size = file_size > max_file_bytes;
-    let (mut content, mut truncated, is_binary) =
-        extract_text(&data, max_file_bytes, truncated_by_size);
+    let wants_outline = can_outline && (outline_mode == OutlineMode::Always || truncated_by_size);
+    let skeleton = if wants_outline {
+        match extract_text(&data, read_limit, file_size > read_limit) {
+            (Some(mut text), source_truncated, false) => {
+                redact(&mut text);
+                outline::outline(&text, path).map(|skeleton| (skeleton, source_truncated))
+            }
+            _ => None,
+        }
+    } else {
+        None
+    };
+    let extracted = extractor
+        .filter(|_| skeleton.is_none() && file_size <= read_limit)
+        .and_then(|extractor| {
+            let mut text = extract::extract(extractor, &data).ok()?;
+            redact(&mut text);
+            Some((extractor, text))
+        });
+    let extractor = extracted.as_ref().map(|(extractor, _)| *extractor);
+    let representation = if skeleton.is_some() {
+        Representation::Outline
+    } else {
+        Representation::Full
+    };
+    let (mut content, mut truncated, is_binary) = match skeleton {
+        Some((skeleton, source_truncated)) => {
+            let (skeleton, cut) = truncate_at_char_boundary(skeleton, max_file_bytes);
+            (Some(skeleton), source_truncated || cut, false)
+        }
+        None => match extracted {
+            Some((_, text)) => {
+                let (text, cut) = truncate_at_char_boundary(text, max_file_bytes);
+                (Some(text), cut, false)
+            }
+            None => match extract_text(&data, max_file_bytes, truncated_by_size) {
+                (Some(prefix), truncated, false) => {
+                    let mut text = with_lookahead(prefix, &data);
+                    redact(&mut text);
+                    let (text, cut) = truncate_at_char_boundary(text, max_file_bytes);
+                    (Some(text), truncated || cut, false)
+                }
+                other => other,
+            },
+        },
+    };
 
     if is_binary && !include_binary {
         return Ok((
@@ -81,6 +271,9 @@ fn process_file(
                 truncated,
                 is_binary,
                 content: None,
+                representation,
+                included_by: None,
+                extractor,
             },
             0,
             0,
@@ -104,12 +297,40 @@ fn process_file(
             truncated,
             is_binary,
             content,
+            representation,
+            included_by: None,
+            extractor,
         },
         content_len,
         content_chars,
     ))
 }
 
+/// `prefix` extended with the text read past it, up to the first NUL or
+/// invalid UTF-8.
+fn with_lookahead(mut prefix: String, data: &[u8]) -> String {
+    let rest = data.get(prefix.len()..).unwrap_or_default();
+    let rest = &rest[..rest.iter().position(|b| *b == 0).unwrap_or(rest.len())];
+    let valid = match std::str::from_utf8(rest) {
+        Ok(text) => text,
+        Err(e) => std::str::from_utf8(&rest[..e.valid_up_to()]).unwrap_or_default(),
+    };
+    prefix.push_str(valid);
+    prefix
+}
+
+fn truncate_at_char_boundary(mut text: String, max_bytes: usize) -> (String, bool) {
+    if text.len() <= max_bytes {
+        return (text, false);
+    }
+    let mut end = max_bytes;
+    while end > 0 && !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    (text, true)
+}
+
 /// Walk the filesystem and collect files into a [`Bundle`] for LLM context.
 ///
 /// Respects `.gitignore`, include/exclude globs, and size limits.
@@ -154,12 +375,62 @@ pub fn build_bundle(prompt: &str, options: BundleOptions) -> Result<Bundle> {
 
     let exclude_expanded: Vec<String> = options.exclude.iter().map(|p| expand_tilde(p)).collect();
 
-    let mut files = Vec::new();
+    let packing = options.token_budget.is_some();
+    // When packing, every candidate is read in full and the byte limit is
+    // enforced by `pack_candidates` so priority decides what is kept.
+    let walk_total_limit = if packing {
+        usize::MAX
+    } else {
+        options.max_total_bytes
+    };
+
+    // Canonical paths touched by the git selection; the walker only keeps these.
+    let changed_files: Option<HashSet<PathBuf>> = match &options.git_selector {
+        Some(selector) => Some(
+            selector
+                .changed_files(&options.root)?
+                .iter()
+                .filter_map(|path| file_identity(path).ok())
+                .collect(),
+        ),
+        None => None,
+    };
+
+    let outline_rules = if options.outline_globs.is_empty() {
+        None
+    } else {
+        let mut builder = OverrideBuilder::new(&options.root);
+        for pattern in &options.outline_globs {
+            builder.add(&expand_tilde(pattern))?;
+        }
+        Some(builder.build()?)
+    };
+    let outline_mode = |path: &Path| {
+        if outline_rules
+            .as_ref()
+            .is_some_and(|rules| rules.matched(path, false).is_whitelist())
+        {
+            OutlineMode::Always
+        } else if options.outline_oversized {
+            OutlineMode::Oversized
+        } else {
+            OutlineMode::Never
+        }
+    };
+
+    let mut redactor = if options.redact_secrets || options.fail_on_secrets {
+        Some(Redactor::new(&options.secret_rules)?)
+    } else {
+        None
+    };
+    let mut redactions = Vec::new();
+
+    let mut candidates = Vec::new();
     let mut seen_files = HashSet::new();
     let mut total_bytes = 0usize;
-    let mut total_chars = 0usize;
 
-    // 1. Read directly-specified absolute files.
+    // 1. Read directly-specified files. A git selection does not filter
+    //    these: naming a file is a request to send it alongside the changes.
     for (file_path, display_path) in &direct_files {
         if !file_path.is_file() {
             return Err(anyhow!(
@@ -169,25 +440,28 @@ pub fn build_bundle(prompt: &str, options: BundleOptions) -> Result<Bundle> {
         }
         let identity = file_identity(file_path)
             .with_context(|| format!("resolve file {}", file_path.display()))?;
-        if !seen_files.insert(identity) {
+        if !seen_files.insert(identity.clone()) {
             continue;
         }
-        let (bf, consumed_bytes, consumed_chars) = process_file(
+        let (bf, consumed_bytes, _) = process_file(
             file_path,
             display_path.clone(),
             options.max_file_bytes,
-            options.max_total_bytes,
+            walk_total_limit,
             total_bytes,
             options.include_binary,
+            outline_mode(file_path),
+            redactor.as_mut(),
+            &mut redactions,
         )?;
         total_bytes += consumed_bytes;
-        total_chars += consumed_chars;
-        files.push(bf);
+        candidates.push(Candidate::new(bf, identity, true, options.tokenizer));
     }
 
     // 2. Walk the directory tree for glob / relative patterns.
-    //    Also walk when include was empty (the "walk everything" case, e.g. --all).
-    if !glob_patterns.is_empty() || options.include_all {
+    //    Also walk when include was empty (the "walk everything" case, e.g. --all)
+    //    or when a git selection picks the files.
+    if !glob_patterns.is_empty() || options.include_all || changed_files.is_some() {
         let mut override_builder = OverrideBuilder::new(&options.root);
         for pattern in &glob_patterns {
             override_builder.add(pattern)?;
@@ -199,7 +473,7 @@ pub fn build_bundle(prompt: &str, options: BundleOptions) -> Result<Bundle> {
 
         let mut walker = WalkBuilder::new(&options.root);
         walker
-            .hidden(!options.include_hidden)
+            .hidden(!options.include_hidden && changed_files.is_none())
             .git_ignore(true)
             .git_exclude(true)
             .git_global(true)
@@ -215,7 +489,13 @@ pub fn build_bundle(prompt: &str, options: BundleOptions) -> Result<Bundle> {
             let path = entry.path();
             let identity =
                 file_identity(path).with_context(|| format!("resolve file {}", path.display()))?;
-            if !seen_files.insert(identity) {
+            if changed_files
+                .as_ref()
+                .is_some_and(|changed| !changed.contains(&identity))
+            {
+                continue;
+            }
+            if !seen_files.insert(identity.clone()) {
                 continue;
             }
             let rel_path = path
@@ -224,36 +504,445 @@ pub fn build_bundle(prompt: &str, options: BundleOptions) -> Result<Bundle> {
                 .to_string_lossy()
                 .to_string();
 
-            let (bf, consumed_bytes, consumed_chars) = process_file(
+            let (bf, consumed_bytes, _) = process_file(
                 path,
                 rel_path,
                 options.max_file_bytes,
-                options.max_total_bytes,
+                walk_total_limit,
                 total_bytes,
                 options.include_binary,
+                outline_mode(path),
+                redactor.as_mut(),
+                &mut redactions,
             )?;
             total_bytes += consumed_bytes;
-            total_chars += consumed_chars;
-            files.push(bf);
+            candidates.push(Candidate::new(bf, identity, false, options.tokenizer));
+        }
+    }
+
+    // 3. Follow local imports of the selection, breadth-first.
+    if let Some(depth) = options.follow_imports {
+        let followable = followable_files(&options, &exclude_expanded)?;
+        let mut frontier: Vec<usize> = (0..candidates.len()).collect();
+        for _ in 0..depth {
+            let mut next = Vec::new();
+            for idx in frontier {
+                let source = &candidates[idx];
+                if !imports::is_supported(&source.identity) {
+                    continue;
+                }
+                let importer = source.file.path.clone();
+                let Ok(text) = std::fs::read_to_string(&source.identity) else {
+                    continue;
+                };
+                for import in imports::local_imports(&source.identity, &text) {
+                    let Ok(identity) = file_identity(&import) else {
+                        continue;
+                    };
+                    let Some(rel_path) = followable.get(&identity) else {
+                        continue;
+                    };
+                    if !seen_files.insert(identity.clone()) {
+                        continue;
+                    }
+                    let (mut bf, consumed_bytes, _) = process_file(
+                        &identity,
+                        rel_path.clone(),
+                        options.max_file_bytes,
+                        walk_total_limit,
+                        total_bytes,
+                        options.include_binary,
+                        outline_mode(&identity),
+                        redactor.as_mut(),
+                        &mut redactions,
+                    )?;
+                    total_bytes += consumed_bytes;
+                    bf.included_by = Some(importer.clone());
+                    next.push(candidates.len());
+                    candidates.push(Candidate::new(bf, identity, false, options.tokenizer));
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+    }
+
+    // 4. Captured command output, ranked with the explicit files.
+    for output in &options.commands {
+        let mut bf = command_file(output);
+        let content_len = bf.bytes;
+        if total_bytes + content_len > walk_total_limit {
+            bf.content = Some("[omitted: exceeds max_total_bytes]".to_string());
+            bf.truncated = true;
+        } else {
+            total_bytes += content_len;
+        }
+        redact_file(redactor.as_mut(), &mut bf, &mut redactions);
+        let identity = PathBuf::from(&bf.path);
+        candidates.push(Candidate::new(bf, identity, true, options.tokenizer));
+    }
+
+    let mut diff_redactions = Vec::new();
+    let diff = match (&options.git_selector, options.include_diff) {
+        (Some(selector), true) => {
+            let mut diff = selector.diff(&options.root)?;
+            // Redact before truncating so a cut cannot leave half a secret.
+            if let Some(redactor) = redactor.as_mut() {
+                diff_redactions = redactor.redact(DIFF_REDACTION_PATH, &mut diff);
+            }
+            Some(truncate_diff(diff, options.max_total_bytes))
         }
+        _ => None,
+    };
+
+    // Prompt and diff are always sent, so they come off the budget first.
+    let base_tokens =
+        options.tokenizer.count(prompt) + diff.as_deref().map_or(0, |d| options.tokenizer.count(d));
+    let (kept, dropped) = match options.token_budget {
+        Some(budget) => pack_candidates(
+            candidates,
+            budget.saturating_sub(base_tokens),
+            options.max_total_bytes,
+            &options.root,
+        ),
+        None => (candidates, Vec::new()),
+    };
+    let file_tokens: usize = kept.iter().map(|c| c.tokens).sum();
+
+    // Only report secrets from content that is actually shipped.
+    let kept_paths: HashSet<&str> = kept.iter().map(|c| c.file.path.as_str()).collect();
+    redactions.retain(|r: &Redaction| kept_paths.contains(r.path.as_str()));
+    redactions.extend(diff_redactions);
+    if options.fail_on_secrets && !redactions.is_empty() {
+        let found: Vec<String> = redactions
+            .iter()
+            .map(|r| format!("  {}:{} {}", r.path, r.line, r.rule))
+            .collect();
+        return Err(anyhow!(
+            "refusing to bundle: {} secret(s) detected\n{}",
+            redactions.len(),
+            found.join("\n")
+        ));
     }
 
-    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut files: Vec<BundleFile> = kept.into_iter().map(|c| c.file).collect();
+    // Command output follows the files it explains.
+    files.sort_by(|a, b| {
+        let is_command = |f: &BundleFile| f.representation == Representation::CommandOutput;
+        is_command(a)
+            .cmp(&is_command(b))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+
+    let total_bytes = files
+        .iter()
+        .filter_map(|f| f.content.as_ref())
+        .map(|c| c.len())
+        .sum();
+    let total_chars = files
+        .iter()
+        .filter_map(|f| f.content.as_ref())
+        .map(|c| c.chars().count())
+        .sum::<usize>();
 
     let stats = BundleStats {
         file_count: files.len(),
         total_bytes,
         total_chars,
-        estimated_tokens: estimate_tokens(prompt.chars().count() + total_chars),
+        estimated_tokens: base_tokens + file_tokens,
+        token_budget: options.token_budget,
+        dropped,
+        tokenizer: Some(options.tokenizer),
     };
 
     Ok(Bundle {
         prompt: prompt.to_string(),
         files,
         stats,
+        diff,
+        redactions,
+        parts: Vec::new(),
+        delta: None,
+        line_numbers: false,
     })
 }
 
+/// Source files an import may pull in, keyed by identity with their bundle
+/// path: everything under the root the walker would visit, so gitignore,
+/// hidden-file, and `--exclude` rules still apply.
+fn followable_files(
+    options: &BundleOptions,
+    exclude: &[String],
+) -> Result<HashMap<PathBuf, String>> {
+    let mut override_builder = OverrideBuilder::new(&options.root);
+    for pattern in exclude {
+        override_builder.add(&format!("!{pattern}"))?;
+    }
+    let mut walker = WalkBuilder::new(&options.root);
+    walker
+        .hidden(!options.include_hidden)
+        .git_ignore(true)
+        .git_exclude(true)
+        .git_global(true)
+        .ignore(true)
+        .overrides(override_builder.build()?);
+
+    let mut files = HashMap::new();
+    for entry in walker.build() {
+        let entry = entry?;
+        let path = entry.path();
+        if !entry.file_type().is_some_and(|ft| ft.is_file()) || !imports::is_supported(path) {
+            continue;
+        }
+        let Ok(identity) = file_identity(path) else {
+            continue;
+        };
+        let rel_path = path
+            .strip_prefix(&options.root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string();
+        files.insert(identity, rel_path);
+    }
+    Ok(files)
+}
+
+/// Path reported for secrets found in the git diff.
+const DIFF_REDACTION_PATH: &str = "<diff>";
+
+fn redact_file(
+    redactor: Option<&mut Redactor>,
+    file: &mut BundleFile,
+    redactions: &mut Vec<Redaction>,
+) {
+    if let (Some(redactor), Some(content)) = (redactor, file.content.as_mut()) {
+        redactions.extend(redactor.redact(&file.path, content));
+    }
+}
+
+fn truncate_diff(mut diff: String, max_bytes: usize) -> String {
+    if diff.len() <= max_bytes {
+        return diff;
+    }
+    let mut end = max_bytes;
+    while end > 0 && !diff.is_char_boundary(end) {
+        end -= 1;
+    }
+    diff.truncate(end);
+    diff.push_str("\n... [diff truncated — exceeded max_total_bytes]\n");
+    diff
+}
+
+/// Count a built bundle's prompt, diff, and file contents with another tokenizer.
+///
+/// Matches `stats.estimated_tokens` when `tokenizer` is the one the bundle
+/// was built with. In an incremental bundle, unchanged files count only
+/// their listed path and modified files their diff, as rendered.
+pub fn count_bundle_tokens(bundle: &Bundle, tokenizer: Tokenizer) -> usize {
+    let file_tokens: usize = bundle
+        .files
+        .iter()
+        .filter_map(|f| sent_text(bundle, f))
+        .map(|c| tokenizer.count(c))
+        .sum();
+    let diff_tokens = bundle.diff.as_deref().map_or(0, |d| tokenizer.count(d));
+    tokenizer.count(&bundle.prompt) + diff_tokens + file_tokens
+}
+
+/// The text a render sends for `file`.
+fn sent_text<'a>(bundle: &'a Bundle, file: &'a BundleFile) -> Option<&'a str> {
+    let Some(delta) = &bundle.delta else {
+        return file.content.as_deref();
+    };
+    if delta.unchanged.binary_search(&file.path).is_ok() {
+        return Some(&file.path);
+    }
+    delta
+        .modified
+        .binary_search_by(|modified| modified.path.cmp(&file.path))
+        .ok()
+        .and_then(|idx| delta.modified[idx].diff.as_deref())
+        .or(file.content.as_deref())
+}
+
+/// A collected file plus what packing needs to rank it.
+struct Candidate {
+    file: BundleFile,
+    identity: PathBuf,
+    /// Named directly via `-f` rather than matched by a glob or directory walk.
+    explicit: bool,
+    /// Tokens of the file content alone, without Markdown scaffolding.
+    tokens: usize,
+}
+
+impl Candidate {
+    fn new(file: BundleFile, identity: PathBuf, explicit: bool, tokenizer: Tokenizer) -> Self {
+        let tokens = file.content.as_deref().map_or(0, |c| tokenizer.count(c));
+        Self {
+            file,
+            identity,
+            explicit,
+            tokens,
+        }
+    }
+}
+
+/// Markdown scaffolding rendered around each file (heading and code fence).
+const FILE_OVERHEAD_CHARS: usize = 24;
+/// How far back `git log` is scanned for per-file recency.
+const RECENCY_COMMIT_WINDOW: usize = 1000;
+const RECENCY_WEIGHT: f64 = 0.4;
+const PROXIMITY_WEIGHT: f64 = 0.4;
+const SIZE_WEIGHT: f64 = 0.2;
+
+/// Keep the highest-priority candidates that fit within `token_budget`.
+///
+/// Explicit files always rank first. The rest are ordered by a blend of git
+/// recency, proximity to uncommitted changes, and (smaller first) size, then
+/// greedily packed so that a large file that does not fit leaves room for
+/// smaller ones behind it.
+fn pack_candidates(
+    candidates: Vec<Candidate>,
+    token_budget: usize,
+    max_total_bytes: usize,
+    root: &Path,
+) -> (Vec<Candidate>, Vec<DroppedFile>) {
+    let scores = priority_scores(&candidates, root);
+    let mut ranked: Vec<(f64, Candidate)> = scores.into_iter().zip(candidates).collect();
+    ranked.sort_by(|(score_a, a), (score_b, b)| {
+        b.explicit
+            .cmp(&a.explicit)
+            .then(score_b.total_cmp(score_a))
+            .then_with(|| a.file.path.cmp(&b.file.path))
+    });
+
+    let mut remaining_tokens = token_budget;
+    let mut remaining_bytes = max_total_bytes;
+    let mut kept = Vec::new();
+    let mut dropped = Vec::new();
+    for (_, candidate) in ranked {
+        let Some(content_len) = candidate.file.content.as_ref().map(|c| c.len()) else {
+            // Skipped binaries only contribute a metadata line.
+            kept.push(candidate);
+            continue;
+        };
+        let tokens =
+            candidate.tokens + estimate_tokens(candidate.file.path.len() + FILE_OVERHEAD_CHARS);
+        let reason = if tokens > remaining_tokens {
+            Some(DropReason::TokenBudget)
+        } else if content_len > remaining_bytes {
+            Some(DropReason::MaxTotalBytes)
+        } else {
+            None
+        };
+        match reason {
+            Some(reason) => dropped.push(DroppedFile {
+                path: candidate.file.path,
+                bytes: candidate.file.bytes,
+                estimated_tokens: tokens,
+                reason,
+            }),
+            None => {
+                remaining_tokens -= tokens;
+                remaining_bytes -= content_len;
+                kept.push(candidate);
+            }
+        }
+    }
+    dropped.sort_by(|a, b| a.path.cmp(&b.path));
+    (kept, dropped)
+}
+
+/// Weighted priority score in `[0, 1]` for each candidate, in input order.
+fn priority_scores(candidates: &[Candidate], root: &Path) -> Vec<f64> {
+    let toplevel = git::toplevel(root);
+    let (commit_times, dirty) = match &toplevel {
+        Some(top) => (
+            git::last_commit_times(top, RECENCY_COMMIT_WINDOW),
+            git::dirty_paths(top),
+        ),
+        None => (HashMap::new(), Vec::new()),
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let dirty_set: HashSet<&PathBuf> = dirty.iter().collect();
+
+    let times: Vec<i64> = candidates
+        .iter()
+        .map(|c| {
+            if dirty_set.contains(&c.identity) {
+                return now;
+            }
+            match &toplevel {
+                Some(_) => commit_times.get(&c.identity).copied().unwrap_or(0),
+                None => modified_secs(&c.identity).unwrap_or(0),
+            }
+        })
+        .collect();
+    let sizes: Vec<i64> = candidates.iter().map(|c| c.file.bytes as i64).collect();
+    let recency = normalize(&times);
+    let largeness = normalize(&sizes);
+
+    candidates
+        .iter()
+        .enumerate()
+        .map(|(idx, c)| {
+            let proximity = toplevel
+                .as_deref()
+                .and_then(|top| c.identity.strip_prefix(top).ok())
+                .map(|rel| {
+                    dirty
+                        .iter()
+                        .filter_map(|changed| changed.strip_prefix(toplevel.as_deref()?).ok())
+                        .map(|changed| path_proximity(rel, changed))
+                        .fold(0.0, f64::max)
+                })
+                .unwrap_or(0.0);
+            RECENCY_WEIGHT * recency[idx]
+                + PROXIMITY_WEIGHT * proximity
+                + SIZE_WEIGHT * (1.0 - largeness[idx])
+        })
+        .collect()
+}
+
+fn modified_secs(path: &Path) -> Option<i64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    let secs = modified.duration_since(UNIX_EPOCH).ok()?.as_secs();
+    Some(secs as i64)
+}
+
+/// Min-max normalize values into `[0, 1]`; all zeros when they are equal.
+fn normalize(values: &[i64]) -> Vec<f64> {
+    let min = values.iter().copied().min().unwrap_or(0);
+    let max = values.iter().copied().max().unwrap_or(0);
+    if max == min {
+        return vec![0.0; values.len()];
+    }
+    let span = (max - min) as f64;
+    values.iter().map(|v| (v - min) as f64 / span).collect()
+}
+
+/// Fraction of leading components shared by two repo-relative paths: `1.0`
+/// for the same file, higher for siblings than for distant directories.
+fn path_proximity(a: &Path, b: &Path) -> f64 {
+    let a_parts: Vec<_> = a.components().collect();
+    let b_parts: Vec<_> = b.components().collect();
+    let longest = a_parts.len().max(b_parts.len());
+    if longest == 0 {
+        return 0.0;
+    }
+    let shared = a_parts
+        .iter()
+        .zip(&b_parts)
+        .take_while(|(x, y)| x == y)
+        .count();
+    shared as f64 / longest as f64
+}
+
 fn file_identity(path: &Path) -> Result<PathBuf> {
     path.canonicalize()
         .with_context(|| format!("canonicalize {}", path.display()))
@@ -311,16 +1000,18 @@ fn read_prefix_and_hash(
     Ok((prefix, hex::encode(digest), total))
 }
 
-/// Rough token count estimate (~4 chars per token).
-pub fn estimate_tokens(chars: usize) -> usize {
-    // Rough heuristic: 4 chars per token.
-    chars.div_ceil(4)
-}
+pub use crate::tokenizer::estimate_tokens;
 
 #[cfg(test)]
 mod tests {
     use super::extract_text;
-    use super::{build_bundle, estimate_tokens, expand_tilde, has_glob_chars, BundleOptions};
+    use super::{
+        build_bundle, count_bundle_tokens, estimate_tokens, expand_tilde, has_glob_chars,
+        path_proximity, BundleOptions, CommandOutput, FILE_OVERHEAD_CHARS,
+    };
+    use crate::git::GitSelector;
+    use crate::tokenizer::Tokenizer;
+    use crate::types::{BundleDelta, DropReason, Extractor, ModifiedFile, Representation};
     use sha2::{Digest, Sha256};
     use std::fs;
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -372,6 +1063,396 @@ mod tests {
         let _ = fs::remove_dir_all(&root);
     }
 
+    #[test]
+    fn bundle_token_budget_keeps_explicit_files_and_reports_drops() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("yoetz_budget_test_{nanos}"));
+        fs::create_dir_all(&root).unwrap();
+
+        fs::write(root.join("main.txt"), "m".repeat(300)).unwrap();
+        fs::write(root.join("other.txt"), "o".repeat(300)).unwrap();
+        fs::write(root.join("tiny.txt"), "t".repeat(20)).unwrap();
+
+        let options = BundleOptions {
+            root: root.clone(),
+            include: vec!["main.txt".to_string(), "*.txt".to_string()],
+            token_budget: Some(100),
+            tokenizer: Tokenizer::Heuristic,
+            ..BundleOptions::default()
+        };
+
+        let bundle = build_bundle("p", options).unwrap();
+        let paths: Vec<_> = bundle.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["main.txt", "tiny.txt"]);
+        assert_eq!(bundle.stats.token_budget, Some(100));
+        assert_eq!(bundle.stats.dropped.len(), 1);
+        assert_eq!(bundle.stats.dropped[0].path, "other.txt");
+        assert_eq!(bundle.stats.dropped[0].reason, DropReason::TokenBudget);
+        assert_eq!(bundle.stats.total_bytes, 320);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn bundle_token_budget_ranks_explicit_files_ahead_of_walk_order() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("yoetz_budget_order_test_{nanos}"));
+        fs::create_dir_all(&root).unwrap();
+        for name in ["a.txt", "b.txt", "z.txt"] {
+            fs::write(root.join(name), "x".repeat(200)).unwrap();
+        }
+        // Same size and no git history: the walk ranks a.txt, then b.txt.
+        let per_file = estimate_tokens(200) + estimate_tokens("a.txt".len() + FILE_OVERHEAD_CHARS);
+        let pack = |files: usize| {
+            let bundle = build_bundle(
+                "p",
+                BundleOptions {
+                    root: root.clone(),
+                    include: vec!["z.txt".to_string(), "*.txt".to_string()],
+                    token_budget: Some(Tokenizer::Heuristic.count("p") + files * per_file),
+                    tokenizer: Tokenizer::Heuristic,
+                    ..BundleOptions::default()
+                },
+            )
+            .unwrap();
+            let kept: Vec<String> = bundle.files.iter().map(|f| f.path.clone()).collect();
+            let dropped: Vec<String> = bundle
+                .stats
+                .dropped
+                .iter()
+                .map(|f| f.path.clone())
+                .collect();
+            (kept, dropped)
+        };
+
+        assert_eq!(
+            pack(1),
+            (vec!["z.txt".into()], vec!["a.txt".into(), "b.txt".into()])
+        );
+        assert_eq!(
+            pack(2),
+            (vec!["a.txt".into(), "z.txt".into()], vec!["b.txt".into()])
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn bundle_outlines_oversized_and_matching_sources() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("yoetz_outline_test_{nanos}"));
+        fs::create_dir_all(&root).unwrap();
+
+        let body = "    let x = 1;\n".repeat(20);
+        fs::write(root.join("big.rs"), format!("pub fn big() {{\n{body}}}\n")).unwrap();
+        fs::write(root.join("small.rs"), "fn small() {\n    1\n}\n").unwrap();
+        fs::write(root.join("notes.txt"), "n".repeat(200)).unwrap();
+
+        let options = BundleOptions {
+            root: root.clone(),
+            include: vec!["*".to_string()],
+            max_file_bytes: 100,
+            outline_oversized: true,
+            outline_globs: vec!["small.rs".to_string()],
+            ..BundleOptions::default()
+        };
+
+        let bundle = build_bundle("p", options).unwrap();
+        let file = |name: &str| bundle.files.iter().find(|f| f.path == name).unwrap();
+
+        let big = file("big.rs");
+        assert_eq!(big.representation, Representation::Outline);
+        assert_eq!(big.content.as_deref(), Some("pub fn big() { ... }\n"));
+        assert!(!big.truncated);
+
+        let small = file("small.rs");
+        assert_eq!(small.representation, Representation::Outline);
+        assert_eq!(small.content.as_deref(), Some("fn small() { ... }\n"));
+
+        let notes = file("notes.txt");
+        assert_eq!(notes.representation, Representation::Full);
+        assert!(notes.truncated);
+        assert_eq!(notes.content.as_ref().unwrap().len(), 100);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn bundle_redacts_secrets_and_fails_when_asked() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("yoetz_redact_test_{nanos}"));
+        fs::create_dir_all(&root).unwrap();
+        let key = ["sk-", "proj-abcdefghijklmnopqrstuvwx"].concat();
+        fs::write(
+            root.join("client.py"),
+            format!("import os\nKEY = \"{key}\"\n"),
+        )
+        .unwrap();
+        fs::write(root.join("clean.txt"), "nothing to see\n").unwrap();
+
+        let options = BundleOptions {
+            root: root.clone(),
+            include: vec!["*".to_string()],
+            ..BundleOptions::default()
+        };
+        let bundle = build_bundle("p", options.clone()).unwrap();
+        let client = bundle.files.iter().find(|f| f.path == "client.py").unwrap();
+        assert_eq!(
+            client.content.as_deref(),
+            Some("import os\nKEY = \"[REDACTED:openai_api_key:1]\"\n")
+        );
+        assert_eq!(bundle.redactions.len(), 1);
+        assert_eq!(bundle.redactions[0].path, "client.py");
+        assert_eq!(bundle.redactions[0].line, 2);
+
+        let err = build_bundle(
+            "p",
+            BundleOptions {
+                fail_on_secrets: true,
+                ..options
+            },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("client.py:2 openai_api_key"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn bundle_redacts_private_key_cut_by_max_file_bytes() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("yoetz_redact_cut_test_{nanos}"));
+        fs::create_dir_all(&root).unwrap();
+        let body = "MIIEpAIBAAKCAQEA7yq3\n".repeat(20);
+        let pem = [
+            "-----BEGIN RSA ",
+            "PRIVATE KEY-----\n",
+            &body,
+            "-----END RSA PRIVATE KEY-----\n",
+        ]
+        .concat();
+        let text = format!("# deploy notes\n{pem}done\n");
+        fs::write(root.join("notes.txt"), &text).unwrap();
+
+        // The cut lands inside the key body, well before its END line.
+        let max_file_bytes = text.find("-----END").unwrap() - 100;
+        let bundle = build_bundle(
+            "p",
+            BundleOptions {
+                root: root.clone(),
+                include: vec!["notes.txt".to_string()],
+                max_file_bytes,
+                ..BundleOptions::default()
+            },
+        )
+        .unwrap();
+        let notes = &bundle.files[0];
+        let content = notes.content.as_deref().unwrap();
+        assert!(notes.truncated);
+        assert!(!content.contains("MIIEpAIBAAKCAQEA"), "{content}");
+        assert!(content.contains("[REDACTED:private_key:1]"));
+        assert_eq!(bundle.redactions.len(), 1);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn bundle_adds_command_output_after_files() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("yoetz_cmd_test_{nanos}"));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("lib.rs"), "fn lib() {}\n").unwrap();
+        let token = ["ghp_", "abcdefghijklmnopqrstuvwxyz0123456789"].concat();
+
+        let options = BundleOptions {
+            root: root.clone(),
+            include: vec!["lib.rs".to_string()],
+            commands: vec![CommandOutput {
+                command: "cargo test".to_string(),
+                stdout: "running 1 test\n".to_string(),
+                stderr: format!("auth {token}"),
+                exit_code: Some(101),
+                ..CommandOutput::default()
+            }],
+            ..BundleOptions::default()
+        };
+        let bundle = build_bundle("why does this fail", options).unwrap();
+        let paths: Vec<_> = bundle.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["lib.rs", "cmd://cargo test"]);
+        let cmd = &bundle.files[1];
+        assert_eq!(cmd.representation, Representation::CommandOutput);
+        assert_eq!(
+            cmd.content.as_deref(),
+            Some("$ cargo test\n[exit code 101]\n--- stdout ---\nrunning 1 test\n--- stderr ---\nauth [REDACTED:github_token:1]\n")
+        );
+        assert_eq!(cmd.sha256.len(), 64);
+        assert_eq!(bundle.redactions[0].path, "cmd://cargo test");
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn bundle_extracts_notebook_text_and_tags_the_extractor() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("yoetz_extract_test_{nanos}"));
+        fs::create_dir_all(&root).unwrap();
+        let notebook = r#"{"cells": [{"cell_type": "code", "source": ["print(1)"], "outputs": [{"output_type": "stream", "text": "1\n"}]}]}"#;
+        fs::write(root.join("eda.ipynb"), notebook).unwrap();
+        fs::write(root.join("broken.pdf"), b"%PDF-1.4\0not really").unwrap();
+
+        let options = BundleOptions {
+            root: root.clone(),
+            include: vec!["*".to_string()],
+            ..BundleOptions::default()
+        };
+        let bundle = build_bundle("summarize", options).unwrap();
+        let pdf = bundle
+            .files
+            .iter()
+            .find(|f| f.path == "broken.pdf")
+            .unwrap();
+        assert!(pdf.is_binary);
+        assert_eq!(pdf.extractor, None);
+        let nb = bundle.files.iter().find(|f| f.path == "eda.ipynb").unwrap();
+        assert_eq!(nb.extractor, Some(Extractor::Notebook));
+        assert_eq!(
+            nb.content.as_deref(),
+            Some("# %% [code] cell 1\nprint(1)\n# Output:\n1\n\n")
+        );
+        assert_eq!(nb.bytes, notebook.len());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn bundle_follows_imports_to_depth_and_records_importer() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("Cargo.toml"), "[package]\n").unwrap();
+        fs::write(root.join("src/main.rs"), "mod a;\nmod generated;\n").unwrap();
+        fs::write(root.join("src/a.rs"), "use crate::b::Thing;\n").unwrap();
+        fs::write(root.join("src/b.rs"), "pub struct Thing;\n").unwrap();
+        fs::write(root.join("src/generated.rs"), "// generated\n").unwrap();
+        fs::write(root.join(".ignore"), "src/generated.rs\n").unwrap();
+
+        let options = |depth| BundleOptions {
+            root: root.to_path_buf(),
+            include: vec!["src/main.rs".to_string()],
+            follow_imports: Some(depth),
+            ..BundleOptions::default()
+        };
+        let bundle = build_bundle("p", options(1)).unwrap();
+        let files: Vec<_> = bundle
+            .files
+            .iter()
+            .map(|f| (f.path.as_str(), f.included_by.as_deref()))
+            .collect();
+        assert_eq!(
+            files,
+            vec![("src/a.rs", Some("src/main.rs")), ("src/main.rs", None)]
+        );
+
+        let bundle = build_bundle("p", options(2)).unwrap();
+        let b = bundle.files.iter().find(|f| f.path == "src/b.rs").unwrap();
+        assert_eq!(b.included_by.as_deref(), Some("src/a.rs"));
+        assert!(bundle.files.iter().all(|f| f.path != "src/generated.rs"));
+    }
+
+    #[test]
+    fn bundle_git_worktree_selects_changed_files_and_diff() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("yoetz_git_select_{nanos}"));
+        fs::create_dir_all(&root).unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(&root)
+                .args([
+                    "-c",
+                    "user.name=yoetz",
+                    "-c",
+                    "user.email=yoetz@example.com",
+                ])
+                .args(args)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {args:?}");
+        };
+        git(&["init", "-q"]);
+        fs::write(root.join("changed.txt"), "before\n").unwrap();
+        fs::write(root.join("stable.txt"), "stable\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "init"]);
+        fs::write(root.join("changed.txt"), "after\n").unwrap();
+        fs::write(root.join("new.txt"), "new\n").unwrap();
+
+        let options = BundleOptions {
+            root: root.clone(),
+            git_selector: Some(GitSelector::Worktree),
+            include_diff: true,
+            ..BundleOptions::default()
+        };
+        let bundle = build_bundle("review", options).unwrap();
+        let paths: Vec<_> = bundle.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["changed.txt", "new.txt"]);
+        let diff = bundle.diff.as_deref().unwrap();
+        assert!(diff.contains("-before"));
+        assert!(diff.contains("+after"));
+
+        git(&["add", "new.txt"]);
+        let staged = build_bundle(
+            "review",
+            BundleOptions {
+                root: root.clone(),
+                git_selector: Some(GitSelector::Staged),
+                ..BundleOptions::default()
+            },
+        )
+        .unwrap();
+        let paths: Vec<_> = staged.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["new.txt"]);
+        assert!(staged.diff.is_none());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn path_proximity_prefers_nearby_paths() {
+        use std::path::Path;
+        let changed = Path::new("src/net/client.rs");
+        assert_eq!(path_proximity(changed, changed), 1.0);
+        let sibling = path_proximity(Path::new("src/net/server.rs"), changed);
+        let distant = path_proximity(Path::new("docs/guide.md"), changed);
+        assert!(sibling > distant);
+        assert_eq!(distant, 0.0);
+    }
+
     #[test]
     fn expand_tilde_expands_home() {
         let home = std::env::var("HOME").unwrap();
@@ -564,6 +1645,7 @@ mod tests {
             BundleOptions {
                 root: root.clone(),
                 include: vec!["unicode.txt".to_string()],
+                tokenizer: Tokenizer::Heuristic,
                 ..BundleOptions::default()
             },
         )
@@ -573,7 +1655,50 @@ mod tests {
         assert_eq!(bundle.stats.total_chars, "a🙂b".chars().count());
         assert_eq!(
             bundle.stats.estimated_tokens,
-            estimate_tokens("🙂".chars().count() + "a🙂b".chars().count())
+            estimate_tokens("🙂".chars().count()) + estimate_tokens("a🙂b".chars().count())
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn count_bundle_tokens_counts_what_a_delta_sends() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("yoetz_delta_tokens_test_{nanos}"));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("a.txt"), "changed ".repeat(200)).unwrap();
+        fs::write(root.join("b.txt"), "same ".repeat(200)).unwrap();
+
+        let mut bundle = build_bundle(
+            "review",
+            BundleOptions {
+                root: root.clone(),
+                include: vec!["*.txt".to_string()],
+                tokenizer: Tokenizer::Heuristic,
+                ..BundleOptions::default()
+            },
+        )
+        .unwrap();
+        let full = count_bundle_tokens(&bundle, Tokenizer::Heuristic);
+        assert_eq!(full, bundle.stats.estimated_tokens);
+
+        let diff = "@@ -1 +1 @@\n-old\n+changed\n";
+        bundle.delta = Some(BundleDelta {
+            since_session: "earlier".to_string(),
+            modified: vec![ModifiedFile {
+                path: "a.txt".to_string(),
+                diff: Some(diff.to_string()),
+            }],
+            unchanged: vec!["b.txt".to_string()],
+            ..BundleDelta::default()
+        });
+        let tokens = |text: &str| Tokenizer::Heuristic.count(text);
+        assert_eq!(
+            count_bundle_tokens(&bundle, Tokenizer::Heuristic),
+            tokens("review") + tokens(diff) + tokens("b.txt")
         );
 
         let _ = fs::remove_dir_all(&root);
@@ -1,11 +1,14 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::paths::home_dir;
+use crate::redact::SecretRule;
+use crate::render::BundleFormat;
 
 /// Top-level yoetz configuration loaded from TOML files.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -18,6 +21,18 @@ pub struct Config {
     pub sessions: SessionsConfig,
     #[serde(default)]
     pub aliases: HashMap<String, String>,
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    /// Named file selections from `[bundles.<name>]`, applied with `--preset`.
+    #[serde(default)]
+    pub bundles: HashMap<String, BundlePreset>,
+    /// Named council panels from `[councils.<name>]`, run with `--council`.
+    #[serde(default)]
+    pub councils: HashMap<String, CouncilSpec>,
+    #[serde(default)]
+    pub retry: RetryConfig,
+    #[serde(default)]
+    pub cache: CacheConfig,
 }
 
 /// Default values for provider, model, and output settings.
@@ -27,6 +42,10 @@ pub struct Defaults {
     pub model: Option<String>,
     pub provider: Option<String>,
     pub max_output_tokens: Option<usize>,
+    /// Models tried in order when the requested model fails (`--fallback-models`).
+    pub fallback_models: Option<Vec<String>>,
+    /// Send bundles ahead of the prompt as a cacheable prefix (`--prompt-cache`).
+    pub prompt_cache: Option<bool>,
 }
 
 /// Configuration for a single LLM provider (base URL, API key, kind).
@@ -35,6 +54,8 @@ pub struct ProviderConfig {
     pub base_url: Option<String>,
     pub api_key_env: Option<String>,
     pub kind: Option<String>,
+    /// Bundle render format for this provider's models when `--bundle-format` is unset.
+    pub bundle_format: Option<BundleFormat>,
 }
 
 /// URLs and paths for model registry sources (OpenRouter, LiteLLM, org).
@@ -79,6 +100,123 @@ impl SessionsConfig {
     }
 }
 
+/// Retries for rate-limited, overloaded, or unreachable providers. Only
+/// honored from trusted config sources, since retries multiply requests.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RetryConfig {
+    /// Calls per model, including the first (default 3; 1 disables retries).
+    pub max_attempts: Option<u32>,
+    /// Backoff before the first retry, doubled per retry (default 1000).
+    pub initial_backoff_ms: Option<u64>,
+    /// Cap for backoff and for honoring `Retry-After` (default 30000).
+    pub max_backoff_ms: Option<u64>,
+}
+
+/// Local response cache for `ask` and `review`. Only honored from trusted
+/// config sources, since a cached answer stands in for a provider call.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CacheConfig {
+    /// Mode used when `--cache` is not passed (default `off`).
+    pub mode: Option<CacheMode>,
+    /// Seconds a cached response stays valid (default 7 days; 0 never expires).
+    pub ttl_secs: Option<u64>,
+}
+
+/// How `ask`/`review` use the response cache (`--cache`, `[cache] mode`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheMode {
+    /// Neither read nor write the cache.
+    #[default]
+    Off,
+    /// Answer from a fresh cached response when one exists; otherwise call
+    /// the provider and store its response.
+    Read,
+    /// Always call the provider and store (or replace) the cached response.
+    Write,
+}
+
+impl std::str::FromStr for CacheMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Ok(Self::Off),
+            "read" => Ok(Self::Read),
+            "write" => Ok(Self::Write),
+            other => Err(anyhow::anyhow!(
+                "invalid cache mode `{other}` (expected read, write, or off)"
+            )),
+        }
+    }
+}
+
+/// Secret redaction applied to bundled content before it is sent anywhere.
+/// Untrusted configs may add rules but cannot turn redaction off.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RedactionConfig {
+    /// Set to `false` to ship bundles unredacted. Defaults to enabled.
+    pub enabled: Option<bool>,
+    /// Extra patterns appended to the built-in ruleset.
+    #[serde(default)]
+    pub rules: Vec<SecretRule>,
+}
+
+/// A reusable bundle selection. Globs are added to the CLI's `-f`/`--exclude`;
+/// sizes, flags, and format apply unless the CLI sets them.
+///
+/// Presets from untrusted configs cannot pull in hidden or binary files, or
+/// include paths outside the working tree.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct BundlePreset {
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    pub max_file_bytes: Option<usize>,
+    pub max_total_bytes: Option<usize>,
+    pub include_hidden: Option<bool>,
+    pub include_binary: Option<bool>,
+    /// Walk every file under the root, like `--all`.
+    pub all: Option<bool>,
+    pub format: Option<BundleFormat>,
+}
+
+/// A council panel: the members `council` asks, each with its own settings.
+/// Loaded from `[councils.<name>]` in config or a `--council-file`.
+///
+/// Only honored from trusted config sources, since members choose the models
+/// that get called (and billed).
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CouncilSpec {
+    #[serde(default)]
+    pub members: Vec<CouncilMember>,
+}
+
+/// One council member. Unset fields fall back to the `council` flags.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CouncilMember {
+    pub model: String,
+    /// Label for this member in results, e.g. `security`.
+    pub name: Option<String>,
+    pub provider: Option<String>,
+    pub temperature: Option<f32>,
+    pub max_output_tokens: Option<usize>,
+    /// Role or persona prompt, sent ahead of any `--system` prompt.
+    pub role: Option<String>,
+}
+
+impl CouncilSpec {
+    /// Load a standalone council file (`[[members]]` tables).
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("read council file {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("parse council file {}", path.display()))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 struct ConfigFile {
     pub defaults: Option<Defaults>,
@@ -88,6 +226,11 @@ struct ConfigFile {
     pub notifications: Option<NotificationsConfig>,
     pub sessions: Option<SessionsConfig>,
     pub aliases: Option<HashMap<String, String>>,
+    pub redaction: Option<RedactionConfig>,
+    pub bundles: Option<HashMap<String, BundlePreset>>,
+    pub councils: Option<HashMap<String, CouncilSpec>>,
+    pub retry: Option<RetryConfig>,
+    pub cache: Option<CacheConfig>,
 }
 
 impl Config {
@@ -165,6 +308,36 @@ impl Config {
                 );
             }
         }
+        if let Some(councils) = other.councils {
+            if trusted {
+                self.councils.extend(councils);
+            } else {
+                eprintln!(
+                    "warning: ignoring [councils] from untrusted config {}",
+                    source.display()
+                );
+            }
+        }
+        if let Some(retry) = other.retry {
+            if trusted {
+                merge_retry(&mut self.retry, retry);
+            } else {
+                eprintln!(
+                    "warning: ignoring [retry] from untrusted config {}",
+                    source.display()
+                );
+            }
+        }
+        if let Some(cache) = other.cache {
+            if trusted {
+                merge_cache(&mut self.cache, cache);
+            } else {
+                eprintln!(
+                    "warning: ignoring [cache] from untrusted config {}",
+                    source.display()
+                );
+            }
+        }
         if let Some(aliases) = other.aliases {
             if trusted {
                 self.aliases.extend(aliases);
@@ -175,21 +348,97 @@ impl Config {
                 );
             }
         }
+        if let Some(redaction) = other.redaction {
+            if redaction.enabled.is_some() && !trusted {
+                eprintln!(
+                    "warning: ignoring redaction.enabled from untrusted config {}",
+                    source.display()
+                );
+            } else if redaction.enabled.is_some() {
+                self.redaction.enabled = redaction.enabled;
+            }
+            self.redaction.rules.extend(redaction.rules);
+        }
+        if let Some(bundles) = other.bundles {
+            for (name, preset) in bundles {
+                if trusted {
+                    self.bundles.insert(name, preset);
+                    continue;
+                }
+                match self.bundles.entry(name) {
+                    Entry::Occupied(entry) => eprintln!(
+                        "warning: ignoring bundles.{} from untrusted config {}; it would replace an existing preset",
+                        entry.key(),
+                        source.display()
+                    ),
+                    Entry::Vacant(entry) => {
+                        let preset = sanitize_untrusted_preset(entry.key(), preset, source);
+                        entry.insert(preset);
+                    }
+                }
+            }
+        }
     }
 }
 
 fn sanitize_untrusted_defaults(mut defaults: Defaults, source: &Path) -> Defaults {
-    warn_and_clear_untrusted_default(&mut defaults.profile, "profile", source);
-    warn_and_clear_untrusted_default(&mut defaults.model, "model", source);
-    warn_and_clear_untrusted_default(&mut defaults.provider, "provider", source);
-    warn_and_clear_untrusted_default(&mut defaults.max_output_tokens, "max_output_tokens", source);
+    warn_and_clear_untrusted_field(&mut defaults.profile, "defaults.profile", source);
+    warn_and_clear_untrusted_field(&mut defaults.model, "defaults.model", source);
+    warn_and_clear_untrusted_field(&mut defaults.provider, "defaults.provider", source);
+    warn_and_clear_untrusted_field(
+        &mut defaults.max_output_tokens,
+        "defaults.max_output_tokens",
+        source,
+    );
+    warn_and_clear_untrusted_field(
+        &mut defaults.fallback_models,
+        "defaults.fallback_models",
+        source,
+    );
+    warn_and_clear_untrusted_field(&mut defaults.prompt_cache, "defaults.prompt_cache", source);
     defaults
 }
 
-fn warn_and_clear_untrusted_default<T>(slot: &mut Option<T>, field: &str, source: &Path) {
+fn sanitize_untrusted_preset(name: &str, mut preset: BundlePreset, source: &Path) -> BundlePreset {
+    for (slot, field) in [
+        (&mut preset.include_hidden, "include_hidden"),
+        (&mut preset.include_binary, "include_binary"),
+        (&mut preset.all, "all"),
+    ] {
+        warn_and_clear_untrusted_field(slot, &format!("bundles.{name}.{field}"), source);
+    }
+    preset.include.retain(|pattern| {
+        let escapes = escapes_working_tree(pattern);
+        if escapes {
+            eprintln!(
+                "warning: ignoring bundles.{name}.include entry {pattern:?} outside the working tree from untrusted config {}",
+                source.display()
+            );
+        }
+        !escapes
+    });
+    preset
+}
+
+/// Whether an include glob can reach outside the directory it is resolved in.
+fn escapes_working_tree(pattern: &str) -> bool {
+    let path = Path::new(pattern);
+    path.is_absolute()
+        || pattern.starts_with('~')
+        || pattern.starts_with('/')
+        || pattern.starts_with('\\')
+        || path.components().any(|c| {
+            matches!(
+                c,
+                std::path::Component::ParentDir | std::path::Component::Prefix(_)
+            )
+        })
+}
+
+fn warn_and_clear_untrusted_field<T>(slot: &mut Option<T>, field: &str, source: &Path) {
     if slot.take().is_some() {
         eprintln!(
-            "warning: ignoring defaults.{field} from untrusted config {}",
+            "warning: ignoring {field} from untrusted config {}",
             source.display()
         );
     }
@@ -261,6 +510,12 @@ fn merge_defaults(target: &mut Defaults, other: Defaults) {
     if other.max_output_tokens.is_some() {
         target.max_output_tokens = other.max_output_tokens;
     }
+    if other.fallback_models.is_some() {
+        target.fallback_models = other.fallback_models;
+    }
+    if other.prompt_cache.is_some() {
+        target.prompt_cache = other.prompt_cache;
+    }
 }
 
 #[cfg(test)]
@@ -286,6 +541,8 @@ model = "gpt-5-4-pro"
                 model: Some("gpt-5-4-pro".to_string()),
                 provider: Some("evil".to_string()),
                 max_output_tokens: Some(99_999),
+                fallback_models: Some(vec!["evil-model".to_string()]),
+                prompt_cache: Some(true),
             }),
             providers: Some(HashMap::from([(
                 "evil".to_string(),
@@ -293,6 +550,7 @@ model = "gpt-5-4-pro"
                     base_url: Some("http://evil.example.com".to_string()),
                     api_key_env: Some("EVIL_KEY".to_string()),
                     kind: None,
+                    bundle_format: None,
                 },
             )])),
             registry: Some(RegistryConfig {
@@ -312,12 +570,33 @@ model = "gpt-5-4-pro"
                 "fast".to_string(),
                 "gpt-5-4-pro".to_string(),
             )])),
+            redaction: Some(RedactionConfig {
+                enabled: Some(false),
+                rules: vec![SecretRule {
+                    name: "repo_token".to_string(),
+                    pattern: "rt_[a-z0-9]{12}".to_string(),
+                }],
+            }),
+            bundles: None,
+            councils: None,
+            retry: Some(RetryConfig {
+                max_attempts: Some(50),
+                ..Default::default()
+            }),
+            cache: Some(CacheConfig {
+                mode: Some(CacheMode::Read),
+                ttl_secs: None,
+            }),
         };
         config.merge(file, false, Path::new("./yoetz.toml"));
         assert!(config.defaults.profile.is_none());
         assert!(config.defaults.model.is_none());
         assert!(config.defaults.provider.is_none());
         assert!(config.defaults.max_output_tokens.is_none());
+        assert!(config.defaults.fallback_models.is_none());
+        assert!(config.defaults.prompt_cache.is_none());
+        assert!(config.retry.max_attempts.is_none());
+        assert!(config.cache.mode.is_none());
         assert!(config.aliases.is_empty());
         // Restricted fields skipped
         assert!(config.providers.is_empty());
@@ -330,6 +609,9 @@ model = "gpt-5-4-pro"
         assert!(config.sessions.max_age_days.is_none());
         assert!(config.sessions.max_count.is_none());
         assert!(!config.sessions.retention_enabled());
+        // Repo configs may tighten redaction but never disable it.
+        assert!(config.redaction.enabled.is_none());
+        assert_eq!(config.redaction.rules.len(), 1);
     }
 
     #[test]
@@ -343,6 +625,7 @@ model = "gpt-5-4-pro"
                     base_url: Some("https://api.openai.com".to_string()),
                     api_key_env: Some("OPENAI_API_KEY".to_string()),
                     kind: None,
+                    bundle_format: None,
                 },
             )])),
             registry: Some(RegistryConfig {
@@ -353,6 +636,11 @@ model = "gpt-5-4-pro"
             notifications: None,
             sessions: None,
             aliases: None,
+            redaction: None,
+            bundles: None,
+            councils: None,
+            retry: None,
+            cache: None,
         };
         config.merge(
             file,
@@ -377,6 +665,11 @@ model = "gpt-5-4-pro"
             }),
             sessions: None,
             aliases: None,
+            redaction: None,
+            bundles: None,
+            councils: None,
+            retry: None,
+            cache: None,
         };
         config.merge(file, false, Path::new("./yoetz.toml"));
         assert_eq!(config.notifications.enabled, Some(false));
@@ -396,6 +689,11 @@ model = "gpt-5-4-pro"
             notifications: None,
             sessions: None,
             aliases: None,
+            redaction: None,
+            bundles: None,
+            councils: None,
+            retry: None,
+            cache: None,
         };
 
         config.merge(
@@ -425,6 +723,11 @@ model = "gpt-5-4-pro"
                 max_count: Some(100),
             }),
             aliases: None,
+            redaction: None,
+            bundles: None,
+            councils: None,
+            retry: None,
+            cache: None,
         };
 
         config.merge(
@@ -453,6 +756,122 @@ max_count = 50
         assert_eq!(sessions.max_age_days, Some(14));
         assert_eq!(sessions.max_count, Some(50));
     }
+
+    #[test]
+    fn untrusted_bundle_presets_are_sanitized() {
+        let toml_str = r#"
+[bundles.backend]
+include = ["crates/**/*.rs", "../secrets/*", "/etc/passwd", "~/.aws/credentials"]
+exclude = ["**/tests/**"]
+max_file_bytes = 50000
+include_hidden = true
+include_binary = true
+all = true
+format = "xml"
+
+[bundles.shared]
+include = ["README.md"]
+"#;
+        let file: ConfigFile = toml::from_str(toml_str).unwrap();
+        let mut config = Config::default();
+        config.bundles.insert(
+            "shared".to_string(),
+            BundlePreset {
+                include: vec!["docs/**".to_string()],
+                ..Default::default()
+            },
+        );
+        config.merge(file, false, Path::new("./yoetz.toml"));
+
+        let backend = &config.bundles["backend"];
+        assert_eq!(backend.include, vec!["crates/**/*.rs".to_string()]);
+        assert_eq!(backend.exclude, vec!["**/tests/**".to_string()]);
+        assert_eq!(backend.max_file_bytes, Some(50_000));
+        assert_eq!(backend.format, Some(BundleFormat::Xml));
+        assert!(backend.include_hidden.is_none());
+        assert!(backend.include_binary.is_none());
+        assert!(backend.all.is_none());
+        // A repo config cannot redefine a preset the user already has.
+        assert_eq!(
+            config.bundles["shared"].include,
+            vec!["docs/**".to_string()]
+        );
+    }
+
+    #[test]
+    fn trusted_bundle_presets_keep_every_field() {
+        let toml_str = r#"
+[bundles.ci]
+include = ["../shared/ci/*.yml"]
+include_hidden = true
+"#;
+        let file: ConfigFile = toml::from_str(toml_str).unwrap();
+        let mut config = Config::default();
+        config.merge(file, true, Path::new("/home/user/.yoetz/config.toml"));
+        let ci = &config.bundles["ci"];
+        assert_eq!(ci.include, vec!["../shared/ci/*.yml".to_string()]);
+        assert_eq!(ci.include_hidden, Some(true));
+    }
+
+    #[test]
+    fn councils_load_only_from_trusted_config() {
+        let toml_str = r#"
+[[councils.review.members]]
+model = "openai/gpt-5.2"
+name = "security"
+role = "You are a security reviewer."
+temperature = 0.2
+
+[[councils.review.members]]
+model = "gemini-3-pro"
+provider = "gemini"
+max_output_tokens = 2048
+"#;
+        let mut config = Config::default();
+        config.merge(
+            toml::from_str(toml_str).unwrap(),
+            false,
+            Path::new("./yoetz.toml"),
+        );
+        assert!(config.councils.is_empty());
+
+        config.merge(
+            toml::from_str(toml_str).unwrap(),
+            true,
+            Path::new("/home/user/.yoetz/config.toml"),
+        );
+        let members = &config.councils["review"].members;
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0].name.as_deref(), Some("security"));
+        assert_eq!(members[0].temperature, Some(0.2));
+        assert_eq!(members[1].provider.as_deref(), Some("gemini"));
+        assert_eq!(members[1].max_output_tokens, Some(2048));
+    }
+
+    #[test]
+    fn council_file_rejects_unknown_member_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("council.toml");
+        fs::write(&path, "[[members]]\nmodel = \"m\"\npersona = \"x\"\n").unwrap();
+        let error = CouncilSpec::load(&path).unwrap_err();
+        assert!(format!("{error:#}").contains("persona"));
+    }
+
+    #[test]
+    fn parse_redaction_rules_from_toml() {
+        let toml_str = r#"
+[redaction]
+enabled = true
+
+[[redaction.rules]]
+name = "internal_token"
+pattern = "itk_(?P<secret>[a-z0-9]{32})"
+"#;
+        let file: ConfigFile = toml::from_str(toml_str).unwrap();
+        let redaction = file.redaction.unwrap();
+        assert_eq!(redaction.enabled, Some(true));
+        assert_eq!(redaction.rules[0].name, "internal_token");
+    }
 }
 
 fn merge_provider(target: &mut ProviderConfig, other: &ProviderConfig) {
@@ -465,6 +884,9 @@ fn merge_provider(target: &mut ProviderConfig, other: &ProviderConfig) {
     if other.kind.is_some() {
         target.kind = other.kind.clone();
     }
+    if other.bundle_format.is_some() {
+        target.bundle_format = other.bundle_format;
+    }
 }
 
 fn merge_registry(target: &mut RegistryConfig, other: RegistryConfig) {
@@ -497,6 +919,27 @@ fn merge_notifications(target: &mut NotificationsConfig, other: NotificationsCon
     }
 }
 
+fn merge_retry(target: &mut RetryConfig, other: RetryConfig) {
+    if other.max_attempts.is_some() {
+        target.max_attempts = other.max_attempts;
+    }
+    if other.initial_backoff_ms.is_some() {
+        target.initial_backoff_ms = other.initial_backoff_ms;
+    }
+    if other.max_backoff_ms.is_some() {
+        target.max_backoff_ms = other.max_backoff_ms;
+    }
+}
+
+fn merge_cache(target: &mut CacheConfig, other: CacheConfig) {
+    if other.mode.is_some() {
+        target.mode = other.mode;
+    }
+    if other.ttl_secs.is_some() {
+        target.ttl_secs = other.ttl_secs;
+    }
+}
+
 fn merge_sessions(target: &mut SessionsConfig, other: SessionsConfig) {
     if other.no_session.is_some() {
         target.no_session = other.no_session;
@@ -0,0 +1,128 @@
+//! Incremental bundles: what changed since an earlier session's bundle.
+//!
+//! Files are matched by path and compared by `sha256` and bundled content.
+//! The new bundle keeps every file so later deltas can diff against it;
+//! renderers read [`BundleDelta`] to send only added files, unified diffs of
+//! modified ones, and the list of unchanged paths.
+
+use similar::TextDiff;
+use std::collections::{HashMap, HashSet};
+
+use crate::types::{Bundle, BundleDelta, BundleFile, ModifiedFile};
+
+/// Lines of unchanged context around each diff hunk.
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// Compare `current` against `previous`, the bundle of session `since_session`.
+pub fn bundle_delta(current: &Bundle, previous: &Bundle, since_session: &str) -> BundleDelta {
+    let before: HashMap<&str, &BundleFile> = previous
+        .files
+        .iter()
+        .map(|file| (file.path.as_str(), file))
+        .collect();
+    let mut delta = BundleDelta {
+        since_session: since_session.to_string(),
+        ..BundleDelta::default()
+    };
+    for file in &current.files {
+        match before.get(file.path.as_str()) {
+            None => delta.added.push(file.path.clone()),
+            Some(old) if old.sha256 == file.sha256 && old.content == file.content => {
+                delta.unchanged.push(file.path.clone());
+            }
+            Some(old) => delta.modified.push(ModifiedFile {
+                path: file.path.clone(),
+                diff: unified_diff(old, file),
+            }),
+        }
+    }
+    let current_paths: HashSet<&str> = current.files.iter().map(|f| f.path.as_str()).collect();
+    delta.removed = previous
+        .files
+        .iter()
+        .filter(|file| !current_paths.contains(file.path.as_str()))
+        .map(|file| file.path.clone())
+        .collect();
+
+    delta.added.sort();
+    delta.modified.sort_by(|a, b| a.path.cmp(&b.path));
+    delta.unchanged.sort();
+    delta.removed.sort();
+    delta
+}
+
+/// Unified diff of the bundled text, or `None` when the file is better sent
+/// whole: either side has no text, the representation changed, or the diff
+/// would be larger than the new content.
+fn unified_diff(old: &BundleFile, new: &BundleFile) -> Option<String> {
+    let (Some(before), Some(after)) = (old.content.as_deref(), new.content.as_deref()) else {
+        return None;
+    };
+    if old.representation != new.representation {
+        return None;
+    }
+    let diff = TextDiff::from_lines(before, after)
+        .unified_diff()
+        .context_radius(DIFF_CONTEXT_LINES)
+        .header(&format!("a/{}", old.path), &format!("b/{}", new.path))
+        .to_string();
+    (diff.len() < after.len()).then_some(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::bundle_delta;
+    use crate::types::{Bundle, BundleFile, BundleStats, Representation};
+
+    fn bundle(files: &[(&str, &str)]) -> Bundle {
+        Bundle {
+            prompt: "p".to_string(),
+            files: files
+                .iter()
+                .map(|(path, content)| BundleFile {
+                    path: path.to_string(),
+                    bytes: content.len(),
+                    sha256: format!("sha-of-{content}"),
+                    truncated: false,
+                    is_binary: false,
+                    content: Some(content.to_string()),
+                    representation: Representation::Full,
+                    included_by: None,
+                    extractor: None,
+                })
+                .collect(),
+            stats: BundleStats::default(),
+            diff: None,
+            redactions: Vec::new(),
+            parts: Vec::new(),
+            delta: None,
+            line_numbers: false,
+        }
+    }
+
+    #[test]
+    fn delta_classifies_files_and_diffs_modified_ones() {
+        let long: String = (1..=40).map(|n| format!("line {n}\n")).collect();
+        let edited = long.replace("line 20\n", "line twenty\n");
+        let previous = bundle(&[("a.rs", &long), ("gone.rs", "x\n"), ("same.rs", "s\n")]);
+        let current = bundle(&[("a.rs", &edited), ("new.rs", "n\n"), ("same.rs", "s\n")]);
+
+        let delta = bundle_delta(&current, &previous, "20260101_000000_abcdef");
+        assert_eq!(delta.since_session, "20260101_000000_abcdef");
+        assert_eq!(delta.added, vec!["new.rs"]);
+        assert_eq!(delta.unchanged, vec!["same.rs"]);
+        assert_eq!(delta.removed, vec!["gone.rs"]);
+        assert_eq!(delta.modified.len(), 1);
+        let diff = delta.modified[0].diff.as_deref().unwrap();
+        assert!(diff.starts_with("--- a/a.rs\n+++ b/a.rs\n@@ -17,7 +17,7 @@\n"));
+        assert!(diff.contains("-line 20\n+line twenty\n"));
+    }
+
+    #[test]
+    fn small_rewrites_are_sent_whole() {
+        let previous = bundle(&[("a.rs", "old\n")]);
+        let current = bundle(&[("a.rs", "new\n")]);
+        let delta = bundle_delta(&current, &previous, "s");
+        assert_eq!(delta.modified[0].diff, None);
+    }
+}
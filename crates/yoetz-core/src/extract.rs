@@ -0,0 +1,391 @@
+//! Text extraction for notebooks and document formats.
+//!
+//! Jupyter notebooks are bundled as their cells in order, with outputs
+//! truncated, instead of raw nbformat JSON; `.docx` and `.odt` files as their
+//! paragraph text; PDFs as their text layer. A file that fails to extract is
+//! bundled as though it had no extractor.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::Value;
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use crate::types::Extractor;
+
+/// Cap on the text kept from one notebook cell's outputs.
+const NOTEBOOK_OUTPUT_MAX_BYTES: usize = 2_000;
+/// Cap on an XML part decompressed from a `.docx`/`.odt` archive.
+const ARCHIVE_PART_MAX_BYTES: u64 = 64_000_000;
+
+const OUTPUT_TRUNCATED: &str = "... [output truncated]\n";
+
+/// Elements that carry text in a word-processing XML part.
+struct TextTags {
+    namespace: &'static str,
+    paragraphs: &'static [&'static str],
+    /// Element whose text nodes are document text; `None` when every text
+    /// node inside a paragraph is.
+    run: Option<&'static str>,
+    tab: &'static str,
+    line_breaks: &'static [&'static str],
+    /// Element standing for `c` spaces (ODF `text:s`).
+    spaces: Option<&'static str>,
+}
+
+const DOCX_TAGS: TextTags = TextTags {
+    namespace: "http://schemas.openxmlformats.org/wordprocessingml/2006/main",
+    paragraphs: &["p"],
+    run: Some("t"),
+    tab: "tab",
+    line_breaks: &["br", "cr"],
+    spaces: None,
+};
+
+const ODT_TAGS: TextTags = TextTags {
+    namespace: "urn:oasis:names:tc:opendocument:xmlns:text:1.0",
+    paragraphs: &["p", "h"],
+    run: None,
+    tab: "tab",
+    line_breaks: &["line-break"],
+    spaces: Some("s"),
+};
+
+/// Extractor for `path`, chosen by extension.
+pub fn extractor_for(path: &Path) -> Option<Extractor> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "ipynb" => Some(Extractor::Notebook),
+        "docx" => Some(Extractor::Docx),
+        "odt" => Some(Extractor::Odt),
+        "pdf" => Some(Extractor::Pdf),
+        _ => None,
+    }
+}
+
+/// Extract text from the complete contents of a file.
+pub fn extract(extractor: Extractor, data: &[u8]) -> Result<String> {
+    match extractor {
+        Extractor::Notebook => notebook_text(data),
+        Extractor::Docx => xml_text(&archive_part(data, "word/document.xml")?, &DOCX_TAGS),
+        Extractor::Odt => xml_text(&archive_part(data, "content.xml")?, &ODT_TAGS),
+        Extractor::Pdf => pdf_text(data),
+    }
+}
+
+/// Render a notebook in the `# %%` cell format: each cell's source, then the
+/// text of a code cell's outputs.
+fn notebook_text(data: &[u8]) -> Result<String> {
+    let notebook: Value = serde_json::from_slice(data).context("parse notebook JSON")?;
+    let cells = notebook
+        .get("cells")
+        .and_then(Value::as_array)
+        .ok_or_else(|| anyhow!("notebook has no cells array"))?;
+    let language = notebook
+        .pointer("/metadata/kernelspec/language")
+        .or_else(|| notebook.pointer("/metadata/language_info/name"))
+        .and_then(Value::as_str);
+
+    let mut out = String::new();
+    if let Some(language) = language {
+        out.push_str(&format!("# Notebook language: {language}\n\n"));
+    }
+    for (index, cell) in cells.iter().enumerate() {
+        let kind = cell
+            .get("cell_type")
+            .and_then(Value::as_str)
+            .unwrap_or("code");
+        out.push_str(&format!("# %% [{kind}] cell {}\n", index + 1));
+        push_block(&mut out, &multiline(cell.get("source")));
+        if kind == "code" {
+            let outputs = cell_outputs(cell);
+            if !outputs.is_empty() {
+                out.push_str("# Output:\n");
+                push_block(&mut out, &truncate_output(outputs));
+            }
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// nbformat stores text either as one string or as a list of lines.
+fn multiline(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(lines)) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+fn cell_outputs(cell: &Value) -> String {
+    let mut text = String::new();
+    let outputs = cell.get("outputs").and_then(Value::as_array);
+    for output in outputs.into_iter().flatten() {
+        match output.get("output_type").and_then(Value::as_str) {
+            Some("stream") => push_block(&mut text, &multiline(output.get("text"))),
+            Some("execute_result" | "display_data") => {
+                let data = output.get("data").and_then(Value::as_object);
+                if let Some(plain) = data.and_then(|data| data.get("text/plain")) {
+                    push_block(&mut text, &multiline(Some(plain)));
+                } else if let Some(mime) = data.and_then(|data| data.keys().next()) {
+                    text.push_str(&format!("[{mime} output omitted]\n"));
+                }
+            }
+            Some("error") => {
+                let field = |key: &str| output.get(key).and_then(Value::as_str).unwrap_or("");
+                text.push_str(&format!("{}: {}\n", field("ename"), field("evalue")));
+            }
+            _ => {}
+        }
+    }
+    text
+}
+
+fn truncate_output(mut text: String) -> String {
+    if text.len() <= NOTEBOOK_OUTPUT_MAX_BYTES {
+        return text;
+    }
+    let mut end = NOTEBOOK_OUTPUT_MAX_BYTES;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text.push_str(OUTPUT_TRUNCATED);
+    text
+}
+
+/// Append `text`, ending it with a newline.
+fn push_block(out: &mut String, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    out.push_str(text);
+    if !text.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+/// Read one member of a zip-based document, refusing oversized members.
+fn archive_part(data: &[u8], name: &str) -> Result<String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(data)).context("open document archive")?;
+    let part = archive
+        .by_name(name)
+        .with_context(|| format!("document archive has no {name}"))?;
+    let mut xml = String::new();
+    part.take(ARCHIVE_PART_MAX_BYTES + 1)
+        .read_to_string(&mut xml)
+        .with_context(|| format!("read {name}"))?;
+    if xml.len() as u64 > ARCHIVE_PART_MAX_BYTES {
+        bail!("{name} is larger than {ARCHIVE_PART_MAX_BYTES} bytes");
+    }
+    Ok(xml)
+}
+
+/// Paragraph text of a word-processing XML part, one paragraph per line.
+fn xml_text(xml: &str, tags: &TextTags) -> Result<String> {
+    let doc = roxmltree::Document::parse(xml).context("parse document XML")?;
+    let mut out = String::new();
+    push_xml_text(doc.root(), tags, false, &mut out);
+    let text = out.trim_end();
+    if text.is_empty() {
+        bail!("document has no text");
+    }
+    Ok(format!("{text}\n"))
+}
+
+fn push_xml_text(node: roxmltree::Node<'_, '_>, tags: &TextTags, in_text: bool, out: &mut String) {
+    if node.is_text() {
+        if in_text {
+            out.push_str(node.text().unwrap_or(""));
+        }
+        return;
+    }
+    let name = node.tag_name();
+    let local = if name.namespace() == Some(tags.namespace) {
+        name.name()
+    } else {
+        ""
+    };
+    if local == tags.tab {
+        out.push('\t');
+    } else if tags.line_breaks.contains(&local) {
+        out.push('\n');
+    } else if tags.spaces == Some(local) {
+        let count = node
+            .attribute((tags.namespace, "c"))
+            .and_then(|c| c.parse().ok())
+            .unwrap_or(1usize);
+        out.push_str(&" ".repeat(count));
+    } else if tags.paragraphs.contains(&local) {
+        for child in node.children() {
+            push_xml_text(child, tags, tags.run.is_none(), out);
+        }
+        out.push('\n');
+    } else {
+        let in_text = in_text || tags.run == Some(local);
+        for child in node.children() {
+            push_xml_text(child, tags, in_text, out);
+        }
+    }
+}
+
+fn pdf_text(data: &[u8]) -> Result<String> {
+    // pdf-extract panics on some malformed files; treat that as a failed
+    // extraction rather than a failed bundle.
+    let text = std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(data))
+        .map_err(|_| anyhow!("PDF text extraction panicked"))?
+        .context("extract PDF text")?;
+    let text = tidy_lines(&text);
+    if text.is_empty() {
+        bail!("PDF has no text layer");
+    }
+    Ok(text)
+}
+
+/// Trim trailing spaces and collapse runs of blank lines left by PDF layout.
+fn tidy_lines(text: &str) -> String {
+    let mut out = String::new();
+    let mut blank_run = 0;
+    for line in text.lines().map(str::trim_end) {
+        if line.is_empty() {
+            blank_run += 1;
+            if blank_run > 1 || out.is_empty() {
+                continue;
+            }
+        } else {
+            blank_run = 0;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    let trimmed = out.trim_end().len();
+    out.truncate(trimmed);
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract, extractor_for};
+    use crate::types::Extractor;
+    use std::io::{Cursor, Write};
+    use std::path::Path;
+
+    fn zip_with(name: &str, body: &str) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file(name, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(body.as_bytes()).unwrap();
+        writer.finish().unwrap().into_inner()
+    }
+
+    /// A one-page PDF showing `text` in Helvetica, with a correct xref table.
+    fn pdf_with(text: &str) -> Vec<u8> {
+        let stream = format!("BT /F1 12 Tf 72 712 Td ({text}) Tj ET");
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 4 0 R /Resources << /Font << /F1 5 0 R >> >> >>".to_string(),
+            format!("<< /Length {} >>\nstream\n{stream}\nendstream", stream.len()),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        ];
+        let mut pdf = String::from("%PDF-1.4\n");
+        let mut offsets = Vec::new();
+        for (index, body) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.push_str(&format!("{} 0 obj\n{body}\nendobj\n", index + 1));
+        }
+        let xref = pdf.len();
+        pdf.push_str(&format!(
+            "xref\n0 {}\n0000000000 65535 f \n",
+            objects.len() + 1
+        ));
+        for offset in offsets {
+            pdf.push_str(&format!("{offset:010} 00000 n \n"));
+        }
+        pdf.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        ));
+        pdf.into_bytes()
+    }
+
+    #[test]
+    fn extractor_is_chosen_by_extension() {
+        assert_eq!(
+            extractor_for(Path::new("a/Analysis.IPYNB")),
+            Some(Extractor::Notebook)
+        );
+        assert_eq!(extractor_for(Path::new("spec.pdf")), Some(Extractor::Pdf));
+        assert_eq!(extractor_for(Path::new("main.rs")), None);
+    }
+
+    #[test]
+    fn notebook_renders_cells_and_truncates_outputs() {
+        let notebook = serde_json::json!({
+            "metadata": {"kernelspec": {"language": "python"}},
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Load\n", "Read the data."]},
+                {
+                    "cell_type": "code",
+                    "source": "df = load()\ndf.head()",
+                    "outputs": [
+                        {"output_type": "stream", "text": ["x".repeat(3000)]},
+                        {"output_type": "display_data", "data": {"image/png": "iVBOR"}}
+                    ]
+                },
+                {
+                    "cell_type": "code",
+                    "source": "1/0",
+                    "outputs": [
+                        {"output_type": "display_data", "data": {"image/png": "iVBOR"}},
+                        {"output_type": "error", "ename": "ZeroDivisionError", "evalue": "division by zero"}
+                    ]
+                }
+            ]
+        });
+        let text = extract(Extractor::Notebook, notebook.to_string().as_bytes()).unwrap();
+        assert!(text.starts_with(
+            "# Notebook language: python\n\n# %% [markdown] cell 1\n# Load\nRead the data.\n\n# %% [code] cell 2\ndf = load()\ndf.head()\n# Output:\nxxx"
+        ));
+        assert!(text.contains("x\n... [output truncated]\n\n# %% [code] cell 3\n"));
+        assert!(text.ends_with(
+            "1/0\n# Output:\n[image/png output omitted]\nZeroDivisionError: division by zero\n\n"
+        ));
+    }
+
+    #[test]
+    fn docx_and_odt_yield_paragraph_text() {
+        let docx = zip_with(
+            "word/document.xml",
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body><w:p><w:r><w:t>Quarterly</w:t></w:r><w:r><w:tab/><w:t xml:space="preserve"> report</w:t></w:r></w:p><w:p><w:r><w:instrText>PAGE</w:instrText><w:t>Second</w:t><w:br/><w:t>line</w:t></w:r></w:p></w:body></w:document>"#,
+        );
+        assert_eq!(
+            extract(Extractor::Docx, &docx).unwrap(),
+            "Quarterly\t report\nSecond\nline\n"
+        );
+
+        let odt = zip_with(
+            "content.xml",
+            r#"<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0"><office:body><office:text><text:h>Title</text:h><text:p>a<text:s text:c="3"/>b <text:span>span</text:span></text:p></office:text></office:body></office:document-content>"#,
+        );
+        assert_eq!(
+            extract(Extractor::Odt, &odt).unwrap(),
+            "Title\na   b span\n"
+        );
+        assert!(extract(Extractor::Odt, b"not a zip").is_err());
+    }
+
+    #[test]
+    fn pdf_text_layer_is_extracted() {
+        let text = extract(Extractor::Pdf, &pdf_with("Hello from a PDF")).unwrap();
+        assert_eq!(text.trim(), "Hello from a PDF");
+        assert!(extract(Extractor::Pdf, b"%PDF-1.4 garbage").is_err());
+    }
+}
@@ -0,0 +1,284 @@
+//! Read-only git queries used when selecting and ranking bundle files.
+//!
+//! Ranking helpers degrade to an empty result when `git` is missing or the
+//! directory is not inside a work tree, so callers can treat git metadata as
+//! an optional signal. [`GitSelector`] queries are explicit user requests and
+//! report git failures as errors instead.
+
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Run `git -C <dir> <args>` and return stdout on success.
+fn git_output(dir: &Path, args: &[&str]) -> Option<Vec<u8>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output.status.success().then_some(output.stdout)
+}
+
+/// Run `git -C <dir> <args>`, turning a non-zero exit into an error that
+/// carries git's stderr.
+fn git_checked(dir: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .context("run git")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("git {} failed: {}", args.join(" "), stderr.trim()));
+    }
+    Ok(output.stdout)
+}
+
+/// Canonical top-level directory of the work tree containing `dir`.
+pub fn toplevel(dir: &Path) -> Option<PathBuf> {
+    let stdout = git_output(dir, &["rev-parse", "--show-toplevel"])?;
+    let text = String::from_utf8(stdout).ok()?;
+    let path = PathBuf::from(text.trim_end_matches(['\n', '\r']));
+    path.canonicalize().ok()
+}
+
+/// Most recent commit time (unix seconds) of every path touched by the last
+/// `max_commits` commits, keyed by absolute path under `toplevel`.
+pub fn last_commit_times(toplevel: &Path, max_commits: usize) -> HashMap<PathBuf, i64> {
+    let max_count = format!("--max-count={max_commits}");
+    let Some(stdout) = git_output(
+        toplevel,
+        &[
+            "log",
+            &max_count,
+            "--format=%x1e%ct",
+            "--name-only",
+            "--no-renames",
+            "-z",
+        ],
+    ) else {
+        return HashMap::new();
+    };
+    parse_log_times(&String::from_utf8_lossy(&stdout), toplevel)
+}
+
+fn parse_log_times(log: &str, toplevel: &Path) -> HashMap<PathBuf, i64> {
+    let mut times = HashMap::new();
+    // Each record is "\x1e<ct>\0\n" followed by NUL-separated names; log order
+    // is newest first, so the first time seen for a path is its latest.
+    for record in log.split('\u{1e}').filter(|r| !r.is_empty()) {
+        let (header, names) = record.split_once('\0').unwrap_or((record, ""));
+        let Ok(time) = header.trim().parse::<i64>() else {
+            continue;
+        };
+        for name in names.split('\0').map(|n| n.trim_matches('\n')) {
+            if !name.is_empty() {
+                times.entry(toplevel.join(name)).or_insert(time);
+            }
+        }
+    }
+    times
+}
+
+/// Absolute paths with uncommitted changes: staged, unstaged, or untracked.
+pub fn dirty_paths(toplevel: &Path) -> Vec<PathBuf> {
+    let Some(stdout) = git_output(
+        toplevel,
+        &["status", "--porcelain=v1", "-z", "--untracked-files=all"],
+    ) else {
+        return Vec::new();
+    };
+    parse_porcelain_paths(&String::from_utf8_lossy(&stdout), toplevel)
+}
+
+fn parse_porcelain_paths(status: &str, toplevel: &Path) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    let mut entries = status.split('\0').filter(|e| !e.is_empty());
+    while let Some(entry) = entries.next() {
+        if entry.len() < 4 {
+            continue;
+        }
+        let (code, path) = entry.split_at(3);
+        paths.push(toplevel.join(path));
+        // Renames and copies carry the original path as a separate entry.
+        if code.starts_with('R') || code.starts_with('C') {
+            entries.next();
+        }
+    }
+    paths
+}
+
+/// Git changes that select the files of a bundle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitSelector {
+    /// Changes since the merge base of `<rev>` and `HEAD`, including
+    /// uncommitted work — the "review my branch" view.
+    ChangedSince(String),
+    /// Changes staged in the index.
+    Staged,
+    /// Uncommitted changes to tracked files, plus untracked files. Before
+    /// the first commit every tracked file counts as changed.
+    Worktree,
+}
+
+impl GitSelector {
+    /// Absolute paths of files under `dir` that the selection touches.
+    /// Deleted files are left out since there is nothing to bundle.
+    pub fn changed_files(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let top = toplevel(dir)
+            .ok_or_else(|| anyhow!("{} is not inside a git work tree", dir.display()))?;
+        let mut args = vec!["diff", "--name-only", "-z", "--diff-filter=d"];
+        let base;
+        match self {
+            Self::ChangedSince(rev) => {
+                base = merge_base(dir, rev)?;
+                args.push(&base);
+            }
+            Self::Staged => args.push("--cached"),
+            Self::Worktree => {
+                base = worktree_base(dir)?;
+                args.push(&base);
+            }
+        }
+        args.extend(["--", "."]);
+        let mut names = split_nul(&git_checked(dir, &args)?);
+        if *self == Self::Worktree {
+            names.extend(split_nul(&git_checked(
+                dir,
+                &[
+                    "ls-files",
+                    "--others",
+                    "--exclude-standard",
+                    "--full-name",
+                    "-z",
+                    "--",
+                    ".",
+                ],
+            )?));
+        }
+        Ok(names.into_iter().map(|name| top.join(name)).collect())
+    }
+
+    /// Unified diff of the selection under `dir`. Untracked files have no
+    /// diff and only appear in the file list.
+    pub fn diff(&self, dir: &Path) -> Result<String> {
+        let mut args = vec!["diff", "--no-color", "--no-ext-diff"];
+        let base;
+        match self {
+            Self::ChangedSince(rev) => {
+                base = merge_base(dir, rev)?;
+                args.push(&base);
+            }
+            Self::Staged => args.push("--cached"),
+            Self::Worktree => {
+                base = worktree_base(dir)?;
+                args.push(&base);
+            }
+        }
+        args.extend(["--", "."]);
+        Ok(String::from_utf8_lossy(&git_checked(dir, &args)?).into_owned())
+    }
+}
+
+/// `HEAD`, or the empty tree in a repository without commits, where
+/// `git diff HEAD` would fail.
+fn worktree_base(dir: &Path) -> Result<String> {
+    if git_output(dir, &["rev-parse", "--verify", "--quiet", "HEAD"]).is_some() {
+        return Ok("HEAD".to_string());
+    }
+    // Hashing empty input as a tree gives the empty tree in either object
+    // format (SHA-1 or SHA-256).
+    let stdout = git_checked(dir, &["hash-object", "-t", "tree", "--stdin"])?;
+    Ok(String::from_utf8_lossy(&stdout).trim().to_string())
+}
+
+fn merge_base(dir: &Path, rev: &str) -> Result<String> {
+    if rev.starts_with('-') {
+        return Err(anyhow!("invalid git revision: {rev}"));
+    }
+    let stdout = git_checked(dir, &["merge-base", rev, "HEAD"])
+        .with_context(|| format!("resolve --changed-since {rev}"))?;
+    Ok(String::from_utf8_lossy(&stdout).trim().to_string())
+}
+
+fn split_nul(stdout: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(stdout)
+        .split('\0')
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_log_times, parse_porcelain_paths, toplevel, GitSelector};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn log_times_keep_newest_commit_per_path() {
+        let log = "\u{1e}200\0\na.rs\0b.rs\0\u{1e}100\0\nb.rs\0c.rs\0";
+        let times = parse_log_times(log, Path::new("/repo"));
+        assert_eq!(times.get(&PathBuf::from("/repo/a.rs")), Some(&200));
+        assert_eq!(times.get(&PathBuf::from("/repo/b.rs")), Some(&200));
+        assert_eq!(times.get(&PathBuf::from("/repo/c.rs")), Some(&100));
+    }
+
+    #[test]
+    fn porcelain_paths_skip_rename_sources() {
+        let status = " M src/lib.rs\0R  new.rs\0old.rs\0?? notes.md\0";
+        let paths = parse_porcelain_paths(status, Path::new("/repo"));
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("/repo/src/lib.rs"),
+                PathBuf::from("/repo/new.rs"),
+                PathBuf::from("/repo/notes.md"),
+            ]
+        );
+    }
+
+    #[test]
+    fn worktree_selection_works_before_the_first_commit() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("yoetz_git_unborn_{nanos}"));
+        fs::create_dir_all(&root).unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(&root)
+                .args(args)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {args:?}");
+        };
+        git(&["init", "-q"]);
+        fs::write(root.join("staged.txt"), "staged\n").unwrap();
+        fs::write(root.join("untracked.txt"), "untracked\n").unwrap();
+        git(&["add", "staged.txt"]);
+
+        let top = toplevel(&root).unwrap();
+        let mut files = GitSelector::Worktree.changed_files(&root).unwrap();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![top.join("staged.txt"), top.join("untracked.txt")]
+        );
+        let diff = GitSelector::Worktree.diff(&root).unwrap();
+        assert!(diff.contains("+staged"));
+        assert!(!diff.contains("untracked"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,398 @@
+//! Local import resolution for `--follow-imports`.
+//!
+//! Finds the files a source file depends on within the same project: Rust
+//! `mod` declarations and `use crate::`/`self::`/`super::` paths, and relative
+//! JS/TS `import`, `export … from`, and `require` specifiers. Resolution is
+//! purely lexical; macros, `#[path]` attributes, external crates, and bare
+//! package specifiers are ignored.
+
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Language {
+    Rust,
+    Script,
+}
+
+fn language_for(path: &Path) -> Option<Language> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "rs" => Some(Language::Rust),
+        "ts" | "tsx" | "mts" | "cts" | "js" | "jsx" | "mjs" | "cjs" => Some(Language::Script),
+        _ => None,
+    }
+}
+
+/// Whether [`local_imports`] understands the language of `path`.
+pub fn is_supported(path: &Path) -> bool {
+    language_for(path).is_some()
+}
+
+/// Existing files imported by `text`, the source of `path`, in first-seen
+/// order. `path` should be absolute so relative imports resolve against it.
+pub fn local_imports(path: &Path, text: &str) -> Vec<PathBuf> {
+    let found = match language_for(path) {
+        Some(Language::Rust) => rust_imports(path, text),
+        Some(Language::Script) => script_imports(path, text),
+        None => Vec::new(),
+    };
+    let mut unique = Vec::new();
+    for file in found {
+        if file != path && !unique.contains(&file) {
+            unique.push(file);
+        }
+    }
+    unique
+}
+
+fn rust_mod_decl() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?m)^[ \t]*(?:pub(?:\([^)]*\))?[ \t]+)?mod[ \t]+([A-Za-z_][A-Za-z0-9_]*)[ \t]*;",
+        )
+        .expect("mod regex compiles")
+    })
+}
+
+fn rust_use_decl() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?m)^[ \t]*(?:pub(?:\([^)]*\))?[ \t]+)?use[ \t]+([^;]+);")
+            .expect("use regex compiles")
+    })
+}
+
+fn rust_imports(path: &Path, text: &str) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let child_dir = rust_child_dir(path);
+    let mut declared = Vec::new();
+    for caps in rust_mod_decl().captures_iter(text) {
+        let name = &caps[1];
+        declared.push(name.to_string());
+        if let Some(file) = child_dir
+            .as_deref()
+            .and_then(|dir| module_file(dir, &[name]))
+        {
+            found.push(file);
+        }
+    }
+
+    let crate_src = crate_src_dir(path);
+    let module = crate_src.as_deref().and_then(|src| module_path(src, path));
+    for caps in rust_use_decl().captures_iter(text) {
+        for segments in expand_use_tree(&caps[1]) {
+            let Some((first, rest)) = segments.split_first() else {
+                continue;
+            };
+            // 2018-style `use child::item` for a `mod child;` declared here.
+            if declared.contains(first) {
+                if let Some(file) = child_dir
+                    .as_deref()
+                    .and_then(|dir| longest_module_file(dir, &segments, None))
+                {
+                    found.push(file);
+                }
+                continue;
+            }
+            let (Some(src), Some(module)) = (crate_src.as_deref(), module.as_ref()) else {
+                continue;
+            };
+            let absolute: Vec<String> = match first.as_str() {
+                "crate" => rest.to_vec(),
+                "self" => module.iter().chain(rest).cloned().collect(),
+                "super" => {
+                    let supers = segments.iter().take_while(|s| *s == "super").count();
+                    let mut base = module.clone();
+                    base.truncate(base.len().saturating_sub(supers));
+                    base.extend(segments[supers..].iter().cloned());
+                    base
+                }
+                _ => continue,
+            };
+            if let Some(file) = longest_module_file(src, &absolute, crate_root_file(src)) {
+                found.push(file);
+            }
+        }
+    }
+    found
+}
+
+/// Directory holding the submodules declared by `path`: its own directory for
+/// `lib.rs`/`main.rs`/`mod.rs`, else a directory named after the file stem.
+fn rust_child_dir(path: &Path) -> Option<PathBuf> {
+    let dir = path.parent()?;
+    let name = path.file_name()?.to_str()?;
+    if matches!(name, "lib.rs" | "main.rs" | "mod.rs") {
+        Some(dir.to_path_buf())
+    } else {
+        Some(dir.join(path.file_stem()?))
+    }
+}
+
+/// `src/` of the nearest enclosing Cargo package, when `path` lives under it.
+fn crate_src_dir(path: &Path) -> Option<PathBuf> {
+    let package = path
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.join("Cargo.toml").is_file())?;
+    let src = package.join("src");
+    path.starts_with(&src).then_some(src)
+}
+
+fn crate_root_file(src: &Path) -> Option<PathBuf> {
+    ["lib.rs", "main.rs"]
+        .iter()
+        .map(|name| src.join(name))
+        .find(|file| file.is_file())
+}
+
+/// Module path of `path` relative to the crate's `src/` directory.
+fn module_path(src: &Path, path: &Path) -> Option<Vec<String>> {
+    let rel = path.strip_prefix(src).ok()?;
+    let mut segments: Vec<String> = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let file = segments.pop()?;
+    match file.as_str() {
+        "lib.rs" | "main.rs" if segments.is_empty() => {}
+        "mod.rs" => {}
+        _ => segments.push(file.strip_suffix(".rs")?.to_string()),
+    }
+    Some(segments)
+}
+
+/// `dir/a/b.rs` or `dir/a/b/mod.rs` for the module path `[a, b]`.
+fn module_file<S: AsRef<str>>(dir: &Path, segments: &[S]) -> Option<PathBuf> {
+    let (last, parents) = segments.split_last()?;
+    let mut base = dir.to_path_buf();
+    for segment in parents {
+        base.push(segment.as_ref());
+    }
+    let flat = base.join(format!("{}.rs", last.as_ref()));
+    if flat.is_file() {
+        return Some(flat);
+    }
+    let nested = base.join(last.as_ref()).join("mod.rs");
+    nested.is_file().then_some(nested)
+}
+
+/// File of the longest module-path prefix of `segments` under `dir`; the
+/// rest of the path names items inside it. Falls back to `root` when no
+/// prefix is a module file.
+fn longest_module_file(dir: &Path, segments: &[String], root: Option<PathBuf>) -> Option<PathBuf> {
+    (1..=segments.len())
+        .rev()
+        .find_map(|len| module_file(dir, &segments[..len]))
+        .or(root)
+}
+
+/// Flatten a `use` tree such as `crate::{a::{b, c as d}, e::*}` into paths:
+/// `[crate, a, b]`, `[crate, a, c]`, `[crate, e]`.
+fn expand_use_tree(tree: &str) -> Vec<Vec<String>> {
+    let tree: String = tree.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut out = Vec::new();
+    expand_into(&tree, &mut Vec::new(), &mut out);
+    out
+}
+
+fn expand_into(tree: &str, prefix: &mut Vec<String>, out: &mut Vec<Vec<String>>) {
+    let tree = tree.trim();
+    let tree = tree.strip_prefix("::").unwrap_or(tree);
+    let Some(open) = tree.find('{') else {
+        let path = tree.split(" as ").next().unwrap_or(tree);
+        let mut segments = prefix.clone();
+        segments.extend(
+            path.split("::")
+                .map(str::trim)
+                .filter(|s| !s.is_empty() && *s != "*")
+                .map(str::to_string),
+        );
+        if segments.last().is_some_and(|s| s == "self") {
+            segments.pop();
+        }
+        if !segments.is_empty() {
+            out.push(segments);
+        }
+        return;
+    };
+    let Some(close) = tree.rfind('}') else {
+        return;
+    };
+    let depth = prefix.len();
+    prefix.extend(
+        tree[..open]
+            .split("::")
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string),
+    );
+    for item in split_top_level(&tree[open + 1..close]) {
+        expand_into(item, prefix, out);
+    }
+    prefix.truncate(depth);
+}
+
+/// Split on commas that are not nested inside braces.
+fn split_top_level(list: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (idx, ch) in list.char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                items.push(&list[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&list[start..]);
+    items.retain(|item| !item.trim().is_empty());
+    items
+}
+
+fn script_specifiers() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r#"(?:\bfrom|\bimport|\brequire[ \t]*\(|\bimport[ \t]*\()[ \t\r\n]*['"](\.{1,2}/[^'"\r\n]*)['"]"#,
+        )
+        .expect("import regex compiles")
+    })
+}
+
+const SCRIPT_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs", "mts", "cts"];
+
+fn script_imports(path: &Path, text: &str) -> Vec<PathBuf> {
+    let Some(dir) = path.parent() else {
+        return Vec::new();
+    };
+    script_specifiers()
+        .captures_iter(text)
+        .filter_map(|caps| resolve_script(&dir.join(&caps[1])))
+        .collect()
+}
+
+/// Resolve a relative specifier the way bundlers do: the exact file, a
+/// TypeScript source behind a `.js` specifier, an added extension, or an
+/// `index` file in the directory.
+fn resolve_script(base: &Path) -> Option<PathBuf> {
+    if base.is_file() {
+        return Some(base.to_path_buf());
+    }
+    let ext = base.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let ts_sources: &[&str] = match ext {
+        "js" => &["ts", "tsx"],
+        "jsx" => &["tsx"],
+        "mjs" => &["mts"],
+        "cjs" => &["cts"],
+        _ => &[],
+    };
+    let swapped = ts_sources
+        .iter()
+        .map(|ts| base.with_extension(ts))
+        .find(|file| file.is_file());
+    if swapped.is_some() {
+        return swapped;
+    }
+    let name = base.file_name()?.to_str()?;
+    SCRIPT_EXTENSIONS
+        .iter()
+        .map(|ext| base.with_file_name(format!("{name}.{ext}")))
+        .chain(
+            SCRIPT_EXTENSIONS
+                .iter()
+                .map(|ext| base.join(format!("index.{ext}"))),
+        )
+        .find(|file| file.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_use_tree, local_imports};
+    use std::fs;
+    use std::path::Path;
+
+    fn write(root: &Path, rel: &str, text: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+
+    #[test]
+    fn use_trees_flatten_groups_renames_and_globs() {
+        let paths = expand_use_tree("crate::{a::{b, c as d}, e::*, self}");
+        assert_eq!(
+            paths,
+            vec![
+                vec!["crate", "a", "b"],
+                vec!["crate", "a", "c"],
+                vec!["crate", "e"],
+                vec!["crate"],
+            ]
+        );
+    }
+
+    #[test]
+    fn rust_imports_follow_mods_and_crate_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        write(&root, "Cargo.toml", "[package]\n");
+        write(&root, "src/main.rs", "mod commands;\nmod util;\n");
+        write(&root, "src/commands/mod.rs", "pub(crate) mod ask;\n");
+        write(
+            &root,
+            "src/commands/ask.rs",
+            "use crate::util::{helper, Thing};\nuse super::review;\nuse crate::{\n    AppContext,\n};\nuse std::fmt;\n",
+        );
+        write(&root, "src/commands/review.rs", "");
+        write(&root, "src/util.rs", "");
+
+        let main = root.join("src/main.rs");
+        assert_eq!(
+            local_imports(&main, &fs::read_to_string(&main).unwrap()),
+            vec![root.join("src/commands/mod.rs"), root.join("src/util.rs")]
+        );
+        let ask = root.join("src/commands/ask.rs");
+        assert_eq!(
+            local_imports(&ask, &fs::read_to_string(&ask).unwrap()),
+            vec![
+                root.join("src/util.rs"),
+                root.join("src/commands/review.rs"),
+                root.join("src/main.rs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn script_imports_resolve_relative_specifiers() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        write(
+            &root,
+            "src/index.ts",
+            "import { a } from './a.js';\nimport type {\n  B,\n} from \"./b\";\nimport './side';\nconst c = require('../lib');\nimport x from 'react';\n",
+        );
+        write(&root, "src/a.ts", "");
+        write(&root, "src/b.tsx", "");
+        write(&root, "src/side.js", "");
+        write(&root, "lib/index.js", "");
+
+        let index = root.join("src/index.ts");
+        assert_eq!(
+            local_imports(&index, &fs::read_to_string(&index).unwrap()),
+            vec![
+                root.join("src/a.ts"),
+                root.join("src/b.tsx"),
+                root.join("src/side.js"),
+                root.join("src/../lib/index.js"),
+            ]
+        );
+    }
+}
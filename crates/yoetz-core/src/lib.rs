@@ -2,9 +2,17 @@
 
 pub mod bundle;
 pub mod config;
+pub mod delta;
+pub mod extract;
+pub mod git;
+pub mod imports;
 pub mod media;
+pub mod outline;
 pub mod output;
 pub mod paths;
+pub mod redact;
 pub mod registry;
+pub mod render;
 pub mod session;
+pub mod tokenizer;
 pub mod types;
@@ -0,0 +1,438 @@
+//! Signatures-only skeletons of source files.
+//!
+//! An outline keeps item declarations, `impl`/`class` headers, function
+//! signatures, attributes, and doc comments while eliding function bodies, so
+//! a large module can be shown whole within a fraction of its size.
+
+use std::path::Path;
+
+/// Source languages with outline support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Language {
+    /// Brace-delimited languages: Rust, TypeScript/JavaScript, Go.
+    Braces(BraceFlavor),
+    Python,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BraceFlavor {
+    Rust,
+    Script,
+    Go,
+}
+
+fn language_for(path: &Path) -> Option<Language> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "rs" => Some(Language::Braces(BraceFlavor::Rust)),
+        "ts" | "tsx" | "mts" | "cts" | "js" | "jsx" | "mjs" | "cjs" => {
+            Some(Language::Braces(BraceFlavor::Script))
+        }
+        "go" => Some(Language::Braces(BraceFlavor::Go)),
+        "py" | "pyi" => Some(Language::Python),
+        _ => None,
+    }
+}
+
+/// Whether [`outline`] understands the language of `path`.
+pub fn is_supported(path: &Path) -> bool {
+    language_for(path).is_some()
+}
+
+/// Build a signatures-only skeleton of `text`, or `None` when the language of
+/// `path` is not supported.
+pub fn outline(text: &str, path: &Path) -> Option<String> {
+    match language_for(path)? {
+        Language::Braces(flavor) => Some(outline_braces(text, flavor)),
+        Language::Python => Some(outline_python(text)),
+    }
+}
+
+/// Marker that replaces an elided body.
+const ELIDED: &str = "{ ... }";
+
+/// Keywords that open a block whose contents are themselves declarations.
+fn opens_container(line: &str, flavor: BraceFlavor) -> bool {
+    let code = strip_leading_modifiers(line.trim_start(), flavor);
+    let keywords: &[&str] = match flavor {
+        BraceFlavor::Rust => &[
+            "impl",
+            "trait",
+            "mod ",
+            "struct ",
+            "enum ",
+            "union ",
+            "extern ",
+            "macro_rules!",
+            "use ",
+        ],
+        BraceFlavor::Script => &[
+            "class ",
+            "interface ",
+            "namespace ",
+            "module ",
+            "enum ",
+            "declare ",
+            "abstract class ",
+        ],
+        BraceFlavor::Go => &["type ", "import", "const", "var"],
+    };
+    keywords.iter().any(|k| code.starts_with(k))
+}
+
+fn strip_leading_modifiers(mut code: &str, flavor: BraceFlavor) -> &str {
+    let modifiers: &[&str] = match flavor {
+        BraceFlavor::Rust => &["pub(crate) ", "pub(super) ", "pub ", "unsafe ", "default "],
+        BraceFlavor::Script => &["export default ", "export ", "declare "],
+        BraceFlavor::Go => &[],
+    };
+    loop {
+        let before = code;
+        for modifier in modifiers {
+            if let Some(rest) = code.strip_prefix(modifier) {
+                code = rest.trim_start();
+            }
+        }
+        if code == before {
+            return code;
+        }
+    }
+}
+
+/// Lexer state carried across lines so braces in strings and comments are ignored.
+#[derive(Default)]
+struct Scanner {
+    in_block_comment: bool,
+    in_string: Option<char>,
+}
+
+impl Scanner {
+    /// Byte offsets and deltas (+1 open, -1 close) of structural braces in `line`.
+    fn braces(&mut self, line: &str, flavor: BraceFlavor) -> Vec<(usize, i32)> {
+        let mut found = Vec::new();
+        let bytes = line.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            let c = bytes[i] as char;
+            if self.in_block_comment {
+                if bytes[i..].starts_with(b"*/") {
+                    self.in_block_comment = false;
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+                continue;
+            }
+            if let Some(quote) = self.in_string {
+                if c == '\\' {
+                    i += 2;
+                    continue;
+                }
+                if c == quote {
+                    self.in_string = None;
+                }
+                i += 1;
+                continue;
+            }
+            match c {
+                '/' if bytes[i..].starts_with(b"//") => break,
+                '/' if bytes[i..].starts_with(b"/*") => {
+                    self.in_block_comment = true;
+                    i += 2;
+                    continue;
+                }
+                '"' => self.in_string = Some('"'),
+                '`' if flavor != BraceFlavor::Rust => self.in_string = Some('`'),
+                '\'' => {
+                    if let Some(len) = char_literal_len(&line[i..], flavor) {
+                        i += len;
+                        continue;
+                    }
+                }
+                '{' => found.push((i, 1)),
+                '}' => found.push((i, -1)),
+                _ => {}
+            }
+            i += 1;
+        }
+        // Plain strings cannot span lines in JS/Go; recover from a stray quote.
+        if flavor != BraceFlavor::Rust && self.in_string == Some('"') {
+            self.in_string = None;
+        }
+        found
+    }
+}
+
+/// Length of a quoted literal starting at `'`, or `None` for a Rust lifetime.
+fn char_literal_len(rest: &str, flavor: BraceFlavor) -> Option<usize> {
+    let mut chars = rest.char_indices().skip(1);
+    if flavor == BraceFlavor::Rust {
+        let (_, first) = chars.next()?;
+        if first == '\\' {
+            return rest[2..].find('\'').map(|end| end + 3);
+        }
+        let (idx, second) = chars.next()?;
+        return (second == '\'').then_some(idx + 1);
+    }
+    let mut escaped = false;
+    for (idx, ch) in chars {
+        if escaped {
+            escaped = false;
+        } else if ch == '\\' {
+            escaped = true;
+        } else if ch == '\'' {
+            return Some(idx + 1);
+        }
+    }
+    Some(rest.len())
+}
+
+fn outline_braces(text: &str, flavor: BraceFlavor) -> String {
+    let mut out = String::new();
+    let mut scanner = Scanner::default();
+    // One entry per open brace: `true` for declaration containers.
+    let mut stack: Vec<bool> = Vec::new();
+    // Depth at which an elided body started; lines are skipped until it closes.
+    let mut skip_until: Option<usize> = None;
+    // A Rust container header still waiting for its `{`, as after a
+    // multi-line `where` clause.
+    let mut pending_container = false;
+
+    for line in text.lines() {
+        let braces = scanner.braces(line, flavor);
+        if let Some(depth) = skip_until {
+            track_braces(&mut stack, &braces);
+            if stack.len() <= depth {
+                skip_until = None;
+            }
+            continue;
+        }
+
+        let container = pending_container || opens_container(line, flavor);
+        pending_container = container
+            && flavor == BraceFlavor::Rust
+            && !braces.iter().any(|&(_, delta)| delta > 0)
+            && !line.trim_end().ends_with(';');
+        let mut first_open = true;
+        let mut elided = false;
+        for (idx, &(offset, delta)) in braces.iter().enumerate() {
+            if delta < 0 {
+                stack.pop();
+                continue;
+            }
+            let is_container = container && first_open;
+            first_open = false;
+            if is_container {
+                stack.push(true);
+                continue;
+            }
+            let depth = stack.len();
+            stack.push(false);
+            if closes_on_same_line(&braces[idx + 1..]) {
+                continue;
+            }
+            // A body opens and runs past this line: keep the signature only.
+            out.push_str(line[..offset].trim_end());
+            out.push(' ');
+            out.push_str(ELIDED);
+            out.push('\n');
+            track_braces(&mut stack, &braces[idx + 1..]);
+            if stack.len() > depth {
+                skip_until = Some(depth);
+            }
+            elided = true;
+            break;
+        }
+        if !elided {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn track_braces(stack: &mut Vec<bool>, braces: &[(usize, i32)]) {
+    for &(_, delta) in braces {
+        if delta > 0 {
+            stack.push(false);
+        } else {
+            stack.pop();
+        }
+    }
+}
+
+/// Whether a just-opened brace is closed by the braces that follow it.
+fn closes_on_same_line(rest: &[(usize, i32)]) -> bool {
+    let mut open = 1;
+    rest.iter().any(|&(_, delta)| {
+        open += delta;
+        open == 0
+    })
+}
+
+fn outline_python(text: &str) -> String {
+    let mut out = String::new();
+    // Indent of the `def` whose body is being skipped.
+    let mut skip_indent: Option<usize> = None;
+    // A multi-line `def` signature still being emitted: (indent, open brackets).
+    let mut pending_def: Option<(usize, i32)> = None;
+    // Keep one blank line between an elided body and the next item.
+    let mut trailing_blank = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if let Some(body_indent) = skip_indent {
+            if trimmed.is_empty() || indent > body_indent {
+                trailing_blank = trimmed.is_empty();
+                continue;
+            }
+            skip_indent = None;
+            if trailing_blank {
+                out.push('\n');
+            }
+        }
+        let def_state = match pending_def.take() {
+            Some((def_indent, depth)) => Some((def_indent, depth + bracket_delta(trimmed))),
+            None if trimmed.starts_with("def ") || trimmed.starts_with("async def ") => {
+                Some((indent, bracket_delta(trimmed)))
+            }
+            None => None,
+        };
+        out.push_str(line);
+        out.push('\n');
+        let Some((def_indent, depth)) = def_state else {
+            continue;
+        };
+        if depth > 0 {
+            pending_def = Some((def_indent, depth));
+        } else if trimmed.trim_end().ends_with(':') {
+            out.push_str(&" ".repeat(def_indent + 4));
+            out.push_str("...\n");
+            skip_indent = Some(def_indent);
+        }
+    }
+    out
+}
+
+fn bracket_delta(line: &str) -> i32 {
+    line.chars()
+        .map(|c| match c {
+            '(' | '[' | '{' => 1,
+            ')' | ']' | '}' => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::outline;
+    use std::path::Path;
+
+    #[test]
+    fn rust_outline_keeps_signatures_and_elides_bodies() {
+        let source = r#"//! Module docs.
+use std::fmt;
+
+/// A point.
+#[derive(Debug)]
+pub struct Point {
+    pub x: i32,
+}
+
+impl Point {
+    /// Build one.
+    pub fn new(x: i32) -> Self {
+        let s = "{ not a brace";
+        Self { x }
+    }
+
+    fn lifetime<'a>(&self, v: &'a str) -> &'a str { v }
+}
+
+fn helper(
+    a: u8,
+) -> u8 {
+    if a > 0 {
+        a
+    } else {
+        0
+    }
+}
+"#;
+        let out = outline(source, Path::new("src/lib.rs")).unwrap();
+        assert!(out.contains("//! Module docs."));
+        assert!(out.contains("pub struct Point {\n    pub x: i32,\n}"));
+        assert!(out.contains("impl Point {"));
+        assert!(out.contains("    /// Build one.\n    pub fn new(x: i32) -> Self { ... }"));
+        assert!(out.contains("fn lifetime<'a>(&self, v: &'a str) -> &'a str { v }"));
+        assert!(out.contains("    a: u8,\n) -> u8 { ... }"));
+        assert!(!out.contains("not a brace"));
+        assert!(!out.contains("else"));
+    }
+
+    #[test]
+    fn rust_outline_keeps_containers_opened_after_a_where_clause() {
+        let source = "impl<K, V> Cache<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.map.get(key).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+}
+";
+        let out = outline(source, Path::new("cache.rs")).unwrap();
+        assert_eq!(
+            out,
+            "impl<K, V> Cache<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    pub fn get(&self, key: &K) -> Option<V> { ... }
+
+    pub fn len(&self) -> usize { ... }
+}
+"
+        );
+    }
+
+    #[test]
+    fn typescript_outline_keeps_class_members() {
+        let source = "export class Store {\n  private items: string[] = [];\n  add(item: string): void {\n    this.items.push(`${item}}`);\n  }\n}\nexport function load(): Store {\n  return new Store();\n}\n";
+        let out = outline(source, Path::new("store.ts")).unwrap();
+        assert_eq!(
+            out,
+            "export class Store {\n  private items: string[] = [];\n  add(item: string): void { ... }\n}\nexport function load(): Store { ... }\n"
+        );
+    }
+
+    #[test]
+    fn python_outline_elides_function_bodies() {
+        let source = "import os\n\nclass Repo:\n    @property\n    def name(self):\n        return os.getcwd()\n\ndef main(\n    argv,\n):\n    print(argv)\n";
+        let out = outline(source, Path::new("tool.py")).unwrap();
+        assert_eq!(
+            out,
+            "import os\n\nclass Repo:\n    @property\n    def name(self):\n        ...\n\ndef main(\n    argv,\n):\n    ...\n"
+        );
+    }
+
+    #[test]
+    fn outline_handles_multibyte_text_in_comments_and_strings() {
+        let source = "/* café */\nfn greet() -> &'static str {\n    \"héllo {\"\n}\n";
+        let out = outline(source, Path::new("greet.rs")).unwrap();
+        assert_eq!(out, "/* café */\nfn greet() -> &'static str { ... }\n");
+    }
+
+    #[test]
+    fn unsupported_language_has_no_outline() {
+        assert!(outline("body", Path::new("notes.md")).is_none());
+    }
+}
@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 
+use crate::types::Usage;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ModelTier {
@@ -91,6 +93,14 @@ pub struct ModelPricing {
     pub prompt_per_1k: Option<f64>,
     pub completion_per_1k: Option<f64>,
     pub request: Option<f64>,
+    /// Rate for input tokens read from the prompt cache; `prompt_per_1k`
+    /// when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_per_1k: Option<f64>,
+    /// Rate for input tokens written to the prompt cache; `prompt_per_1k`
+    /// when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_per_1k: Option<f64>,
 }
 
 impl ModelPricing {
@@ -105,6 +115,22 @@ impl ModelPricing {
         Some(prompt_cost + completion_cost + request_cost)
     }
 
+    /// Cost of a finished call from its reported usage, pricing cached input
+    /// at the cached rates. `None` without input and output counts.
+    pub fn cost(&self, usage: &Usage) -> Option<f64> {
+        let input = usage.input_tokens?;
+        let output = usage.output_tokens?;
+        let read = usage.cache_read_tokens.unwrap_or(0).min(input);
+        let write = usage.cache_write_tokens.unwrap_or(0).min(input - read);
+        let prompt = self.prompt_per_1k?;
+        let per_1k = |tokens: u64, rate: f64| rate * tokens as f64 / 1000.0;
+        let input_cost = per_1k(input - read - write, prompt)
+            + per_1k(read, self.cache_read_per_1k.unwrap_or(prompt))
+            + per_1k(write, self.cache_write_per_1k.unwrap_or(prompt));
+        let output_cost = per_1k(output, self.completion_per_1k?);
+        Some(input_cost + output_cost + self.request.unwrap_or(0.0))
+    }
+
     fn merge_from(&mut self, other: ModelPricing) {
         if other.prompt_per_1k.is_some() {
             self.prompt_per_1k = other.prompt_per_1k;
@@ -115,6 +141,12 @@ impl ModelPricing {
         if other.request.is_some() {
             self.request = other.request;
         }
+        if other.cache_read_per_1k.is_some() {
+            self.cache_read_per_1k = other.cache_read_per_1k;
+        }
+        if other.cache_write_per_1k.is_some() {
+            self.cache_write_per_1k = other.cache_write_per_1k;
+        }
     }
 }
 
@@ -814,6 +846,7 @@ mod tests {
                     prompt_per_1k: Some(0.01),
                     completion_per_1k: Some(0.02),
                     request: None,
+                    ..Default::default()
                 },
                 provider: Some("openrouter".to_string()),
                 capability: Some(ModelCapability {
@@ -838,6 +871,7 @@ mod tests {
                     prompt_per_1k: None,
                     completion_per_1k: None,
                     request: Some(0.1),
+                    ..Default::default()
                 },
                 provider: None,
                 capability: Some(ModelCapability {
@@ -869,6 +903,35 @@ mod tests {
         assert_eq!(capability.web_search, Some(false));
     }
 
+    #[test]
+    fn cost_prices_cached_input_at_cached_rates() {
+        let pricing = ModelPricing {
+            prompt_per_1k: Some(3.0),
+            completion_per_1k: Some(15.0),
+            cache_read_per_1k: Some(0.3),
+            cache_write_per_1k: Some(3.75),
+            ..Default::default()
+        };
+        let usage = Usage {
+            input_tokens: Some(10_000),
+            output_tokens: Some(1_000),
+            cache_read_tokens: Some(6_000),
+            cache_write_tokens: Some(2_000),
+            ..Usage::default()
+        };
+        // 2k uncached + 6k read + 2k written, then 1k output.
+        let cost = pricing.cost(&usage).unwrap();
+        assert!((cost - (6.0 + 1.8 + 7.5 + 15.0)).abs() < 1e-9);
+
+        let no_cache_rates = ModelPricing {
+            cache_read_per_1k: None,
+            cache_write_per_1k: None,
+            ..pricing.clone()
+        };
+        assert!((no_cache_rates.cost(&usage).unwrap() - 45.0).abs() < 1e-9);
+        assert!(pricing.cost(&Usage::default()).is_none());
+    }
+
     #[test]
     fn prune_provider_removes_only_that_provider_outside_keep_set() {
         let mut reg = ModelRegistry {
@@ -260,7 +260,8 @@ pub fn prune_sessions_in(
     Ok(removed)
 }
 
-fn yoetz_root_dir() -> PathBuf {
+/// Yoetz state directory: `$YOETZ_DIR`, else `~/.yoetz`.
+pub fn yoetz_root_dir() -> PathBuf {
     if let Ok(dir) = env::var("YOETZ_DIR") {
         return PathBuf::from(dir);
     }
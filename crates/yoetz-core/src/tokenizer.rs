@@ -0,0 +1,172 @@
+//! Token counting for context budgets and pricing estimates.
+//!
+//! Counts use embedded BPE vocabularies: OpenAI models get their own encoding,
+//! and other known families get `o200k_base` as the closest available
+//! approximation. The ~4 chars/token heuristic is only used for model ids
+//! from unrecognized families.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tiktoken_rs::CoreBPE;
+
+/// Tokenizer used to estimate how many tokens a text will cost.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Tokenizer {
+    /// GPT-4o / GPT-4.1 / GPT-5 / o-series encoding.
+    #[default]
+    O200kBase,
+    /// GPT-4 / GPT-3.5 encoding.
+    Cl100kBase,
+    /// ~4 characters per token.
+    Heuristic,
+}
+
+/// Model families served exactly by `o200k_base`.
+const O200K_FAMILIES: &[&str] = &[
+    "gpt-4o", "gpt-4.1", "gpt-4.5", "gpt-5", "gpt-oss", "chatgpt", "codex", "o1", "o3", "o4",
+];
+/// Model families served exactly by `cl100k_base`.
+const CL100K_FAMILIES: &[&str] = &["gpt-4", "gpt-3.5", "text-embedding"];
+/// Non-OpenAI families whose private vocabularies are approximated by `o200k_base`.
+const APPROXIMATED_FAMILIES: &[&str] = &[
+    "claude",
+    "gemini",
+    "gemma",
+    "grok",
+    "llama",
+    "mistral",
+    "mixtral",
+    "codestral",
+    "devstral",
+    "magistral",
+    "deepseek",
+    "qwen",
+    "kimi",
+    "glm",
+    "command",
+    "composer",
+    "sonar",
+];
+
+impl Tokenizer {
+    /// Pick a tokenizer from a model id such as `openai/gpt-5.2` or `claude-sonnet-4-5`.
+    ///
+    /// `None` (no model selected yet) uses the default encoding; ids from
+    /// unrecognized families fall back to the heuristic.
+    pub fn for_model(model_id: Option<&str>) -> Self {
+        let Some(model_id) = model_id else {
+            return Self::default();
+        };
+        let name = model_id
+            .rsplit('/')
+            .next()
+            .unwrap_or(model_id)
+            .to_ascii_lowercase();
+        let has_family = |families: &[&str]| families.iter().any(|f| name.starts_with(f));
+        // o200k families must be checked first: "gpt-4o" also matches "gpt-4".
+        if has_family(O200K_FAMILIES) {
+            Self::O200kBase
+        } else if has_family(CL100K_FAMILIES) {
+            Self::Cl100kBase
+        } else if has_family(APPROXIMATED_FAMILIES) {
+            Self::O200kBase
+        } else {
+            Self::Heuristic
+        }
+    }
+
+    /// Stable name used in reports (`o200k_base`, `cl100k_base`, `heuristic`).
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::O200kBase => "o200k_base",
+            Self::Cl100kBase => "cl100k_base",
+            Self::Heuristic => "heuristic",
+        }
+    }
+
+    /// Number of tokens `text` encodes to. Special-token markers are counted
+    /// as ordinary text, since bundled files are never trusted control input.
+    pub fn count(self, text: &str) -> usize {
+        if text.is_empty() {
+            return 0;
+        }
+        match self.bpe() {
+            Some(bpe) => bpe.encode_ordinary(text).len(),
+            None => estimate_tokens(text.chars().count()),
+        }
+    }
+
+    fn bpe(self) -> Option<&'static CoreBPE> {
+        match self {
+            Self::O200kBase => Some(tiktoken_rs::o200k_base_singleton()),
+            Self::Cl100kBase => Some(tiktoken_rs::cl100k_base_singleton()),
+            Self::Heuristic => None,
+        }
+    }
+}
+
+impl fmt::Display for Tokenizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Count tokens in `text` with the tokenizer for `model_id`.
+pub fn count_tokens(model_id: Option<&str>, text: &str) -> usize {
+    Tokenizer::for_model(model_id).count(text)
+}
+
+/// Rough token count estimate (~4 chars per token).
+pub fn estimate_tokens(chars: usize) -> usize {
+    // Rough heuristic: 4 chars per token.
+    chars.div_ceil(4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count_tokens, Tokenizer};
+
+    #[test]
+    fn for_model_maps_families() {
+        assert_eq!(
+            Tokenizer::for_model(Some("openai/gpt-5.2")),
+            Tokenizer::O200kBase
+        );
+        assert_eq!(
+            Tokenizer::for_model(Some("gpt-4o-mini")),
+            Tokenizer::O200kBase
+        );
+        assert_eq!(
+            Tokenizer::for_model(Some("gpt-4-turbo")),
+            Tokenizer::Cl100kBase
+        );
+        assert_eq!(
+            Tokenizer::for_model(Some("anthropic/claude-sonnet-4-5")),
+            Tokenizer::O200kBase
+        );
+        assert_eq!(
+            Tokenizer::for_model(Some("acme/house-model")),
+            Tokenizer::Heuristic
+        );
+        assert_eq!(Tokenizer::for_model(None), Tokenizer::O200kBase);
+    }
+
+    #[test]
+    fn bpe_counts_differ_from_heuristic_on_code() {
+        let code = "fn main() {\n    let x: Vec<u8> = vec![0; 16];\n}\n";
+        let bpe = Tokenizer::O200kBase.count(code);
+        let heuristic = Tokenizer::Heuristic.count(code);
+        assert!(bpe > 0);
+        assert_ne!(bpe, heuristic);
+        assert_eq!(count_tokens(Some("gpt-5"), "hello world"), 2);
+    }
+
+    #[test]
+    fn special_token_markers_count_as_text() {
+        let count = Tokenizer::Cl100kBase.count("<|endoftext|>");
+        assert!(count > 1);
+    }
+}
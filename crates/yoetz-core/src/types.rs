@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 use crate::media::MediaOutput;
+use crate::tokenizer::Tokenizer;
 
 /// A collection of files bundled with a prompt for LLM context.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,6 +10,58 @@ pub struct Bundle {
     pub prompt: String,
     pub files: Vec<BundleFile>,
     pub stats: BundleStats,
+    /// Unified diff of the git selection, when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+    /// Secrets replaced by placeholders before bundling.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redactions: Vec<Redaction>,
+    /// Numbered Markdown parts written when the bundle was split for
+    /// size-limited uploads, in upload order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parts: Vec<BundlePart>,
+    /// Changes since an earlier session's bundle (`--since-session`).
+    /// `files` still holds every file; renderers send only the changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta: Option<BundleDelta>,
+    /// Render file contents with a line-number gutter (`--line-numbers`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub line_numbers: bool,
+}
+
+/// How a bundle differs from the bundle of an earlier session, matched by
+/// path and compared by content. Every list is sorted by path.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleDelta {
+    pub since_session: String,
+    /// Files new since the earlier bundle, sent in full.
+    pub added: Vec<String>,
+    /// Files whose content changed.
+    pub modified: Vec<ModifiedFile>,
+    /// Files identical to the earlier copy, listed but not sent again.
+    pub unchanged: Vec<String>,
+    /// Files in the earlier bundle that this one no longer includes.
+    pub removed: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModifiedFile {
+    pub path: String,
+    /// Unified diff from the earlier content; `None` when the file is sent
+    /// in full instead (binary, outline changes, or a diff larger than the file).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+/// One file of a split bundle, named relative to the session directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundlePart {
+    /// 1-based position in upload order.
+    pub index: usize,
+    pub file_name: String,
+    pub bytes: usize,
+    /// Bundle file paths whose content (or a chunk of it) is in this part.
+    pub files: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +72,44 @@ pub struct BundleFile {
     pub truncated: bool,
     pub is_binary: bool,
     pub content: Option<String>,
+    /// How `content` represents the file.
+    #[serde(default)]
+    pub representation: Representation,
+    /// Bundle path of the file whose imports pulled this one in
+    /// (`--follow-imports`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub included_by: Option<String>,
+    /// Format-aware extractor that turned a notebook or document into
+    /// `content`; absent for files bundled as their own text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extractor: Option<Extractor>,
+}
+
+/// Form of a bundled file's content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Representation {
+    /// The file text (possibly truncated).
+    #[default]
+    Full,
+    /// A signatures-only skeleton with bodies elided.
+    Outline,
+    /// Captured stdout/stderr and exit status of an `--include-cmd` command.
+    CommandOutput,
+}
+
+/// Extractor used to bundle a file that is not plain text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Extractor {
+    /// Jupyter `.ipynb`: cells in order, outputs truncated.
+    Notebook,
+    /// Word `.docx` paragraphs.
+    Docx,
+    /// OpenDocument `.odt` paragraphs.
+    Odt,
+    /// PDF text layer.
+    Pdf,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -27,6 +118,42 @@ pub struct BundleStats {
     pub total_bytes: usize,
     pub total_chars: usize,
     pub estimated_tokens: usize,
+    /// Token budget the bundle was packed into, when packing was requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_budget: Option<usize>,
+    /// Files that matched the selection but were left out while packing.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dropped: Vec<DroppedFile>,
+    /// Tokenizer that produced `estimated_tokens`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokenizer: Option<Tokenizer>,
+}
+
+/// A secret found in bundled content and replaced by `placeholder`.
+/// Diff findings use the path `<diff>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Redaction {
+    pub path: String,
+    /// 1-based line where the secret starts.
+    pub line: usize,
+    pub rule: String,
+    pub placeholder: String,
+}
+
+/// A file excluded from a packed bundle, with the reason it did not fit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DroppedFile {
+    pub path: String,
+    pub bytes: usize,
+    pub estimated_tokens: usize,
+    pub reason: DropReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DropReason {
+    TokenBudget,
+    MaxTotalBytes,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -49,6 +176,31 @@ pub struct Usage {
     pub thoughts_tokens: Option<u64>,
     pub total_tokens: Option<u64>,
     pub cost_usd: Option<f64>,
+    /// Input tokens served from the provider's prompt cache. Included in
+    /// `input_tokens`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_tokens: Option<u64>,
+    /// Input tokens written to the provider's prompt cache. Included in
+    /// `input_tokens`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_tokens: Option<u64>,
+    /// Provider calls behind this result, recorded when a call was retried or
+    /// replaced by a fallback model.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<CallAttempt>,
+}
+
+/// One provider call made while producing a result.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct CallAttempt {
+    pub provider: Option<String>,
+    pub model: String,
+    /// Why the call failed; `None` for the call that produced the answer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Wait before the next call to the same model, in milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_delay_ms: Option<u64>,
 }
 
 impl Usage {
@@ -74,6 +226,13 @@ impl Usage {
         if let Some(cost) = other.cost_usd {
             self.cost_usd = Some(self.cost_usd.unwrap_or(0.0) + cost);
         }
+        if let Some(read) = other.cache_read_tokens {
+            self.cache_read_tokens = Some(self.cache_read_tokens.unwrap_or(0) + read);
+        }
+        if let Some(write) = other.cache_write_tokens {
+            self.cache_write_tokens = Some(self.cache_write_tokens.unwrap_or(0) + write);
+        }
+        self.attempts.extend(other.attempts.iter().cloned());
     }
 }
 
@@ -89,6 +248,9 @@ pub struct RunResult {
     pub usage: Usage,
     pub content: String,
     pub artifacts: ArtifactPaths,
+    /// Answered from the local response cache (`--cache read`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -98,6 +260,12 @@ pub struct ArtifactPaths {
     pub bundle_md: Option<String>,
     pub response_json: Option<String>,
     pub media_dir: Option<String>,
+    /// The bundle rendered in each non-Markdown `--bundle-format` in use.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bundle_rendered: Vec<String>,
+    /// Tool calls made by `ask --tools`, one JSON object per line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]